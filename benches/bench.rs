@@ -2,12 +2,17 @@
 
 extern crate test;
 extern crate byteorder;
+#[macro_use]
+extern crate speedy_derive;
 extern crate speedy;
 
-use std::io::Read;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::PathBuf;
 use test::{Bencher, black_box};
 use byteorder::{ReadBytesExt, NativeEndian};
-use speedy::{Readable, Writable};
+use speedy::{Readable, Reader, Writable, Writer, Context, Endianness, BigEndianCtx};
 
 #[bench]
 fn deserialization_manual_bytes( b: &mut Bencher ) {
@@ -99,3 +104,145 @@ fn deserialization_speedy_u64( b: &mut Bencher ) {
         deserialized
     })
 }
+
+fn big_vec_u32() -> Vec< u32 > {
+    (0..100000).collect()
+}
+
+// Big endian needs every element to be swapped on little endian machines.
+#[bench]
+fn serialization_speedy_vec_u32_runtime_endianness( b: &mut Bencher ) {
+    let original = big_vec_u32();
+    let endianness = black_box( Endianness::BigEndian );
+    b.iter( || {
+        original.write_to_vec( endianness ).unwrap()
+    })
+}
+
+#[bench]
+fn serialization_speedy_vec_u32_compile_time_endianness( b: &mut Bencher ) {
+    let original = big_vec_u32();
+    b.iter( || {
+        original.write_to_vec( BigEndianCtx ).unwrap()
+    })
+}
+
+// Writes the length and then every element separately, like the slow path does.
+struct ElementByElement< 'a >( &'a [u32] );
+
+impl< 'r, C: Context > Writable< C > for ElementByElement< 'r > {
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        writer.write_u32( self.0.len() as u32 )?;
+        for &value in self.0 {
+            writer.write_u32( value )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[bench]
+fn serialization_speedy_vec_u32_native_endianness( b: &mut Bencher ) {
+    let original = big_vec_u32();
+    b.iter( || {
+        original.write_to_vec( Endianness::default() ).unwrap()
+    })
+}
+
+#[bench]
+fn serialization_speedy_vec_u32_native_endianness_element_by_element( b: &mut Bencher ) {
+    let original = big_vec_u32();
+    b.iter( || {
+        ElementByElement( &original ).write_to_vec( Endianness::default() ).unwrap()
+    })
+}
+
+#[bench]
+fn deserialization_speedy_vec_u32_runtime_endianness( b: &mut Bencher ) {
+    let data = big_vec_u32().write_to_vec( Endianness::BigEndian ).unwrap();
+    let endianness = black_box( Endianness::BigEndian );
+    b.iter( || {
+        let deserialized: Vec< u32 > = Readable::read_from_buffer( endianness, &data ).unwrap();
+        deserialized
+    })
+}
+
+#[bench]
+fn deserialization_speedy_vec_u32_compile_time_endianness( b: &mut Bencher ) {
+    let data = big_vec_u32().write_to_vec( Endianness::BigEndian ).unwrap();
+    b.iter( || {
+        let deserialized: Vec< u32 > = Readable::read_from_buffer( BigEndianCtx, &data ).unwrap();
+        deserialized
+    })
+}
+
+#[derive(Readable, Writable)]
+struct DerivedStruct {
+    a: u8,
+    b: u16,
+    c: u32
+}
+
+fn derived_structs() -> Vec< DerivedStruct > {
+    (0..10000).map( |index| DerivedStruct { a: index as u8, b: index as u16, c: index } ).collect()
+}
+
+// Reads the same data as a `Vec< DerivedStruct >`, but without reserving any capacity up front.
+struct UnreservedDerivedStructs( Vec< DerivedStruct > );
+
+impl< 'a, C: Context > Readable< 'a, C > for UnreservedDerivedStructs {
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = reader.read_u32()? as usize;
+        let mut vec = Vec::new();
+        for _ in 0..length {
+            vec.push( reader.read_value()? );
+        }
+        Ok( UnreservedDerivedStructs( vec ) )
+    }
+}
+
+#[bench]
+fn deserialization_speedy_vec_derived_structs( b: &mut Bencher ) {
+    let data = derived_structs().write_to_vec(()).unwrap();
+    b.iter( || {
+        let deserialized: Vec< DerivedStruct > = Readable::read_from_buffer( (), &data ).unwrap();
+        deserialized
+    })
+}
+
+#[bench]
+fn deserialization_speedy_vec_derived_structs_unreserved( b: &mut Bencher ) {
+    let data = derived_structs().write_to_vec(()).unwrap();
+    b.iter( || {
+        let deserialized: UnreservedDerivedStructs = Readable::read_from_buffer( (), &data ).unwrap();
+        deserialized.0
+    })
+}
+
+fn derived_structs_file( name: &str ) -> PathBuf {
+    let path = env::temp_dir().join( name );
+    fs::write( &path, derived_structs().write_to_vec(()).unwrap() ).unwrap();
+    path
+}
+
+#[bench]
+fn deserialization_speedy_stream_unbuffered_derived_structs( b: &mut Bencher ) {
+    let path = derived_structs_file( "speedy-bench-unbuffered.bin" );
+    b.iter( || {
+        let file = File::open( &path ).unwrap();
+        let deserialized: Vec< DerivedStruct > = Readable::read_from_stream( (), file ).unwrap();
+        deserialized
+    });
+    fs::remove_file( &path ).unwrap();
+}
+
+#[bench]
+fn deserialization_speedy_stream_buffered_derived_structs( b: &mut Bencher ) {
+    let path = derived_structs_file( "speedy-bench-buffered.bin" );
+    b.iter( || {
+        let file = File::open( &path ).unwrap();
+        let deserialized: Vec< DerivedStruct > = Readable::read_from_stream_buffered( (), file ).unwrap();
+        deserialized
+    });
+    fs::remove_file( &path ).unwrap();
+}
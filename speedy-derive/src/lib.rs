@@ -1,15 +1,17 @@
 #![recursion_limit="128"]
 
+use std::cmp;
 use std::collections::HashMap;
-use std::u32;
 
 extern crate proc_macro;
+extern crate proc_macro2;
 extern crate syn;
 
 #[macro_use]
 extern crate quote;
 
 use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 
 trait IterExt: Iterator + Sized {
     fn collect_vec( self ) -> Vec< Self::Item > {
@@ -19,41 +21,383 @@ trait IterExt: Iterator + Sized {
 
 impl< T > IterExt for T where T: Iterator + Sized {}
 
-#[proc_macro_derive(Readable)]
+#[proc_macro_derive(Readable, attributes(speedy))]
 pub fn readable( input: TokenStream ) -> TokenStream {
-    let src = input.to_string();
-    let ast = syn::parse_macro_input( &src ).unwrap();
-    let tokens = impl_readable( &ast );
-    tokens.parse().unwrap()
+    let ast = syn::parse_macro_input!( input as syn::DeriveInput );
+    let tokens = impl_readable( &ast ).unwrap_or_else( to_compile_error );
+    TokenStream::from( tokens )
 }
 
-#[proc_macro_derive(Writable)]
+#[proc_macro_derive(Writable, attributes(speedy))]
 pub fn writable( input: TokenStream ) -> TokenStream {
-    let src = input.to_string();
-    let ast = syn::parse_macro_input( &src ).unwrap();
-    let tokens = impl_writable( &ast );
-    tokens.parse().unwrap()
+    let ast = syn::parse_macro_input!( input as syn::DeriveInput );
+    let tokens = impl_writable( &ast ).unwrap_or_else( to_compile_error );
+    TokenStream::from( tokens )
+}
+
+#[proc_macro_derive(Schema, attributes(speedy))]
+pub fn schema( input: TokenStream ) -> TokenStream {
+    let ast = syn::parse_macro_input!( input as syn::DeriveInput );
+    let tokens = impl_schema( &ast ).unwrap_or_else( to_compile_error );
+    TokenStream::from( tokens )
+}
+
+// `syn::Error::to_compile_error` refers to `::core`, which doesn't resolve in crates using the 2015 edition.
+fn to_compile_error( error: syn::Error ) -> TokenStream2 {
+    let errors = error.into_iter().map( |error| {
+        let message = error.to_string();
+        quote_spanned! { error.span() => compile_error! { #message } }
+    });
+
+    quote! { #(#errors)* }
+}
+
+#[derive(Default)]
+struct ContainerAttributes {
+    is_pod: bool,
+    is_transparent: bool,
+    is_type_hashed: bool,
+    tag_type: Option< TagType >,
+    is_tag_trailing: bool,
+    is_bools_packed: bool,
+    are_variants_length_prefixed: bool,
+    tag_from: Option< syn::Path >,
+    tag_to: Option< syn::Path >,
+    version: Option< u8 >,
+    end_marker: Option< (u64, TagType) >,
+    validate: Option< syn::Path >,
+    endianness: Option< TokenStream2 >,
+    read_bound: Option< Vec< syn::WherePredicate > >,
+    write_bound: Option< Vec< syn::WherePredicate > >
+}
+
+fn is_phantom_data( ty: &syn::Type ) -> bool {
+    match *ty {
+        syn::Type::Path( syn::TypePath { ref path, .. } ) => path.segments.last().map( |segment| segment.ident == "PhantomData" ).unwrap_or( false ),
+        _ => false
+    }
+}
+
+fn is_bool( ty: &syn::Type ) -> bool {
+    match *ty {
+        syn::Type::Path( syn::TypePath { qself: None, ref path } ) => path.is_ident( "bool" ),
+        _ => false
+    }
+}
+
+fn is_raw_bytes( ty: &syn::Type ) -> bool {
+    match *ty {
+        syn::Type::Path( syn::TypePath { ref path, .. } ) => path.segments.last().map( |segment| segment.ident == "RawBytes" ).unwrap_or( false ),
+        _ => false
+    }
+}
+
+// A custom bound replaces the bounds which would be generated for the fields' types;
+// it can refer to the context as `C_` and to the lifetime of `Readable` as `'a_`.
+fn parse_bound( value: &syn::LitStr ) -> syn::Result< Vec< syn::WherePredicate > > {
+    let predicates = value.parse_with( syn::punctuated::Punctuated::< syn::WherePredicate, syn::Token![,] >::parse_terminated )?;
+    Ok( predicates.into_iter().collect() )
+}
+
+fn parse_endianness( value: &syn::LitStr ) -> syn::Result< TokenStream2 > {
+    match value.value().as_str() {
+        "big" => Ok( quote! { ::speedy::Endianness::BigEndian } ),
+        "little" => Ok( quote! { ::speedy::Endianness::LittleEndian } ),
+        _ => Err( syn::Error::new_spanned( value, "expected `big` or `little`" ) )
+    }
+}
+
+fn parse_container_attributes( ast: &syn::DeriveInput ) -> syn::Result< ContainerAttributes > {
+    let mut attributes = ContainerAttributes::default();
+    for attr in ast.attrs.iter().filter( |attr| attr.path().is_ident( "speedy" ) ) {
+        attr.parse_nested_meta( |meta| {
+            if meta.path.is_ident( "pod" ) {
+                attributes.is_pod = true;
+                Ok(())
+            } else if meta.path.is_ident( "bound" ) {
+                if meta.input.peek( syn::Token![=] ) {
+                    let bound = parse_bound( &meta.value()?.parse()? )?;
+                    attributes.read_bound = Some( bound.clone() );
+                    attributes.write_bound = Some( bound );
+                    Ok(())
+                } else {
+                    meta.parse_nested_meta( |meta| {
+                        if meta.path.is_ident( "read" ) {
+                            attributes.read_bound = Some( parse_bound( &meta.value()?.parse()? )? );
+                            Ok(())
+                        } else if meta.path.is_ident( "write" ) {
+                            attributes.write_bound = Some( parse_bound( &meta.value()?.parse()? )? );
+                            Ok(())
+                        } else {
+                            Err( meta.error( "expected `read` or `write`" ) )
+                        }
+                    })
+                }
+            } else if meta.path.is_ident( "validate" ) {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.validate = Some( value.parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "transparent" ) {
+                attributes.is_transparent = true;
+                Ok(())
+            } else if meta.path.is_ident( "tag_from" ) {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.tag_from = Some( value.parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "tag_to" ) {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.tag_to = Some( value.parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "length_prefixed_variants" ) {
+                attributes.are_variants_length_prefixed = true;
+                Ok(())
+            } else if meta.path.is_ident( "pack_bools" ) {
+                attributes.is_bools_packed = true;
+                Ok(())
+            } else if meta.path.is_ident( "end_marker" ) {
+                // The marker is a byte unless its literal has a wider suffix, e.g. `0xCAFE_u16`.
+                let value: syn::LitInt = meta.value()?.parse()?;
+                let tag_type = match value.suffix() {
+                    "" => TagType::U8,
+                    suffix => TagType::from_name( suffix )
+                        .filter( |&tag_type| tag_type != TagType::VarInt )
+                        .ok_or_else( || syn::Error::new_spanned( &value, "unsupported end marker type; expected one of: `u8`, `u16`, `u32`, `u64`" ) )?
+                };
+                let marker: u64 = value.base10_parse()?;
+                if marker > tag_type.max() {
+                    return Err( syn::Error::new_spanned( &value, format!( "the end marker doesn't fit into an `{}`", tag_type.name() ) ) );
+                }
+                attributes.end_marker = Some( (marker, tag_type) );
+                Ok(())
+            } else if meta.path.is_ident( "multi_version" ) {
+                // The version which is written; unless given it's the highest one used by the fields.
+                let version = if meta.input.peek( syn::Token![=] ) {
+                    let value: syn::LitInt = meta.value()?.parse()?;
+                    value.base10_parse()?
+                } else {
+                    0
+                };
+                attributes.version = Some( version );
+                Ok(())
+            } else if meta.path.is_ident( "type_hash" ) {
+                attributes.is_type_hashed = true;
+                Ok(())
+            } else if meta.path.is_ident( "tag_type" ) {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.tag_type = Some( if value.value() == "auto" { smallest_tag_type( ast )? } else { TagType::parse( &value )? } );
+                Ok(())
+            } else if meta.path.is_ident( "tag_position" ) {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.is_tag_trailing = match value.value().as_str() {
+                    "leading" => false,
+                    "trailing" => true,
+                    _ => return Err( syn::Error::new_spanned( &value, "expected `leading` or `trailing`" ) )
+                };
+                Ok(())
+            } else if meta.path.is_ident( "endianness" ) {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                attributes.endianness = Some( parse_endianness( &value )? );
+                Ok(())
+            } else {
+                Err( meta.error( "unsupported speedy attribute" ) )
+            }
+        })?;
+    }
+
+    if attributes.tag_type.is_some() {
+        if let syn::Data::Enum( .. ) = ast.data {} else {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(tag_type)]` is only supported on enums" ) );
+        }
+    }
+
+    if attributes.is_tag_trailing {
+        if let syn::Data::Enum( .. ) = ast.data {} else {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(tag_position)]` is only supported on enums" ) );
+        }
+    }
+
+    if attributes.tag_from.is_some() || attributes.tag_to.is_some() {
+        if let syn::Data::Enum( .. ) = ast.data {} else {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(tag_from)]` and `#[speedy(tag_to)]` are only supported on enums" ) );
+        }
+
+        if attributes.tag_from.is_none() || attributes.tag_to.is_none() {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(tag_from)]` and `#[speedy(tag_to)]` must be used together" ) );
+        }
+    }
+
+    if attributes.are_variants_length_prefixed {
+        if let syn::Data::Enum( .. ) = ast.data {} else {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(length_prefixed_variants)]` is only supported on enums" ) );
+        }
+
+        if attributes.is_tag_trailing {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(length_prefixed_variants)]` can't be combined with `#[speedy(tag_position = \"trailing\")]`" ) );
+        }
+    }
+
+    // Unless explicitly overridden an enum's tag is as wide as its `#[repr]`.
+    if let syn::Data::Enum( .. ) = ast.data {
+        if attributes.tag_type.is_none() {
+            attributes.tag_type = parse_repr( ast )?.iter().filter_map( |repr| TagType::from_name( repr ) ).next();
+        }
+    }
+
+    if attributes.is_type_hashed {
+        if let syn::Data::Struct( .. ) = ast.data {} else {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(type_hash)]` is only supported on structs" ) );
+        }
+    }
+
+    if attributes.end_marker.is_some() {
+        if let syn::Data::Struct( .. ) = ast.data {} else {
+            return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(end_marker)]` is only supported on structs" ) );
+        }
+    }
+
+    if let Some( version ) = attributes.version {
+        match ast.data {
+            syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => {
+                if attributes.is_pod || attributes.is_transparent || attributes.is_type_hashed {
+                    return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(multi_version)]` can't be combined with `#[speedy(pod)]`, `#[speedy(transparent)]` or `#[speedy(type_hash)]`" ) );
+                }
+
+                let highest_version = get_fields( fields )?.iter()
+                    .flat_map( |field| field.attributes.since.into_iter().chain( field.attributes.until ) )
+                    .max()
+                    .unwrap_or( 1 );
+
+                if version == 0 {
+                    attributes.version = Some( highest_version );
+                } else if version < highest_version {
+                    return Err( syn::Error::new_spanned( &ast.ident, format!( "`#[speedy(multi_version)]` is lower than the version {} used by one of the fields", highest_version ) ) );
+                }
+            },
+            _ => return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(multi_version)]` is only supported on structs" ) )
+        }
+    } else {
+        let fields: Vec< &syn::Field > = match ast.data {
+            syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => fields.iter().collect(),
+            syn::Data::Enum( syn::DataEnum { ref variants, .. } ) => variants.iter().flat_map( |variant| variant.fields.iter() ).collect(),
+            syn::Data::Union( .. ) => Vec::new()
+        };
+
+        if let Some( field ) = get_fields( fields )?.iter().find( |field| field.attributes.since.is_some() || field.attributes.until.is_some() ) {
+            return Err( syn::Error::new_spanned( field.ty, "`#[speedy(since)]` and `#[speedy(until)]` require `#[speedy(multi_version)]`" ) );
+        }
+    }
+
+    if attributes.is_bools_packed {
+        match ast.data {
+            syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => {
+                if attributes.is_pod || attributes.is_transparent {
+                    return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(pack_bools)]` can't be combined with `#[speedy(pod)]` or `#[speedy(transparent)]`" ) );
+                }
+
+                if let Some( field ) = get_fields( fields )?.iter().find( |field| is_bool( field.ty ) && (field.attributes.default_on_eof || field.attributes.skip_if_default) ) {
+                    return Err( syn::Error::new_spanned( field.ty, "`#[speedy(pack_bools)]` can't be combined with `#[speedy(default_on_eof)]` or `#[speedy(skip_if_default)]` on a `bool` field" ) );
+                }
+            },
+            _ => return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(pack_bools)]` is only supported on structs" ) )
+        }
+    }
+
+    if attributes.is_transparent {
+        match ast.data {
+            syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => {
+                let count = fields.iter().filter( |field| !is_phantom_data( &field.ty ) ).count();
+                if count != 1 {
+                    return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(transparent)]` requires exactly one non-`PhantomData` field" ) );
+                }
+            },
+            _ => return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(transparent)]` is only supported on structs" ) )
+        }
+    }
+
+    if attributes.is_pod {
+        // The fields are copied to and from the memory of the struct as-is, so there can't be any padding between them.
+        let reprs = parse_repr( ast )?;
+        let has_repr = |name: &str| reprs.iter().any( |repr| repr == name );
+        let has_defined_layout = (has_repr( "C" ) && (has_repr( "packed" ) || has_repr( "packed(1)" ))) || has_repr( "transparent" );
+        match ast.data {
+            syn::Data::Struct( syn::DataStruct { ref fields, .. } ) if has_defined_layout => {
+                if get_fields( fields )?.iter().any( |field| field.attributes.skip || field.attributes.default_on_eof || field.attributes.skip_if_default || field.attributes.reserved.is_some() ) {
+                    return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(pod)]` structs can't have skipped, defaulted or reserved fields" ) );
+                }
+            },
+            syn::Data::Struct( .. ) => return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(pod)]` requires `#[repr(C, packed)]` or `#[repr(transparent)]`" ) ),
+            _ => return Err( syn::Error::new_spanned( &ast.ident, "`#[speedy(pod)]` is only supported on structs" ) )
+        }
+    }
+
+    Ok( attributes )
+}
+
+fn parse_repr( ast: &syn::DeriveInput ) -> syn::Result< Vec< String > > {
+    let mut reprs = Vec::new();
+    for attr in ast.attrs.iter().filter( |attr| attr.path().is_ident( "repr" ) ) {
+        attr.parse_nested_meta( |meta| {
+            if let Some( ident ) = meta.path.get_ident() {
+                reprs.push( ident.to_string() );
+            }
+
+            if meta.input.peek( syn::token::Paren ) {
+                let content;
+                syn::parenthesized!( content in meta.input );
+                if meta.path.is_ident( "packed" ) {
+                    let alignment: syn::LitInt = content.parse()?;
+                    reprs.pop();
+                    reprs.push( format!( "packed({})", alignment.base10_digits() ) );
+                }
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok( reprs )
 }
 
 enum Variant {
     Readable,
-    Writable
+    Writable,
+    Schema
 }
 
-fn common_tokens( ast: &syn::MacroInput, types: &[&syn::Ty], variant: Variant ) -> (quote::Tokens, quote::Tokens, quote::Tokens) {
+fn common_tokens( ast: &syn::DeriveInput, types: &[&syn::Type], extra_predicates: &[TokenStream2], variant: Variant ) -> (TokenStream2, TokenStream2, TokenStream2) {
     let impl_params = {
-        let lifetime_params = ast.generics.lifetimes.iter().map( |alpha| quote! { #alpha } );
-        let type_params = ast.generics.ty_params.iter().map( |ty| quote! { #ty } );
-        let params = lifetime_params.chain( type_params ).collect_vec();
+        let params = ast.generics.params.iter().map( |param| {
+            match *param {
+                syn::GenericParam::Lifetime( ref alpha ) => quote! { #alpha },
+                syn::GenericParam::Type( ref ty ) => {
+                    let mut ty = ty.clone();
+                    ty.eq_token = None;
+                    ty.default = None;
+                    quote! { #ty }
+                },
+                syn::GenericParam::Const( ref constant ) => {
+                    let mut constant = constant.clone();
+                    constant.eq_token = None;
+                    constant.default = None;
+                    quote! { #constant }
+                }
+            }
+        }).collect_vec();
+
         quote! {
             #(#params,)*
         }
     };
 
     let ty_params = {
-        let lifetime_params = ast.generics.lifetimes.iter().map( |alpha| quote! { #alpha } );
-        let type_params = ast.generics.ty_params.iter().map( |ty| { let ident = &ty.ident; quote! { #ident } } );
-        let params = lifetime_params.chain( type_params ).collect_vec();
+        let params = ast.generics.params.iter().map( |param| {
+            match *param {
+                syn::GenericParam::Lifetime( ref alpha ) => { let lifetime = &alpha.lifetime; quote! { #lifetime } },
+                syn::GenericParam::Type( ref ty ) => { let ident = &ty.ident; quote! { #ident } },
+                syn::GenericParam::Const( ref constant ) => { let ident = &constant.ident; quote! { #ident } }
+            }
+        }).collect_vec();
+
         if params.is_empty() {
             quote! {}
         } else {
@@ -64,13 +408,14 @@ fn common_tokens( ast: &syn::MacroInput, types: &[&syn::Ty], variant: Variant )
     let where_clause = {
         let constraints = types.iter().map( |&ty| {
             match variant {
-                Variant::Readable => quote! { #ty: ::speedy::Readable< C_ > },
-                Variant::Writable => quote! { #ty: ::speedy::Writable< C_ > }
+                Variant::Readable => quote! { #ty: ::speedy::Readable< 'a_, C_ > },
+                Variant::Writable => quote! { #ty: ::speedy::Writable< C_ > },
+                Variant::Schema => quote! { #ty: ::speedy::Schema }
             }
         });
 
-        let predicates = ast.generics.where_clause.predicates.iter().map( |pred| quote! { #pred } );
-        let items = constraints.chain( predicates ).collect_vec();
+        let predicates = ast.generics.where_clause.iter().flat_map( |clause| clause.predicates.iter() ).map( |pred| quote! { #pred } );
+        let items = constraints.chain( predicates ).chain( extra_predicates.iter().cloned() ).collect_vec();
         if items.is_empty() {
             quote! {}
         } else {
@@ -81,10 +426,157 @@ fn common_tokens( ast: &syn::MacroInput, types: &[&syn::Ty], variant: Variant )
     (impl_params, ty_params, where_clause)
 }
 
+#[derive(Default)]
+struct FieldAttributes {
+    skip: bool,
+    default_on_eof: bool,
+    skip_if_default: bool,
+    default_value: Option< syn::Expr >,
+    fixed_length: Option< usize >,
+    fixed_count: Option< usize >,
+    front_coded: bool,
+    split_lengths: bool,
+    remaining: bool,
+    flatten: bool,
+    since: Option< u8 >,
+    until: Option< u8 >,
+    reserved: Option< usize >
+}
+
+fn is_option( ty: &syn::Type ) -> bool {
+    match *ty {
+        syn::Type::Path( syn::TypePath { ref path, .. } ) => match path.segments.last() {
+            Some( segment ) => segment.ident == "Option",
+            None => false
+        },
+        _ => false
+    }
+}
+
+fn vec_element_type( ty: &syn::Type ) -> Option< &syn::Type > {
+    let segment = match *ty {
+        syn::Type::Path( syn::TypePath { ref path, .. } ) => path.segments.last()?,
+        _ => return None
+    };
+
+    if segment.ident != "Vec" {
+        return None;
+    }
+
+    match segment.arguments {
+        syn::PathArguments::AngleBracketed( ref arguments ) => match arguments.args.first() {
+            Some( syn::GenericArgument::Type( ty ) ) => Some( ty ),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn parse_field_attributes( field: &syn::Field ) -> syn::Result< FieldAttributes > {
+    let mut attributes = FieldAttributes::default();
+    for attr in field.attrs.iter().filter( |attr| attr.path().is_ident( "speedy" ) ) {
+        attr.parse_nested_meta( |meta| {
+            if meta.path.is_ident( "skip" ) {
+                attributes.skip = true;
+                Ok(())
+            } else if meta.path.is_ident( "default_on_eof" ) {
+                attributes.default_on_eof = true;
+                Ok(())
+            } else if meta.path.is_ident( "skip_if_default" ) {
+                attributes.skip_if_default = true;
+                Ok(())
+            } else if meta.path.is_ident( "default_value" ) {
+                attributes.default_value = Some( meta.value()?.parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "fixed_length" ) {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                attributes.fixed_length = Some( value.base10_parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "fixed_count" ) {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                attributes.fixed_count = Some( value.base10_parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "front_coded" ) {
+                attributes.front_coded = true;
+                Ok(())
+            } else if meta.path.is_ident( "split_lengths" ) {
+                attributes.split_lengths = true;
+                Ok(())
+            } else if meta.path.is_ident( "remaining" ) {
+                attributes.remaining = true;
+                Ok(())
+            } else if meta.path.is_ident( "reserved" ) {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                attributes.reserved = Some( value.base10_parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "since" ) {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                attributes.since = Some( value.base10_parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "until" ) {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                attributes.until = Some( value.base10_parse()? );
+                Ok(())
+            } else if meta.path.is_ident( "flatten" ) {
+                // Nested values are never framed, so an `Option`'s fields are always written inline,
+                // right after its presence byte (or with no presence byte at all for the `NonZero*`
+                // types, where zero means `None`); this only documents the intent.
+                attributes.flatten = true;
+                Ok(())
+            } else {
+                Err( meta.error( "unsupported speedy attribute" ) )
+            }
+        })?;
+    }
+
+    if attributes.skip as u8 + attributes.default_on_eof as u8 + attributes.skip_if_default as u8 > 1 {
+        return Err( syn::Error::new_spanned( field, "`#[speedy(skip)]`, `#[speedy(default_on_eof)]` and `#[speedy(skip_if_default)]` are mutually exclusive" ) );
+    }
+
+    if attributes.since == Some( 0 ) {
+        return Err( syn::Error::new_spanned( field, "`#[speedy(since)]` must be at least 1" ) );
+    }
+
+    if let Some( until ) = attributes.until {
+        if until <= attributes.since.unwrap_or( 1 ) {
+            return Err( syn::Error::new_spanned( field, "`#[speedy(until)]` must be higher than `#[speedy(since)]`" ) );
+        }
+    }
+
+    let is_versioned = attributes.since.is_some() || attributes.until.is_some();
+    if attributes.default_value.is_some() && !attributes.skip && !attributes.default_on_eof && !is_versioned {
+        return Err( syn::Error::new_spanned( field, "`#[speedy(default_value)]` requires either `#[speedy(skip)]`, `#[speedy(default_on_eof)]`, `#[speedy(since)]` or `#[speedy(until)]`" ) );
+    }
+
+    let custom_encodings = attributes.fixed_length.is_some() as u8 + attributes.fixed_count.is_some() as u8 + attributes.front_coded as u8 + attributes.split_lengths as u8 + attributes.remaining as u8;
+    if attributes.flatten && custom_encodings > 0 {
+        return Err( syn::Error::new_spanned( field, "`#[speedy(flatten)]` can't be combined with a custom encoding" ) );
+    }
+
+    if custom_encodings > 1 {
+        return Err( syn::Error::new_spanned( field, "`#[speedy(fixed_length)]`, `#[speedy(fixed_count)]`, `#[speedy(front_coded)]`, `#[speedy(split_lengths)]` and `#[speedy(remaining)]` are mutually exclusive" ) );
+    }
+
+    if attributes.fixed_count.is_some() && vec_element_type( &field.ty ).is_none() {
+        return Err( syn::Error::new_spanned( &field.ty, "`#[speedy(fixed_count)]` is only supported on `Vec` fields" ) );
+    }
+
+    if attributes.flatten && !is_option( &field.ty ) {
+        return Err( syn::Error::new_spanned( &field.ty, "`#[speedy(flatten)]` is only supported on `Option` fields" ) );
+    }
+
+    if attributes.remaining && vec_element_type( &field.ty ).is_none() {
+        return Err( syn::Error::new_spanned( &field.ty, "`#[speedy(remaining)]` is only supported on `Vec` fields" ) );
+    }
+
+    Ok( attributes )
+}
+
 struct Field< 'a > {
     index: usize,
     name: Option< &'a syn::Ident >,
-    ty: &'a syn::Ty
+    ty: &'a syn::Type,
+    attributes: FieldAttributes
 }
 
 impl< 'a > Field< 'a > {
@@ -92,96 +584,713 @@ impl< 'a > Field< 'a > {
         if let Some( name ) = self.name {
             name.clone()
         } else {
-            syn::Ident::from( format!( "v{}_", self.index ) )
+            syn::Ident::new( &format!( "v{}_", self.index ), Span::call_site() )
+        }
+    }
+
+    fn default_value( &self ) -> TokenStream2 {
+        match self.attributes.default_value {
+            Some( ref value ) => quote! { #value },
+            None => quote! { ::std::default::Default::default() }
         }
     }
 
-    fn name( &self ) -> syn::Ident {
-        if let Some( name ) = self.name {
-            name.clone()
-        } else {
-            syn::Ident::from( format!( "{}", self.index ) )
+    fn label( &self ) -> String {
+        match self.name {
+            Some( name ) => name.to_string(),
+            None => self.index.to_string()
+        }
+    }
+
+    // Fields with a custom encoding don't need their type to implement the traits.
+    fn has_custom_encoding( &self ) -> bool {
+        self.attributes.fixed_length.is_some() || self.attributes.fixed_count.is_some() || self.attributes.front_coded || self.attributes.split_lengths || self.attributes.remaining
+    }
+
+    // The type which needs to implement the traits for this field to be (de)serializable.
+    fn bound_type( &self ) -> Option< &'a syn::Type > {
+        if self.attributes.fixed_count.is_some() || self.attributes.remaining {
+            vec_element_type( self.ty )
+        } else if self.has_custom_encoding() {
+            None
+        } else {
+            Some( self.ty )
+        }
+    }
+
+    fn read_expr( &self ) -> TokenStream2 {
+        match self.attributes.fixed_length {
+            Some( length ) => quote! { _reader_.read_fixed_string( #length ) },
+            None if self.attributes.front_coded => quote! { _reader_.read_front_coded_strings() },
+            None if self.attributes.split_lengths => quote! { _reader_.read_split_length_strings() },
+            None if self.attributes.fixed_count.is_some() => {
+                let count = self.attributes.fixed_count.unwrap();
+                quote! { _reader_.read_fixed_count( #count ) }
+            },
+            None if self.attributes.remaining => quote! { _reader_.read_remaining_elements() },
+            None => quote! { _reader_.read_value() }
+        }
+    }
+
+    // A field marked with `#[speedy(reserved = N)]` is preceded by N reserved bytes,
+    // which are written as zeros and ignored when read.
+    fn reserved_length( &self ) -> usize {
+        self.attributes.reserved.unwrap_or( 0 )
+    }
+
+    fn skip_reserved( &self ) -> Option< TokenStream2 > {
+        self.attributes.reserved.map( |length| quote! { _reader_.skip_bytes( #length )?; } )
+    }
+
+    fn write_reserved( &self ) -> Option< TokenStream2 > {
+        self.attributes.reserved.map( |length| quote! { _writer_.write_bytes( &[0_u8; #length] )?; } )
+    }
+
+    // With `#[speedy(multi_version)]` a field is only present in the versions
+    // from `#[speedy(since)]` up to, but not including, `#[speedy(until)]`.
+    fn version_check( &self ) -> Option< TokenStream2 > {
+        match (self.attributes.since.unwrap_or( 1 ), self.attributes.until) {
+            (1, None) => None,
+            (since, None) => Some( quote! { version_ >= #since } ),
+            (1, Some( until )) => Some( quote! { version_ < #until } ),
+            (since, Some( until )) => Some( quote! { (version_ >= #since && version_ < #until) } )
+        }
+    }
+
+    // With `#[speedy(pack_bools)]` every `bool` field is stored as a single bit.
+    fn is_packed( &self, are_bools_packed: bool ) -> bool {
+        are_bools_packed && !self.attributes.skip && !self.has_custom_encoding() && is_bool( self.ty )
+    }
+
+    fn name( &self ) -> syn::Member {
+        if let Some( name ) = self.name {
+            syn::Member::Named( name.clone() )
+        } else {
+            syn::Member::Unnamed( self.index.into() )
+        }
+    }
+}
+
+/// A bitmap which precedes the fields and has a bit for some of them, in declaration order.
+///
+/// Fields marked with `#[speedy(skip_if_default)]` have a bit which is set if the field
+/// is present, and with `#[speedy(pack_bools)]` the `bool` fields are stored in one.
+struct FieldBitmap {
+    variable: syn::Ident,
+    length: usize,
+    bits: HashMap< usize, (usize, u8) >
+}
+
+impl FieldBitmap {
+    fn new< F: Fn( &Field ) -> bool >( variable: &str, fields: &[Field], filter: F ) -> Self {
+        let bits: HashMap< _, _ > = fields.iter()
+            .filter( |field| filter( field ) )
+            .enumerate()
+            .map( |(bit, field)| (field.index, (bit / 8, 1_u8 << (bit % 8))) )
+            .collect();
+
+        FieldBitmap {
+            variable: syn::Ident::new( variable, Span::call_site() ),
+            length: bits.len().div_ceil( 8 ),
+            bits
+        }
+    }
+
+    fn presence( fields: &[Field] ) -> Self {
+        FieldBitmap::new( "presence_", fields, |field| field.attributes.skip_if_default )
+    }
+
+    fn packed_bools( fields: &[Field], are_bools_packed: bool ) -> Self {
+        FieldBitmap::new( "bools_", fields, |field| field.is_packed( are_bools_packed ) )
+    }
+
+    fn is_set( &self, field: &Field ) -> Option< TokenStream2 > {
+        let variable = &self.variable;
+        self.bits.get( &field.index ).map( |&(byte, mask)| quote! { #variable[ #byte ] & #mask != 0 } )
+    }
+
+    fn set( &self, field: &Field ) -> TokenStream2 {
+        let variable = &self.variable;
+        let &(byte, mask) = &self.bits[ &field.index ];
+        quote! { #variable[ #byte ] |= #mask; }
+    }
+
+    fn read( &self ) -> Option< TokenStream2 > {
+        if self.length == 0 {
+            return None;
+        }
+
+        let variable = &self.variable;
+        let length = self.length;
+        Some( quote! {
+            let mut #variable = [0_u8; #length];
+            _reader_.read_bytes( &mut #variable )?;
+        })
+    }
+
+    fn write( &self, setters: &[TokenStream2] ) -> Option< TokenStream2 > {
+        if self.length == 0 {
+            return None;
+        }
+
+        let variable = &self.variable;
+        let length = self.length;
+        Some( quote! {
+            let mut #variable = [0_u8; #length];
+            #(#setters)*
+            for byte_ in #variable.iter() {
+                _writer_.write_u8( *byte_ )?;
+            }
+        })
+    }
+}
+
+fn get_fields< 'a, I: IntoIterator< Item = &'a syn::Field > + 'a >( fields: I ) -> syn::Result< Vec< Field< 'a > > > {
+    let mut last_field = None;
+    let fields: Vec< _ > = fields.into_iter()
+        .enumerate()
+        .map( |(index, field)| {
+            let attributes = parse_field_attributes( field )?;
+            if !attributes.skip {
+                last_field = Some( index );
+            }
+
+            Ok( Field {
+                index,
+                name: field.ident.as_ref(),
+                ty: &field.ty,
+                attributes
+            })
+        })
+        .collect::< syn::Result< _ > >()?;
+
+    // A field which consumes the rest of the input can't be followed by anything.
+    for field in &fields {
+        if field.attributes.remaining && last_field != Some( field.index ) {
+            return Err( syn::Error::new_spanned( field.ty, "`#[speedy(remaining)]` can only be used on the last field" ) );
+        }
+
+        if is_raw_bytes( field.ty ) && !field.attributes.skip && !field.has_custom_encoding() && last_field != Some( field.index ) {
+            return Err( syn::Error::new_spanned( field.ty, "`RawBytes` consumes the rest of the input, so it can only be used on the last field" ) );
+        }
+    }
+
+    Ok( fields )
+}
+
+fn minimum_bytes_needed( fields: &syn::Fields, are_bools_packed: bool ) -> syn::Result< TokenStream2 > {
+    let fields = get_fields( fields )?;
+    let bitmap_length = FieldBitmap::packed_bools( &fields, are_bools_packed ).length + FieldBitmap::presence( &fields ).length;
+    let reserved_length: usize = fields.iter()
+        .filter( |field| field.version_check().is_none() )
+        .map( |field| field.reserved_length() )
+        .sum();
+    let fixed_length = bitmap_length + reserved_length;
+    let terms = fields.iter()
+        .filter( |field| !field.attributes.skip && !field.attributes.default_on_eof && !field.attributes.skip_if_default && !field.is_packed( are_bools_packed ) && field.version_check().is_none() )
+        .map( |field| {
+            match field.attributes.fixed_length {
+                Some( length ) => quote! { #length },
+                None if field.attributes.front_coded || field.attributes.split_lengths => quote! { 4 },
+                None if field.attributes.fixed_count.is_some() => {
+                    let count = field.attributes.fixed_count.unwrap();
+                    let ty = vec_element_type( field.ty ).unwrap();
+                    quote! { #count * <#ty as ::speedy::Readable< 'a_, C_ >>::minimum_bytes_needed() }
+                },
+                None if field.attributes.remaining => quote! { 0 },
+                None => {
+                    let ty = field.ty;
+                    quote! { <#ty as ::speedy::Readable< 'a_, C_ >>::minimum_bytes_needed() }
+                }
+            }
+        });
+
+    Ok( quote! { #fixed_length #(+ #terms)* } )
+}
+
+// The statements which add the largest possible size of every field to `size_`,
+// or `None` if one of the fields doesn't have such a bound.
+fn max_bytes_len_body( fields: &syn::Fields, are_bools_packed: bool ) -> syn::Result< Option< TokenStream2 > > {
+    let fields = get_fields( fields )?;
+    let mut terms = Vec::new();
+    let reserved_length: usize = fields.iter()
+        .filter( |field| field.attributes.until.is_none() )
+        .map( |field| field.reserved_length() )
+        .sum();
+    if reserved_length != 0 {
+        terms.push( quote! { #reserved_length } );
+    }
+
+    for field in fields.iter().filter( |field| !field.attributes.skip && field.attributes.until.is_none() && !field.is_packed( are_bools_packed ) ) {
+        terms.push( match field.attributes.fixed_length {
+            Some( length ) => quote! { #length },
+            None if field.attributes.front_coded || field.attributes.split_lengths || field.attributes.remaining => return Ok( None ),
+            None if field.attributes.fixed_count.is_some() => {
+                let count = field.attributes.fixed_count.unwrap();
+                let ty = vec_element_type( field.ty ).unwrap();
+                quote! { <#ty as ::speedy::Writable< C_ >>::max_bytes_len()?.checked_mul( #count )? }
+            },
+            None => {
+                let ty = field.ty;
+                quote! { <#ty as ::speedy::Writable< C_ >>::max_bytes_len()? }
+            }
+        });
+    }
+
+    Ok( Some( quote! { #(let size_ = size_.checked_add( #terms )?;)* } ) )
+}
+
+// The serialized size of a type, if it's known from its name alone.
+fn fixed_size( ty: &syn::Type ) -> Option< usize > {
+    match *ty {
+        syn::Type::Path( syn::TypePath { qself: None, ref path } ) => {
+            let size = match path.get_ident()?.to_string().as_str() {
+                "u8" | "i8" | "bool" => 1,
+                "u16" | "i16" => 2,
+                "u32" | "i32" | "f32" => 4,
+                "u64" | "i64" | "f64" => 8,
+                _ => return None
+            };
+
+            Some( size )
+        },
+        syn::Type::Array( syn::TypeArray { ref elem, len: syn::Expr::Lit( syn::ExprLit { lit: syn::Lit::Int( ref len ), .. } ), .. } ) => {
+            Some( fixed_size( elem )? * len.base10_parse::< usize >().ok()? )
+        },
+        _ => None
+    }
+}
+
+// An enum with a trailing tag can only be read if the size of its payload
+// doesn't depend on the tag, so every variant must have the same fixed size.
+fn trailing_tag_payload_size< 'a, I: IntoIterator< Item = &'a syn::Variant > >( variants: I ) -> syn::Result< usize > {
+    let mut payload_size = None;
+    for variant in variants {
+        let mut size = 0;
+        for field in get_fields( &variant.fields )? {
+            size += field.reserved_length();
+            if field.attributes.skip {
+                continue;
+            }
+
+            let field_size = if field.has_custom_encoding() || field.attributes.default_on_eof || field.attributes.skip_if_default {
+                None
+            } else {
+                fixed_size( field.ty )
+            };
+
+            match field_size {
+                Some( field_size ) => size += field_size,
+                None => return Err( syn::Error::new_spanned( field.ty, "`#[speedy(tag_position = \"trailing\")]` requires every field to be a primitive or an array of primitives" ) )
+            }
+        }
+
+        match payload_size {
+            None => payload_size = Some( size ),
+            Some( payload_size ) if payload_size != size => {
+                let message = format!( "`#[speedy(tag_position = \"trailing\")]` requires every variant to have the same size; this one has {} bytes instead of {}", size, payload_size );
+                return Err( syn::Error::new_spanned( &variant.ident, message ) );
+            },
+            Some( _ ) => {}
+        }
+    }
+
+    Ok( payload_size.unwrap_or( 0 ) )
+}
+
+fn readable_body< 'a >( types: &mut Vec< &'a syn::Type >, type_name: &str, fields: &'a syn::Fields, are_bools_packed: bool ) -> syn::Result< (TokenStream2, TokenStream2) > {
+    let mut field_names = Vec::new();
+    let mut field_readers = Vec::new();
+    let fields = get_fields( fields )?;
+    let bools = FieldBitmap::packed_bools( &fields, are_bools_packed );
+    let presence = FieldBitmap::presence( &fields );
+    field_readers.extend( bools.read() );
+    field_readers.extend( presence.read() );
+
+    for field in fields {
+        let ident = field.var_name();
+        let name = quote! { #ident };
+        field_names.push( name );
+
+        let skip_reserved = field.skip_reserved();
+        if field.attributes.skip {
+            let default_value = field.default_value();
+            field_readers.push( quote! { #skip_reserved let #ident = #default_value; } );
+            continue;
+        }
+
+        let reader = if let Some( is_set ) = bools.is_set( &field ) {
+            quote! { #skip_reserved let #ident = #is_set; }
+        } else {
+            types.extend( field.bound_type() );
+
+            let field_name = field.label();
+            let label = format!( "{}.{}", type_name, field_name );
+            let read_expr = field.read_expr();
+            let read_expr = quote! {{
+                _reader_.begin_field( #label );
+                let value_ = #read_expr;
+                _reader_.end_field();
+                value_
+            }};
+            let field_index = field.index;
+            let reader = if field.attributes.default_on_eof {
+                let default_value = field.default_value();
+                quote! {
+                    let #ident = match #read_expr {
+                        Ok( value_ ) => value_,
+                        Err( ref error_ ) if error_.kind() == ::std::io::ErrorKind::UnexpectedEof => #default_value,
+                        Err( error_ ) => return Err( ::speedy::private::field_error( error_, #type_name, #field_name, #field_index, ::speedy::private::bytes_consumed( _start_, _reader_ ) ) )
+                    };
+                }
+            } else if let Some( is_present ) = presence.is_set( &field ) {
+                let ty = field.ty;
+                quote! {
+                    let #ident = if #is_present {
+                        #read_expr.map_err( |error_| ::speedy::private::field_error( error_, #type_name, #field_name, #field_index, ::speedy::private::bytes_consumed( _start_, _reader_ ) ) )?
+                    } else {
+                        <#ty as ::std::default::Default>::default()
+                    };
+                }
+            } else {
+                quote! {
+                    let #ident = #read_expr.map_err( |error_| ::speedy::private::field_error( error_, #type_name, #field_name, #field_index, ::speedy::private::bytes_consumed( _start_, _reader_ ) ) )?;
+                }
+            };
+            quote! { #skip_reserved #reader }
+        };
+
+        match field.version_check() {
+            Some( is_present ) => {
+                let default_value = field.default_value();
+                field_readers.push( quote! {
+                    let #ident = if #is_present {
+                        #reader
+                        #ident
+                    } else {
+                        #default_value
+                    };
+                });
+            },
+            None => field_readers.push( reader )
+        }
+    }
+
+    let body = quote! { #(#field_readers)* };
+    let initializer = quote! { #(#field_names),* };
+    Ok( (body, initializer) )
+}
+
+// Same as `readable_body`, except the fields are read into `self`.
+fn read_in_place_body( type_name: &str, fields: &syn::Fields, are_bools_packed: bool ) -> syn::Result< TokenStream2 > {
+    let mut field_readers = Vec::new();
+    let fields = get_fields( fields )?;
+    let bools = FieldBitmap::packed_bools( &fields, are_bools_packed );
+    let presence = FieldBitmap::presence( &fields );
+    field_readers.extend( bools.read() );
+    field_readers.extend( presence.read() );
+
+    for field in fields {
+        let name = field.name();
+        let skip_reserved = field.skip_reserved();
+        if field.attributes.skip {
+            let default_value = field.default_value();
+            field_readers.push( quote! { #skip_reserved self.#name = #default_value; } );
+            continue;
+        }
+
+        let reader = if let Some( is_set ) = bools.is_set( &field ) {
+            quote! { #skip_reserved self.#name = #is_set; }
+        } else {
+            let field_name = field.label();
+            let label = format!( "{}.{}", type_name, field_name );
+            let read_expr = if field.has_custom_encoding() {
+                let read_expr = field.read_expr();
+                quote! { #read_expr.map( |value_| self.#name = value_ ) }
+            } else {
+                quote! { _reader_.read_value_into( &mut self.#name ) }
+            };
+            let read_expr = quote! {{
+                _reader_.begin_field( #label );
+                let result_ = #read_expr;
+                _reader_.end_field();
+                result_
+            }};
+            let field_index = field.index;
+            let reader = if field.attributes.default_on_eof {
+                let default_value = field.default_value();
+                quote! {
+                    match #read_expr {
+                        Ok(()) => {},
+                        Err( ref error_ ) if error_.kind() == ::std::io::ErrorKind::UnexpectedEof => self.#name = #default_value,
+                        Err( error_ ) => return Err( ::speedy::private::field_error( error_, #type_name, #field_name, #field_index, ::speedy::private::bytes_consumed( _start_, _reader_ ) ) )
+                    }
+                }
+            } else if let Some( is_present ) = presence.is_set( &field ) {
+                let ty = field.ty;
+                quote! {
+                    if #is_present {
+                        #read_expr.map_err( |error_| ::speedy::private::field_error( error_, #type_name, #field_name, #field_index, ::speedy::private::bytes_consumed( _start_, _reader_ ) ) )?;
+                    } else {
+                        self.#name = <#ty as ::std::default::Default>::default();
+                    }
+                }
+            } else {
+                quote! {
+                    #read_expr.map_err( |error_| ::speedy::private::field_error( error_, #type_name, #field_name, #field_index, ::speedy::private::bytes_consumed( _start_, _reader_ ) ) )?;
+                }
+            };
+            quote! { #skip_reserved #reader }
+        };
+
+        match field.version_check() {
+            Some( is_present ) => {
+                let default_value = field.default_value();
+                field_readers.push( quote! {
+                    if #is_present {
+                        #reader
+                    } else {
+                        self.#name = #default_value;
+                    }
+                });
+            },
+            None => field_readers.push( reader )
+        }
+    }
+
+    Ok( quote! { #(#field_readers)* } )
+}
+
+fn writable_body< 'a >( types: &mut Vec< &'a syn::Type >, type_name: &str, fields: &'a syn::Fields, is_unpacked: bool, are_bools_packed: bool ) -> syn::Result< (TokenStream2, TokenStream2) > {
+    let mut field_patterns = Vec::new();
+    let mut field_writers = Vec::new();
+    let mut bool_setters = Vec::new();
+    let mut presence_setters = Vec::new();
+    let fields = get_fields( fields )?;
+    let bools = FieldBitmap::packed_bools( &fields, are_bools_packed );
+    let presence = FieldBitmap::presence( &fields );
+    for field in fields {
+        // The latest version is always written, which doesn't have the fields which were removed.
+        if field.attributes.skip || field.attributes.until.is_some() {
+            if is_unpacked {
+                field_patterns.push( match field.name {
+                    Some( name ) => quote! { #name: _ },
+                    None => quote! { _ }
+                });
+            }
+
+            if field.attributes.until.is_none() {
+                field_writers.extend( field.write_reserved() );
+            }
+
+            continue;
+        }
+
+        field_writers.extend( field.write_reserved() );
+
+        types.extend( field.bound_type() );
+
+        let reference = if is_unpacked {
+            let name = field.var_name();
+            field_patterns.push( quote! { ref #name } );
+
+            quote! { #name }
+        } else {
+            let name = field.name();
+            quote! { &self.#name }
+        };
+
+        if bools.is_set( &field ).is_some() {
+            let set = bools.set( &field );
+            bool_setters.push( quote! {
+                if *#reference {
+                    #set
+                }
+            });
+            continue;
+        }
+
+        let writer = match field.attributes.fixed_length {
+            Some( length ) => quote! { _writer_.write_fixed_string( #reference, #length )?; },
+            None if field.attributes.front_coded => quote! { _writer_.write_front_coded_strings( #reference )?; },
+            None if field.attributes.split_lengths => quote! { _writer_.write_split_length_strings( #reference )?; },
+            None if field.attributes.fixed_count.is_some() => {
+                let count = field.attributes.fixed_count.unwrap();
+                quote! { _writer_.write_fixed_count( #reference, #count )?; }
+            },
+            None if field.attributes.remaining => quote! { _writer_.write_fixed_count( #reference, (#reference).len() )?; },
+            None => quote! { _writer_.write_value( #reference )?; }
+        };
+
+        let label = format!( "{}.{}", type_name, field.label() );
+        let writer = quote! {
+            _writer_.begin_field( #label );
+            #writer
+            _writer_.end_field();
+        };
+
+        if let Some( is_present ) = presence.is_set( &field ) {
+            let ty = field.ty;
+            let set = presence.set( &field );
+            presence_setters.push( quote! {
+                if *#reference != <#ty as ::std::default::Default>::default() {
+                    #set
+                }
+            });
+            field_writers.push( quote! { if #is_present { #writer } } );
+        } else {
+            field_writers.push( writer );
+        }
+    }
+
+    let bitmap_writers = bools.write( &bool_setters ).into_iter().chain( presence.write( &presence_setters ) );
+    field_writers.splice( 0..0, bitmap_writers );
+
+    let body = quote! { #(#field_writers)* };
+    let initializer = quote! { #(#field_patterns),* };
+    Ok( (body, initializer) )
+}
+
+#[derive(Copy, Clone, PartialEq, Default)]
+enum TagType {
+    U8,
+    U16,
+    #[default]
+    U32,
+    U64,
+    // An `u64` encoded as a LEB128 varint.
+    VarInt
+}
+
+impl TagType {
+    fn from_name( name: &str ) -> Option< Self > {
+        match name {
+            "u8" => Some( TagType::U8 ),
+            "u16" => Some( TagType::U16 ),
+            "u32" => Some( TagType::U32 ),
+            "u64" => Some( TagType::U64 ),
+            "varint" => Some( TagType::VarInt ),
+            _ => None
         }
     }
-}
 
-fn get_fields< 'a >( fields: &'a [syn::Field] ) -> Box< Iterator< Item = Field< 'a > > + 'a > {
-    let iter = fields.iter()
-        .enumerate()
-        .map( |(index, field)| {
-            Field {
-                index,
-                name: field.ident.as_ref(),
-                ty: &field.ty
-            }
-        });
+    fn parse( value: &syn::LitStr ) -> syn::Result< Self > {
+        TagType::from_name( &value.value() )
+            .ok_or_else( || syn::Error::new_spanned( value, "unsupported tag type; expected one of: `u8`, `u16`, `u32`, `u64`, `varint`, `auto`" ) )
+    }
 
-    Box::new( iter )
-}
+    fn name( self ) -> &'static str {
+        match self {
+            TagType::U8 => "u8",
+            TagType::U16 => "u16",
+            TagType::U32 => "u32",
+            TagType::U64 => "u64",
+            TagType::VarInt => "varint"
+        }
+    }
 
-fn readable_body< 'a >( types: &mut Vec< &'a syn::Ty >, fields: &'a [syn::Field] ) -> (quote::Tokens, quote::Tokens) {
-    let mut field_names = Vec::new();
-    let mut field_readers = Vec::new();
-    for field in get_fields( &fields ) {
-        let ident = field.var_name();
-        types.push( field.ty );
+    fn max( self ) -> u64 {
+        match self {
+            TagType::U8 => u8::MAX as u64,
+            TagType::U16 => u16::MAX as u64,
+            TagType::U32 => u32::MAX as u64,
+            TagType::U64 | TagType::VarInt => u64::MAX
+        }
+    }
 
-        let name = quote! { #ident };
-        field_names.push( name );
-        field_readers.push( quote! { let #ident = _reader_.read_value()?; } )
+    // The type the tag has in the generated code.
+    fn ty( self ) -> syn::Ident {
+        match self {
+            TagType::VarInt => syn::Ident::new( "u64", Span::call_site() ),
+            _ => syn::Ident::new( self.name(), Span::call_site() )
+        }
     }
 
-    let body = quote! { #(#field_readers)* };
-    let initializer = quote! { #(#field_names),* };
-    (body, initializer)
-}
+    fn read( self ) -> TokenStream2 {
+        match self {
+            TagType::VarInt => quote! { _reader_.read_varint_u64()? },
+            _ => quote! { _reader_.read_value()? }
+        }
+    }
 
-fn writable_body< 'a >( types: &mut Vec< &'a syn::Ty >, fields: &'a [syn::Field], is_unpacked: bool ) -> (quote::Tokens, quote::Tokens) {
-    let mut field_names = Vec::new();
-    let mut field_writers = Vec::new();
-    for field in get_fields( &fields ) {
-        types.push( field.ty );
+    fn write( self, kind: &TokenStream2 ) -> TokenStream2 {
+        match self {
+            TagType::U8 => quote! { _writer_.write_u8( #kind )?; },
+            TagType::U16 => quote! { _writer_.write_u16( #kind )?; },
+            TagType::U32 => quote! { _writer_.write_u32( #kind )?; },
+            TagType::U64 => quote! { _writer_.write_u64( #kind )?; },
+            TagType::VarInt => quote! { _writer_.write_varint_u64( #kind )?; }
+        }
+    }
 
-        let reference = if is_unpacked {
-            let name = field.var_name();
-            field_names.push( name.clone() );
+    fn minimum_bytes_needed( self ) -> TokenStream2 {
+        match self {
+            TagType::VarInt => quote! { 1 },
+            _ => {
+                let ty = self.ty();
+                quote! { ::std::mem::size_of::< #ty >() }
+            }
+        }
+    }
 
-            quote! { #name }
-        } else {
-            let name = field.name();
-            quote! { &self.#name }
-        };
+    fn max_bytes_needed( self ) -> TokenStream2 {
+        match self {
+            // The longest LEB128 encoding of an `u64`.
+            TagType::VarInt => quote! { 10 },
+            _ => self.minimum_bytes_needed()
+        }
+    }
+}
 
-        field_writers.push( quote! { _writer_.write_value( #reference )?; } );
+// Reads the `#[speedy(end_marker)]` and fails if it's wrong; a missing marker fails when it's read.
+fn read_end_marker( (marker, tag_type): (u64, TagType) ) -> TokenStream2 {
+    let ty = tag_type.ty();
+    let marker = syn::LitInt::new( &format!( "{}_{}", marker, tag_type.name() ), Span::call_site() );
+    quote! {
+        let marker_: #ty = _reader_.read_value()?;
+        if marker_ != #marker {
+            return Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "invalid end marker" ) );
+        }
     }
+}
 
-    let body = quote! { #(#field_writers)* };
-    let initializer = quote! { #(ref #field_names),* };
-    (body, initializer)
+fn write_end_marker( (marker, tag_type): (u64, TagType) ) -> TokenStream2 {
+    let marker = syn::LitInt::new( &format!( "{}_{}", marker, tag_type.name() ), Span::call_site() );
+    tag_type.write( &quote! { #marker } )
 }
 
 struct EnumCtx {
     ident: syn::Ident,
-    previous_kind: Option< u32 >,
-    kind_to_full_name: HashMap< u32, String >
+    tag_type: TagType,
+    previous_kind: Option< u64 >,
+    kind_to_full_name: HashMap< u64, String >
 }
 
 impl EnumCtx {
-    fn new( ident: &syn::Ident ) -> Self {
+    fn new( ident: &syn::Ident, tag_type: TagType ) -> Self {
         EnumCtx {
             ident: ident.clone(),
+            tag_type,
             previous_kind: None,
             kind_to_full_name: HashMap::new()
         }
     }
 
-    fn next( &mut self, variant: &syn::Variant ) -> u32 {
+    fn tag_type( &self ) -> syn::Ident {
+        self.tag_type.ty()
+    }
+
+    fn next( &mut self, variant: &syn::Variant ) -> syn::Result< syn::LitInt > {
         let full_name = format!( "{}::{}", self.ident, variant.ident );
+        let too_big = |span: &dyn quote::ToTokens| {
+            syn::Error::new_spanned( span, format!( "Enum discriminant `{}` is too big for the `{}` tag type!", full_name, self.tag_type.name() ) )
+        };
+
         let kind = match variant.discriminant {
             None => {
                 let kind = if let Some( previous_kind ) = self.previous_kind {
-                    if previous_kind >= u32::MAX {
-                        panic!( "Enum discriminant `{}` is too big!", full_name );
+                    if previous_kind >= self.tag_type.max() {
+                        return Err( too_big( &variant.ident ) );
                     }
 
                     previous_kind + 1
@@ -192,55 +1301,144 @@ impl EnumCtx {
                 self.previous_kind = Some( kind );
                 kind
             },
-            Some( syn::ConstExpr::Lit( syn::Lit::Int( value, _ ) ) ) => {
-                if value > u32::MAX as u64 {
-                    panic!( "Enum discriminant `{}` is too big!", full_name );
+            Some( (_, syn::Expr::Lit( syn::ExprLit { lit: syn::Lit::Int( ref value ), .. } )) ) => {
+                let value = match value.base10_parse::< u64 >() {
+                    Ok( value ) => value,
+                    Err( _ ) => return Err( too_big( value ) )
+                };
+
+                if value > self.tag_type.max() {
+                    return Err( too_big( &variant.discriminant.as_ref().unwrap().1 ) );
                 }
 
-                let kind = value as u32;
-                self.previous_kind = Some( kind );
-                kind
+                self.previous_kind = Some( value );
+                value
             },
-            _ => panic!( "Enum discriminant `{}` is currently unsupported!", full_name )
+            Some( (_, ref expr) ) => return Err( syn::Error::new_spanned( expr, format!( "Enum discriminant `{}` is currently unsupported!", full_name ) ) )
         };
 
         if let Some( other_full_name ) = self.kind_to_full_name.get( &kind ) {
-            panic!( "Two discriminants with the same value of '{}': `{}`, `{}`", kind, full_name, other_full_name );
+            return Err( syn::Error::new_spanned( &variant.ident, format!( "Two discriminants with the same value of '{}': `{}`, `{}`", kind, full_name, other_full_name ) ) );
         }
 
         self.kind_to_full_name.insert( kind, full_name );
-        kind
+        Ok( syn::LitInt::new( &format!( "{}{}", kind, self.tag_type.ty() ), Span::call_site() ) )
+    }
+}
+
+// With `#[speedy(tag_type = "auto")]` the tag is as narrow as the discriminants allow.
+fn smallest_tag_type( ast: &syn::DeriveInput ) -> syn::Result< TagType > {
+    let variants = match ast.data {
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) => variants,
+        _ => return Ok( TagType::default() )
+    };
+
+    let mut ctx = EnumCtx::new( &ast.ident, TagType::U64 );
+    let mut max_kind = 0;
+    for variant in variants {
+        max_kind = cmp::max( max_kind, ctx.next( variant )?.base10_parse::< u64 >()? );
+    }
+
+    let tag_type = [TagType::U8, TagType::U16, TagType::U32].iter().cloned()
+        .find( |tag_type| max_kind <= tag_type.max() )
+        .unwrap_or( TagType::U64 );
+
+    Ok( tag_type )
+}
+
+fn struct_fields( ast: &syn::DeriveInput ) -> syn::Result< Vec< Field< '_ > > > {
+    match ast.data {
+        syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => get_fields( fields ),
+        _ => Ok( Vec::new() )
+    }
+}
+
+/// A 64-bit FNV-1a hash of the names, types and attributes of the fields
+/// which are serialized, used by `#[speedy(type_hash)]`.
+fn type_hash( fields: &[Field], are_bools_packed: bool ) -> u64 {
+    let mut description = String::new();
+    for field in fields {
+        if let Some( length ) = field.attributes.reserved {
+            description.push_str( &format!( "reserved={};", length ) );
+        }
+        if field.attributes.skip {
+            continue;
+        }
+
+        let ty = field.ty;
+        description.push_str( &format!( "{}:{}", field.label(), quote! { #ty } ) );
+        if field.is_packed( are_bools_packed ) {
+            description.push_str( ":packed" );
+        }
+        if field.attributes.default_on_eof {
+            description.push_str( ":default_on_eof" );
+        }
+        if field.attributes.skip_if_default {
+            description.push_str( ":skip_if_default" );
+        }
+        if let Some( length ) = field.attributes.fixed_length {
+            description.push_str( &format!( ":fixed_length={}", length ) );
+        }
+        if let Some( count ) = field.attributes.fixed_count {
+            description.push_str( &format!( ":fixed_count={}", count ) );
+        }
+        if field.attributes.front_coded {
+            description.push_str( ":front_coded" );
+        }
+        if field.attributes.split_lengths {
+            description.push_str( ":split_lengths" );
+        }
+        if field.attributes.remaining {
+            description.push_str( ":remaining" );
+        }
+        description.push( ';' );
     }
+
+    description.bytes().fold( 0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul( 0x100000001b3 ) )
+}
+
+fn pod_predicates( fields: &[Field] ) -> Vec< TokenStream2 > {
+    fields.iter().map( |field| {
+        let ty = field.ty;
+        quote! { #ty: ::speedy::private::Primitive }
+    }).collect_vec()
 }
 
-fn impl_readable( ast: &syn::MacroInput ) -> quote::Tokens {
+fn impl_readable( ast: &syn::DeriveInput ) -> syn::Result< TokenStream2 > {
+    let attributes = parse_container_attributes( ast )?;
     let name = &ast.ident;
     let mut types = Vec::new();
-    let reader_body = match ast.body {
-        syn::Body::Struct( syn::VariantData::Struct( ref fields ) ) => {
-            let (body, initializer) = readable_body( &mut types, fields );
+    let reader_body = match ast.data {
+        syn::Data::Struct( syn::DataStruct { fields: ref fields @ syn::Fields::Named( .. ), .. } ) => {
+            let (body, initializer) = readable_body( &mut types, &name.to_string(), fields, attributes.is_bools_packed )?;
             quote! {
                 #body
                 Ok( #name { #initializer } )
             }
         },
-        syn::Body::Struct( syn::VariantData::Tuple( ref fields ) ) => {
-            let (body, initializer) = readable_body( &mut types, fields );
+        syn::Data::Struct( syn::DataStruct { fields: ref fields @ syn::Fields::Unnamed( .. ), .. } ) => {
+            let (body, initializer) = readable_body( &mut types, &name.to_string(), fields, attributes.is_bools_packed )?;
             quote! {
                 #body
                 Ok( #name( #initializer ) )
             }
         },
-        syn::Body::Enum( ref body ) => {
-            let mut ctx = EnumCtx::new( &name );
-            let variants = body.iter()
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) if variants.is_empty() => {
+            quote! {
+                Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "enum has no variants" ) )
+            }
+        },
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) => {
+            let ast_variants = variants;
+            let mut ctx = EnumCtx::new( &name, attributes.tag_type.unwrap_or_default() );
+            let variants = variants.iter()
                 .map( |variant| {
-                    let kind = ctx.next( &variant );
+                    let kind = ctx.next( &variant )?;
                     let unqualified_ident = &variant.ident;
                     let variant_path = quote! { #name::#unqualified_ident };
-                    match variant.data {
-                        syn::VariantData::Struct( ref fields ) => {
-                            let (body, initializer) = readable_body( &mut types, fields );
+                    Ok( match variant.fields {
+                        syn::Fields::Named( .. ) => {
+                            let (body, initializer) = readable_body( &mut types, &format!( "{}::{}", name, unqualified_ident ), &variant.fields, false )?;
                             quote! {
                                 #kind => {
                                     #body
@@ -248,8 +1446,8 @@ fn impl_readable( ast: &syn::MacroInput ) -> quote::Tokens {
                                 }
                             }
                         },
-                        syn::VariantData::Tuple( ref fields ) => {
-                            let (body, initializer) = readable_body( &mut types, fields );
+                        syn::Fields::Unnamed( .. ) => {
+                            let (body, initializer) = readable_body( &mut types, &format!( "{}::{}", name, unqualified_ident ), &variant.fields, false )?;
                             quote! {
                                 #kind => {
                                     #body
@@ -257,102 +1455,677 @@ fn impl_readable( ast: &syn::MacroInput ) -> quote::Tokens {
                                 }
                             }
                         },
-                        syn::VariantData::Unit => {
+                        syn::Fields::Unit => {
                             quote! { #kind => {
                                 Ok( #variant_path )
                             }}
                         }
-                    }
+                    })
                 })
-                .collect_vec();
+                .collect::< syn::Result< Vec< _ > > >()?;
 
-            quote! {
-                let kind_: u32 = _reader_.read_value()?;
+            let tag_type = ctx.tag_type();
+            let read_tag = ctx.tag_type.read();
+            // The wire tag is mapped to one of the discriminants, which are what the variants are matched on.
+            let read_tag = match attributes.tag_from {
+                Some( ref tag_from ) => quote! {
+                    match #tag_from( #read_tag ) {
+                        Some( kind_ ) => kind_,
+                        None => return Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "invalid enum variant" ) )
+                    }
+                },
+                None => read_tag
+            };
+            let body = quote! {
                 match kind_ {
                     #(#variants),*
                     _ => Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "invalid enum variant" ) )
                 }
+            };
+
+            if attributes.are_variants_length_prefixed {
+                // Anything which is left in the variant's frame was added by a newer version of the type.
+                quote! {
+                    let kind_: #tag_type = #read_tag;
+                    let length_ = ::speedy::private::read_length_prefix( _reader_ )?;
+                    let consumed_ = ::speedy::private::bytes_consumed( _start_, _reader_ );
+                    use ::speedy::Reader as _;
+                    let _reader_ = &mut ::speedy::private::VariantFrameReader::new( _reader_, length_ );
+                    let _start_ = ::speedy::private::rebase_start( consumed_, _reader_ );
+                    let value_: ::std::io::Result< Self > = #body;
+                    let value_ = value_?;
+                    _reader_.skip_rest()?;
+                    Ok( value_ )
+                }
+            } else if attributes.is_tag_trailing {
+                let payload_size = trailing_tag_payload_size( ast_variants )?;
+                quote! {
+                    let consumed_ = ::speedy::private::bytes_consumed( _start_, _reader_ );
+                    let mut payload_ = [0_u8; #payload_size];
+                    _reader_.read_bytes( &mut payload_ )?;
+                    let kind_: #tag_type = #read_tag;
+                    use ::speedy::Reader as _;
+                    let _reader_ = &mut ::speedy::private::PayloadReader::new( _reader_, &payload_ );
+                    let _start_ = ::speedy::private::rebase_start( consumed_, _reader_ );
+                    #body
+                }
+            } else {
+                quote! {
+                    let kind_: #tag_type = #read_tag;
+                    #body
+                }
             }
         },
-        syn::Body::Struct( syn::VariantData::Unit ) => {
+        syn::Data::Struct( syn::DataStruct { fields: syn::Fields::Unit, .. } ) => {
             quote! {
                 Ok( #name )
             }
+        },
+        syn::Data::Union( syn::DataUnion { ref union_token, .. } ) => return Err( syn::Error::new_spanned( union_token, "unions are not supported" ) )
+    };
+
+    let minimum_bytes_needed = match ast.data {
+        syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => minimum_bytes_needed( fields, attributes.is_bools_packed )?,
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) if !variants.is_empty() => {
+            let tag_size = attributes.tag_type.unwrap_or_default().minimum_bytes_needed();
+            if attributes.is_tag_trailing {
+                let payload_size = trailing_tag_payload_size( variants )?;
+                quote! { #payload_size + #tag_size }
+            } else if attributes.are_variants_length_prefixed {
+                quote! { #tag_size + 4 }
+            } else {
+                tag_size
+            }
+        },
+        _ => quote! { 0 }
+    };
+
+    // Any version up to the one which would be written can be read.
+    let read_version = attributes.version.map( |version| quote! {
+        let version_ = _reader_.read_u8()?;
+        if version_ == 0 || version_ > #version {
+            return Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "unsupported version" ) );
+        }
+    });
+
+    let mut extra_predicates = Vec::new();
+    let reader_body = if attributes.is_pod {
+        extra_predicates = pod_predicates( &struct_fields( ast )? );
+        quote! {
+            if _reader_.endianness().conversion_necessary() {
+                #reader_body
+            } else {
+                let mut value_ = ::std::mem::MaybeUninit::< Self >::uninit();
+                {
+                    let bytes_ = unsafe { ::std::slice::from_raw_parts_mut( value_.as_mut_ptr() as *mut u8, ::std::mem::size_of::< Self >() ) };
+                    _reader_.read_bytes( bytes_ )?;
+                }
+                Ok( unsafe { value_.assume_init() } )
+            }
         }
+    } else {
+        reader_body
     };
 
-    let (impl_params, ty_params, where_clause) = common_tokens( ast, &types, Variant::Readable );
-    quote! {
-        impl< #impl_params C_: ::speedy::Context > ::speedy::Readable< C_ > for #name #ty_params #where_clause {
+    let minimum_bytes_needed = if attributes.is_type_hashed { quote! { 8 + #minimum_bytes_needed } } else { minimum_bytes_needed };
+    let reader_body = if attributes.is_type_hashed {
+        let hash = type_hash( &struct_fields( ast )?, attributes.is_bools_packed );
+        quote! {
+            if _reader_.read_u64()? != #hash {
+                return Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "type hash mismatch" ) );
+            }
+            #reader_body
+        }
+    } else {
+        reader_body
+    };
+
+    let minimum_bytes_needed = if attributes.version.is_some() { quote! { 1 + #minimum_bytes_needed } } else { minimum_bytes_needed };
+    let reader_body = match attributes.version {
+        Some( _ ) => quote! {
+            #read_version
+            #reader_body
+        },
+        None => reader_body
+    };
+
+    let minimum_bytes_needed = match attributes.end_marker {
+        Some( (_, tag_type) ) => {
+            let marker_size = tag_type.minimum_bytes_needed();
+            quote! { #minimum_bytes_needed + #marker_size }
+        },
+        None => minimum_bytes_needed
+    };
+    let reader_body = match attributes.end_marker {
+        Some( end_marker ) => {
+            let read_end_marker = read_end_marker( end_marker );
+            quote! {
+                let value_ = (|| -> ::std::io::Result< Self > { #reader_body })()?;
+                #read_end_marker
+                Ok( value_ )
+            }
+        },
+        None => reader_body
+    };
+
+    let reader_body = match attributes.validate {
+        Some( ref validate ) => quote! {
+            let value_ = (|| -> ::std::io::Result< Self > { #reader_body })()?;
+            match #validate( &value_ ) {
+                Ok(()) => Ok( value_ ),
+                Err( error_ ) => Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, error_ ) )
+            }
+        },
+        None => reader_body
+    };
+
+    let reader_body = match attributes.endianness {
+        Some( ref endianness ) => quote! {
+            use ::speedy::Reader as _;
+            let _reader_ = &mut ::speedy::private::EndiannessOverrideReader::new( _reader_, #endianness );
+            #reader_body
+        },
+        None => reader_body
+    };
+
+    // Everything else is simply overwritten with a freshly read value.
+    let read_in_place = match ast.data {
+        syn::Data::Struct( syn::DataStruct { fields: ref fields @ syn::Fields::Named( .. ), .. } ) |
+        syn::Data::Struct( syn::DataStruct { fields: ref fields @ syn::Fields::Unnamed( .. ), .. } ) if !attributes.is_pod => {
+            let body = read_in_place_body( &name.to_string(), fields, attributes.is_bools_packed )?;
+            let body = if attributes.is_type_hashed {
+                let hash = type_hash( &struct_fields( ast )?, attributes.is_bools_packed );
+                quote! {
+                    if _reader_.read_u64()? != #hash {
+                        return Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, "type hash mismatch" ) );
+                    }
+                    #body
+                }
+            } else {
+                body
+            };
+
+            let body = match attributes.version {
+                Some( _ ) => quote! {
+                    #read_version
+                    #body
+                },
+                None => body
+            };
+
+            let body = match attributes.end_marker {
+                Some( end_marker ) => {
+                    let read_end_marker = read_end_marker( end_marker );
+                    quote! {
+                        #body
+                        #read_end_marker
+                    }
+                },
+                None => body
+            };
+
+            let body = match attributes.validate {
+                Some( ref validate ) => quote! {
+                    #body
+                    if let Err( error_ ) = #validate( self ) {
+                        return Err( ::std::io::Error::new( ::std::io::ErrorKind::InvalidData, error_ ) );
+                    }
+                },
+                None => body
+            };
+
+            let body = match attributes.endianness {
+                Some( ref endianness ) => quote! {
+                    use ::speedy::Reader as _;
+                    let _reader_ = &mut ::speedy::private::EndiannessOverrideReader::new( _reader_, #endianness );
+                    #body
+                },
+                None => body
+            };
+
+            quote! {
+                #[inline]
+                fn read_in_place< R_: ::speedy::Reader< 'a_, C_ > >( &mut self, _reader_: &mut R_ ) -> ::std::io::Result< () > {
+                    let _start_ = ::speedy::Reader::remaining_bytes_hint( _reader_ );
+                    #body
+                    Ok(())
+                }
+            }
+        },
+        _ => quote! {}
+    };
+
+    if let Some( ref bound ) = attributes.read_bound {
+        types.clear();
+        extra_predicates.extend( bound.iter().map( |predicate| quote! { #predicate } ) );
+    }
+
+    let (impl_params, ty_params, where_clause) = common_tokens( ast, &types, &extra_predicates, Variant::Readable );
+    Ok( quote! {
+        impl< 'a_, #impl_params C_: ::speedy::Context > ::speedy::Readable< 'a_, C_ > for #name #ty_params #where_clause {
             #[inline]
-            fn read_from< R_: ::speedy::Reader< C_ > >( _reader_: &mut R_ ) -> ::std::io::Result< Self > {
+            fn read_from< R_: ::speedy::Reader< 'a_, C_ > >( _reader_: &mut R_ ) -> ::std::io::Result< Self > {
+                let _start_ = ::speedy::Reader::remaining_bytes_hint( _reader_ );
                 #reader_body
             }
+
+            #read_in_place
+
+            #[inline]
+            fn minimum_bytes_needed() -> usize {
+                #minimum_bytes_needed
+            }
         }
-    }
+    })
 }
 
-fn impl_writable( ast: &syn::MacroInput ) -> quote::Tokens {
+fn impl_writable( ast: &syn::DeriveInput ) -> syn::Result< TokenStream2 > {
+    let attributes = parse_container_attributes( ast )?;
     let name = &ast.ident;
     let mut types = Vec::new();
-    let writer_body = match ast.body {
-        syn::Body::Struct( syn::VariantData::Unit ) => {
-            quote! {}
+    let writer_body = match ast.data {
+        syn::Data::Struct( syn::DataStruct { fields: syn::Fields::Unit, .. } ) => {
+            quote! { Ok(()) }
+        },
+        syn::Data::Struct( syn::DataStruct { fields: ref fields @ syn::Fields::Named( .. ), .. } ) => {
+            let (body, _) = writable_body( &mut types, &name.to_string(), fields, false, attributes.is_bools_packed )?;
+            quote! { #body Ok(()) }
         },
-        syn::Body::Struct( syn::VariantData::Struct( ref fields ) ) => {
-            let (body, _) = writable_body( &mut types, fields, false );
-            quote! { #body }
+        syn::Data::Struct( syn::DataStruct { fields: ref fields @ syn::Fields::Unnamed( .. ), .. } ) => {
+            let (body, _) = writable_body( &mut types, &name.to_string(), fields, false, attributes.is_bools_packed )?;
+            quote! { #body Ok(()) }
         },
-        syn::Body::Struct( syn::VariantData::Tuple( ref fields ) ) => {
-            let (body, _) = writable_body( &mut types, fields, false );
-            quote! { #body }
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) if variants.is_empty() => {
+            quote! { match *self {} }
         },
-        syn::Body::Enum( ref variants ) => {
-            let mut ctx = EnumCtx::new( &name );
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) => {
+            if attributes.is_tag_trailing {
+                trailing_tag_payload_size( variants )?;
+            }
+
+            // The length of a variant's frame is only known once its fields are written,
+            // so they're written twice: once to count their bytes and once for real.
+            let frame = |body: TokenStream2| if attributes.are_variants_length_prefixed {
+                quote! {
+                    let length_ = {
+                        use ::speedy::Writer as _;
+                        let _writer_ = &mut ::speedy::private::SizeCountingWriter::new( &*_writer_ );
+                        #body
+                        _writer_.size()
+                    };
+                    ::speedy::private::write_length( _writer_, length_ )?;
+                    #body
+                }
+            } else {
+                body
+            };
+
+            let mut ctx = EnumCtx::new( &name, attributes.tag_type.unwrap_or_default() );
             let variants = variants.iter()
                 .map( |variant| {
-                    let kind = ctx.next( &variant );
+                    let kind = ctx.next( &variant )?;
+                    let tag = match attributes.tag_to {
+                        Some( ref tag_to ) => quote! { #tag_to( #kind ) },
+                        None => quote! { #kind }
+                    };
+                    let write_tag = ctx.tag_type.write( &tag );
                     let unqualified_ident = &variant.ident;
                     let variant_path = quote! { #name::#unqualified_ident };
-                    match variant.data {
-                        syn::VariantData::Struct( ref fields ) => {
-                            let (body, identifiers) = writable_body( &mut types, fields, true );
+                    Ok( match variant.fields {
+                        syn::Fields::Named( .. ) => {
+                            let (body, identifiers) = writable_body( &mut types, &format!( "{}::{}", name, unqualified_ident ), &variant.fields, true, false )?;
+                            let body = frame( body );
+                            let body = if attributes.is_tag_trailing { quote! { #body #write_tag } } else { quote! { #write_tag #body } };
                             quote! {
                                 #variant_path { #identifiers } => {
-                                    _writer_.write_value( &#kind )?;
                                     #body
                                 }
                             }
                         },
-                        syn::VariantData::Tuple( ref fields ) => {
-                            let (body, identifiers) = writable_body( &mut types, fields, true );
+                        syn::Fields::Unnamed( .. ) => {
+                            let (body, identifiers) = writable_body( &mut types, &format!( "{}::{}", name, unqualified_ident ), &variant.fields, true, false )?;
+                            let body = frame( body );
+                            let body = if attributes.is_tag_trailing { quote! { #body #write_tag } } else { quote! { #write_tag #body } };
                             quote! {
                                 #variant_path( #identifiers ) => {
-                                    _writer_.write_value( &#kind )?;
                                     #body
                                 }
                             }
                         },
-                        syn::VariantData::Unit => {
+                        syn::Fields::Unit => {
+                            let body = frame( quote! {} );
                             quote! { #variant_path => {
-                                _writer_.write_value( &#kind )?;
+                                #write_tag
+                                #body
                             }}
                         },
-                    }
+                    })
                 })
-                .collect_vec();
-            quote! { match *self { #(#variants),* } }
+                .collect::< syn::Result< Vec< _ > > >()?;
+            quote! {
+                match *self { #(#variants),* }
+                Ok(())
+            }
+        },
+        syn::Data::Union( syn::DataUnion { ref union_token, .. } ) => return Err( syn::Error::new_spanned( union_token, "unions are not supported" ) )
+    };
+
+    let mut extra_predicates = Vec::new();
+    let writer_body = if attributes.is_pod {
+        // Taking references to the fields of a packed struct is not allowed,
+        // so when swapping is necessary the fields are written one by one by value.
+        let fields = struct_fields( ast )?;
+        extra_predicates = pod_predicates( &fields );
+        let writers = fields.iter().map( |field| {
+            let ty = field.ty;
+            let name = field.name();
+            quote! {
+                <#ty as ::speedy::private::Primitive>::write_by_value( self.#name, _writer_ )?;
+            }
+        });
+
+        quote! {
+            if _writer_.endianness().conversion_necessary() {
+                #(#writers)*
+                Ok(())
+            } else {
+                let bytes_ = unsafe { ::std::slice::from_raw_parts( self as *const Self as *const u8, ::std::mem::size_of::< Self >() ) };
+                _writer_.write_bytes( bytes_ )
+            }
         }
+    } else {
+        writer_body
     };
 
-    let (impl_params, ty_params, where_clause) = common_tokens( ast, &types, Variant::Writable );
-    quote! {
+    let writer_body = if attributes.is_type_hashed {
+        let hash = type_hash( &struct_fields( ast )?, attributes.is_bools_packed );
+        quote! {
+            _writer_.write_u64( #hash )?;
+            #writer_body
+        }
+    } else {
+        writer_body
+    };
+
+    let writer_body = match attributes.version {
+        Some( version ) => quote! {
+            _writer_.write_u8( #version )?;
+            #writer_body
+        },
+        None => writer_body
+    };
+
+    let writer_body = match attributes.end_marker {
+        Some( end_marker ) => {
+            let write_end_marker = write_end_marker( end_marker );
+            quote! {
+                (|| -> ::std::io::Result< () > { #writer_body })()?;
+                #write_end_marker
+                Ok(())
+            }
+        },
+        None => writer_body
+    };
+
+    let writer_body = match attributes.endianness {
+        Some( ref endianness ) => quote! {
+            use ::speedy::Writer as _;
+            let _writer_ = &mut ::speedy::private::EndiannessOverrideWriter::new( _writer_, #endianness );
+            #writer_body
+        },
+        None => writer_body
+    };
+
+    let max_bytes_len_body = match ast.data {
+        syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => {
+            let fields_ = get_fields( fields )?;
+            let bitmap_length = FieldBitmap::packed_bools( &fields_, attributes.is_bools_packed ).length + FieldBitmap::presence( &fields_ ).length;
+            let hash_length = if attributes.is_type_hashed { 8 } else { 0 };
+            let version_length = if attributes.version.is_some() { 1 } else { 0 };
+            let initial_size = hash_length + version_length + bitmap_length;
+            let marker_size = match attributes.end_marker {
+                Some( (_, tag_type) ) => tag_type.minimum_bytes_needed(),
+                None => quote! { 0 }
+            };
+            match max_bytes_len_body( fields, attributes.is_bools_packed )? {
+                Some( body ) => quote! {
+                    let size_: usize = #initial_size + #marker_size;
+                    #body
+                    ::std::option::Option::Some( size_ )
+                },
+                None => quote! { ::std::option::Option::None }
+            }
+        },
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) if variants.is_empty() => {
+            quote! { ::std::option::Option::Some( 0 ) }
+        },
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) => {
+            let tag_size = attributes.tag_type.unwrap_or_default().max_bytes_needed();
+            let tag_size = if attributes.are_variants_length_prefixed { quote! { #tag_size + 4 } } else { tag_size };
+            let variants = variants.iter()
+                .map( |variant| max_bytes_len_body( &variant.fields, false ) )
+                .collect::< syn::Result< Option< Vec< _ > > > >()?;
+            match variants {
+                Some( variants ) => quote! {
+                    let mut max_: usize = 0;
+                    #({
+                        let size_: usize = #tag_size;
+                        #variants
+                        max_ = ::std::cmp::max( max_, size_ );
+                    })*
+                    ::std::option::Option::Some( max_ )
+                },
+                None => quote! { ::std::option::Option::None }
+            }
+        },
+        syn::Data::Union( syn::DataUnion { ref union_token, .. } ) => return Err( syn::Error::new_spanned( union_token, "unions are not supported" ) )
+    };
+
+    if let Some( ref bound ) = attributes.write_bound {
+        types.clear();
+        extra_predicates.extend( bound.iter().map( |predicate| quote! { #predicate } ) );
+    }
+
+    let (impl_params, ty_params, where_clause) = common_tokens( ast, &types, &extra_predicates, Variant::Writable );
+    Ok( quote! {
         impl< #impl_params C_: ::speedy::Context > ::speedy::Writable< C_ > for #name #ty_params #where_clause {
             #[inline]
             fn write_to< 'a_, T_: ?Sized + ::speedy::Writer< 'a_, C_ > >( &'a_ self, _writer_: &mut T_ ) -> ::std::io::Result< () > {
                 #writer_body
-                Ok(())
             }
+
+            #[inline]
+            fn max_bytes_len() -> ::std::option::Option< usize > {
+                #max_bytes_len_body
+            }
+        }
+    })
+}
+
+// The type as it was written, without the spaces `quote!` puts between every token.
+fn type_name( ty: &syn::Type ) -> String {
+    let tokens = quote! { #ty }.to_string();
+    let mut name = String::new();
+    let mut chars = tokens.chars().peekable();
+    while let Some( ch ) = chars.next() {
+        if ch == ' ' {
+            let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+            if name.chars().last().map( is_word ).unwrap_or( false ) && chars.peek().cloned().map( is_word ).unwrap_or( false ) {
+                name.push( ' ' );
+            }
+        } else {
+            name.push( ch );
+        }
+    }
+
+    name
+}
+
+fn option_tokens< T: quote::ToTokens >( value: Option< T > ) -> TokenStream2 {
+    match value {
+        Some( value ) => quote! { ::std::option::Option::Some( #value ) },
+        None => quote! { ::std::option::Option::None }
+    }
+}
+
+fn schema_fields< 'a >( types: &mut Vec< &'a syn::Type >, fields: &'a syn::Fields, are_bools_packed: bool ) -> syn::Result< TokenStream2 > {
+    let mut schema_fields = Vec::new();
+    for field in get_fields( fields )? {
+        // Reserved bytes are listed as a nameless pseudo-field in front of the field.
+        if let Some( length ) = field.attributes.reserved {
+            let ty = format!( "[u8; {}]", length );
+            let since = option_tokens( field.attributes.since );
+            let until = option_tokens( field.attributes.until );
+            schema_fields.push( quote! {
+                ::speedy::SchemaField {
+                    name: "",
+                    ty: #ty,
+                    is_skipped_if_default: false,
+                    is_packed: false,
+                    since: #since,
+                    until: #until,
+                    schema: ::speedy::SchemaNode::Reserved( #length )
+                }
+            });
+        }
+
+        if field.attributes.skip {
+            continue;
         }
+
+        schema_fields.push({
+            types.extend( field.bound_type() );
+
+            let schema = match field.attributes.fixed_length {
+                Some( length ) => quote! { ::speedy::SchemaNode::FixedLengthString( #length ) },
+                None if field.attributes.front_coded => quote! { ::speedy::SchemaNode::FrontCodedStrings },
+                None if field.attributes.split_lengths => quote! { ::speedy::SchemaNode::SplitLengthStrings },
+                None if field.attributes.fixed_count.is_some() => {
+                    let count = field.attributes.fixed_count.unwrap();
+                    let ty = vec_element_type( field.ty ).unwrap();
+                    quote! {
+                        ::speedy::SchemaNode::FixedCount {
+                            count: #count,
+                            element: ::std::boxed::Box::new( <#ty as ::speedy::Schema>::schema() )
+                        }
+                    }
+                },
+                None if field.attributes.remaining => {
+                    let ty = vec_element_type( field.ty ).unwrap();
+                    quote! { ::speedy::SchemaNode::Remaining( ::std::boxed::Box::new( <#ty as ::speedy::Schema>::schema() ) ) }
+                },
+                None => {
+                    let ty = field.ty;
+                    quote! { <#ty as ::speedy::Schema>::schema() }
+                }
+            };
+
+            let name = field.label();
+            let ty = type_name( field.ty );
+            let is_skipped_if_default = field.attributes.skip_if_default;
+            let is_packed = field.is_packed( are_bools_packed );
+            let since = option_tokens( field.attributes.since );
+            let until = option_tokens( field.attributes.until );
+            quote! {
+                ::speedy::SchemaField {
+                    name: #name,
+                    ty: #ty,
+                    is_skipped_if_default: #is_skipped_if_default,
+                    is_packed: #is_packed,
+                    since: #since,
+                    until: #until,
+                    schema: #schema
+                }
+            }
+        });
     }
+
+    Ok( quote! { vec![ #(#schema_fields),* ] } )
+}
+
+fn impl_schema( ast: &syn::DeriveInput ) -> syn::Result< TokenStream2 > {
+    let attributes = parse_container_attributes( ast )?;
+    let name = &ast.ident;
+    let name_str = name.to_string();
+    let mut types = Vec::new();
+    let body = match ast.data {
+        syn::Data::Struct( syn::DataStruct { ref fields, .. } ) if attributes.is_transparent => {
+            let field = fields.iter().find( |field| !is_phantom_data( &field.ty ) ).unwrap();
+            let ty = &field.ty;
+            types.push( ty );
+            quote! { <#ty as ::speedy::Schema>::schema() }
+        },
+        syn::Data::Struct( syn::DataStruct { ref fields, .. } ) => {
+            let fields_schema = schema_fields( &mut types, fields, attributes.is_bools_packed )?;
+            let type_hash = if attributes.is_type_hashed {
+                let hash = type_hash( &struct_fields( ast )?, attributes.is_bools_packed );
+                quote! { Some( #hash ) }
+            } else {
+                quote! { None }
+            };
+            let version = option_tokens( attributes.version );
+            let end_marker = option_tokens( attributes.end_marker.map( |(marker, tag_type)| {
+                let ty = tag_type.name();
+                quote! { (#ty, #marker) }
+            }));
+
+            quote! {
+                ::speedy::SchemaNode::Struct {
+                    name: #name_str,
+                    type_hash: #type_hash,
+                    version: #version,
+                    fields: #fields_schema,
+                    end_marker: #end_marker
+                }
+            }
+        },
+        syn::Data::Enum( syn::DataEnum { ref variants, .. } ) => {
+            let tag_type = attributes.tag_type.unwrap_or_default();
+            let mut ctx = EnumCtx::new( name, tag_type );
+            let variants = variants.iter()
+                .map( |variant| {
+                    let kind = ctx.next( variant )?;
+                    let tag = match attributes.tag_to {
+                        Some( ref tag_to ) => quote! { #tag_to( #kind ) as u64 },
+                        None => {
+                            let tag: u64 = kind.base10_parse()?;
+                            quote! { #tag }
+                        }
+                    };
+                    let variant_name = variant.ident.to_string();
+                    let fields_schema = schema_fields( &mut types, &variant.fields, false )?;
+                    Ok( quote! {
+                        ::speedy::SchemaVariant {
+                            name: #variant_name,
+                            tag: #tag,
+                            fields: #fields_schema
+                        }
+                    })
+                })
+                .collect::< syn::Result< Vec< _ > > >()?;
+
+            let tag_type = tag_type.name();
+            let is_tag_trailing = attributes.is_tag_trailing;
+            let are_variants_length_prefixed = attributes.are_variants_length_prefixed;
+            quote! {
+                ::speedy::SchemaNode::Enum {
+                    name: #name_str,
+                    tag_type: #tag_type,
+                    is_tag_trailing: #is_tag_trailing,
+                    are_variants_length_prefixed: #are_variants_length_prefixed,
+                    variants: vec![ #(#variants),* ]
+                }
+            }
+        },
+        syn::Data::Union( syn::DataUnion { ref union_token, .. } ) => return Err( syn::Error::new_spanned( union_token, "unions are not supported" ) )
+    };
+
+    let (impl_params, ty_params, where_clause) = common_tokens( ast, &types, &[], Variant::Schema );
+    Ok( quote! {
+        impl< #impl_params > ::speedy::Schema for #name #ty_params #where_clause {
+            fn schema() -> ::speedy::SchemaNode {
+                #body
+            }
+        }
+    })
 }
@@ -0,0 +1,122 @@
+//! Reading of data which was serialized with `bincode`'s default configuration,
+//! to make migrating existing data to speedy easier.
+//!
+//! Only the types which both formats support are covered, that is the primitives,
+//! `String`, `Vec` and `Option`. The behavior matches `bincode::deserialize`:
+//! everything is little-endian and lengths are stored as `u64`s.
+//!
+//! ```rust
+//! use speedy::bincode_compat::read_bincode_from_buffer;
+//!
+//! // `bincode::serialize( &vec![ 1_u16, 2 ] )`
+//! let data = [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0];
+//! let values: Vec< u16 > = read_bincode_from_buffer( &data ).unwrap();
+//! assert_eq!( values, [1, 2] );
+//! ```
+
+use std::io;
+
+use reader::Reader;
+use buffer_reader::BufferReader;
+use context::Context;
+use endianness::Endianness;
+use readable_impl::{preallocated_capacity, read_elements};
+
+/// Types which can be read from data serialized with `bincode`.
+///
+/// The multi-byte values are read in the reader's endianness,
+/// which should be little-endian to match `bincode`.
+pub trait BincodeReadable: Sized {
+    fn read_bincode_from< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self >;
+}
+
+/// Deserializes a value which was serialized with `bincode::serialize`.
+///
+/// Just like `bincode::deserialize` this ignores any bytes which follow the value.
+#[inline]
+pub fn read_bincode_from_buffer< T: BincodeReadable >( buffer: &[u8] ) -> io::Result< T > {
+    let mut reader = BufferReader::new( Endianness::LittleEndian, buffer );
+    T::read_bincode_from( &mut reader )
+}
+
+#[inline]
+fn read_length< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< usize > {
+    let length = reader.read_u64()?;
+    if length > usize::MAX as u64 {
+        return Err( io::Error::new( io::ErrorKind::InvalidData, "length prefix doesn't fit into an usize" ) );
+    }
+
+    Ok( length as usize )
+}
+
+macro_rules! impl_for_primitive {
+    ($type:ty, $read_name:ident) => {
+        impl BincodeReadable for $type {
+            #[inline]
+            fn read_bincode_from< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+                reader.$read_name()
+            }
+        }
+    }
+}
+
+impl_for_primitive!( u8, read_u8 );
+impl_for_primitive!( u16, read_u16 );
+impl_for_primitive!( u32, read_u32 );
+impl_for_primitive!( u64, read_u64 );
+impl_for_primitive!( i8, read_i8 );
+impl_for_primitive!( i16, read_i16 );
+impl_for_primitive!( i32, read_i32 );
+impl_for_primitive!( i64, read_i64 );
+impl_for_primitive!( f32, read_f32 );
+impl_for_primitive!( f64, read_f64 );
+
+impl BincodeReadable for bool {
+    #[inline]
+    fn read_bincode_from< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        match reader.read_u8()? {
+            0 => Ok( false ),
+            1 => Ok( true ),
+            _ => Err( io::Error::new( io::ErrorKind::InvalidData, "invalid bool" ) )
+        }
+    }
+}
+
+impl BincodeReadable for String {
+    #[inline]
+    fn read_bincode_from< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length( reader )?;
+        if let Some( remaining ) = reader.remaining_bytes_hint() {
+            if length > remaining {
+                return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "length prefix is bigger than the remaining input" ) );
+            }
+        }
+
+        let bytes = read_elements( reader, length )?;
+        String::from_utf8( bytes ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )
+    }
+}
+
+impl< T: BincodeReadable > BincodeReadable for Vec< T > {
+    #[inline]
+    fn read_bincode_from< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length( reader )?;
+        let mut vec = Vec::with_capacity( preallocated_capacity::< T >( length ) );
+        for _ in 0..length {
+            vec.push( T::read_bincode_from( reader )? );
+        }
+
+        Ok( vec )
+    }
+}
+
+impl< T: BincodeReadable > BincodeReadable for Option< T > {
+    #[inline]
+    fn read_bincode_from< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        match reader.read_u8()? {
+            0 => Ok( None ),
+            1 => Ok( Some( T::read_bincode_from( reader )? ) ),
+            _ => Err( io::Error::new( io::ErrorKind::InvalidData, "invalid option discriminant" ) )
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::io;
+use std::cmp;
+
+use reader::Reader;
+use context::Context;
+
+/// Reads values which aren't a whole number of bytes wide
+/// on top of another reader, most significant bit first.
+///
+/// ```rust
+/// use speedy::{BitReader, BufferReader, Endianness, Reader};
+///
+/// let data = [0b101_00011, 0xff];
+/// let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+/// {
+///     let mut bits = BitReader::new( &mut reader );
+///     assert_eq!( bits.read_bits( 3 ).unwrap(), 5 );
+///     assert_eq!( bits.read_bits( 5 ).unwrap(), 3 );
+/// }
+/// assert_eq!( reader.read_u8().unwrap(), 0xff );
+/// ```
+pub struct BitReader< 'r, R: 'r > {
+    reader: &'r mut R,
+    current: u8,
+    bits_left: u32
+}
+
+impl< 'r, R > BitReader< 'r, R > {
+    #[inline]
+    pub fn new( reader: &'r mut R ) -> Self {
+        BitReader {
+            reader,
+            current: 0,
+            bits_left: 0
+        }
+    }
+
+    /// Reads the next `count` bits (at most 64) as an unsigned integer.
+    pub fn read_bits< 'a, C: Context >( &mut self, count: u32 ) -> io::Result< u64 > where R: Reader< 'a, C > {
+        if count > 64 {
+            return Err( io::Error::new( io::ErrorKind::InvalidInput, "cannot read more than 64 bits at a time" ) );
+        }
+
+        let mut value: u64 = 0;
+        let mut remaining = count;
+        while remaining > 0 {
+            if self.bits_left == 0 {
+                self.current = self.reader.read_u8()?;
+                self.bits_left = 8;
+            }
+
+            let chunk = cmp::min( remaining, self.bits_left );
+            let bits = (self.current >> (self.bits_left - chunk)) as u64 & ((1 << chunk) - 1);
+            value = (value << chunk) | bits;
+
+            self.bits_left -= chunk;
+            remaining -= chunk;
+        }
+
+        Ok( value )
+    }
+
+    /// Discards whatever is left of the partially read byte, if any,
+    /// so that the next read starts on a byte boundary.
+    #[inline]
+    pub fn align( &mut self ) {
+        self.bits_left = 0;
+    }
+
+    /// Byte-aligns and returns the underlying reader.
+    #[inline]
+    pub fn into_inner( self ) -> &'r mut R {
+        self.reader
+    }
+}
@@ -0,0 +1,94 @@
+use std::io;
+use std::borrow::Cow;
+
+use reader::Reader;
+use context::Context;
+
+/// A reader over an in-memory buffer.
+///
+/// This is what `Readable::read_from_buffer` uses internally, but it can also
+/// be used directly to hand-roll a format on top of speedy's primitives.
+///
+/// ```rust
+/// use speedy::{BufferReader, Endianness, Reader};
+///
+/// let data = [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0];
+/// let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+/// let a = reader.read_u32().unwrap();
+/// let b = reader.read_u32().unwrap();
+/// let c = reader.read_u32().unwrap();
+/// assert_eq!( (a, b, c), (1, 2, 3) );
+/// assert!( reader.remaining().is_empty() );
+/// ```
+pub struct BufferReader< 'a, C: Context > {
+    context: C,
+    buffer: &'a [u8]
+}
+
+impl< 'a, C: Context > BufferReader< 'a, C > {
+    #[inline]
+    pub fn new( context: C, buffer: &'a [u8] ) -> Self {
+        BufferReader { context, buffer }
+    }
+
+    /// Returns the part of the buffer which wasn't read yet.
+    #[inline]
+    pub fn remaining( &self ) -> &'a [u8] {
+        self.buffer
+    }
+
+    #[inline]
+    fn take( &mut self, length: usize ) -> io::Result< &'a [u8] > {
+        if length > self.buffer.len() {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of buffer" ) );
+        }
+
+        let (head, tail) = self.buffer.split_at( length );
+        self.buffer = tail;
+        Ok( head )
+    }
+}
+
+impl< 'a, C: Context > Reader< 'a, C > for BufferReader< 'a, C > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        output.copy_from_slice( self.take( output.len() )? );
+        Ok(())
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        self.take( length ).map( Cow::Borrowed )
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        if output.len() > self.buffer.len() {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of buffer" ) );
+        }
+
+        output.copy_from_slice( &self.buffer[ ..output.len() ] );
+        Ok(())
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        Some( self.buffer.len() )
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        true
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let length = self.buffer.len();
+        self.take( length ).map( |bytes| bytes.to_vec() )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        &self.context
+    }
+}
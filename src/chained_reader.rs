@@ -0,0 +1,131 @@
+use std::io;
+use std::borrow::Cow;
+
+use reader::Reader;
+use context::Context;
+
+/// A reader over several in-memory buffers which are read one after another
+/// as if they were a single buffer, e.g. for a format which is split across
+/// a header file and a data file.
+///
+/// Just like with a `BufferReader` the values can borrow from the buffers,
+/// however only when the borrowed data doesn't straddle two of them.
+///
+/// ```rust
+/// use speedy::{ChainedReader, Endianness, Reader};
+///
+/// let header = [1, 0, 0];
+/// let data = [0, 2, 0, 0, 0];
+/// let mut reader = ChainedReader::new( Endianness::LittleEndian, &[&header[..], &data[..]] );
+/// let a = reader.read_u32().unwrap();
+/// let b = reader.read_u32().unwrap();
+/// assert_eq!( (a, b), (1, 2) );
+/// ```
+pub struct ChainedReader< 'a, C: Context > {
+    context: C,
+    buffers: Vec< &'a [u8] >,
+    index: usize
+}
+
+impl< 'a, C: Context > ChainedReader< 'a, C > {
+    #[inline]
+    pub fn new( context: C, buffers: &[&'a [u8]] ) -> Self {
+        ChainedReader {
+            context,
+            buffers: buffers.iter().cloned().filter( |buffer| !buffer.is_empty() ).collect(),
+            index: 0
+        }
+    }
+
+    #[inline]
+    fn remaining_length( &self ) -> usize {
+        self.buffers[ self.index.. ].iter().map( |buffer| buffer.len() ).sum()
+    }
+
+    // Copies the next `output.len()` bytes without consuming them, and returns
+    // the position right after them.
+    fn copy_to( &self, output: &mut [u8] ) -> io::Result< (usize, &'a [u8]) > {
+        if output.len() > self.remaining_length() {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of buffer" ) );
+        }
+
+        let mut index = self.index;
+        let mut offset = 0;
+        while offset < output.len() {
+            let buffer = self.buffers[ index ];
+            let length = buffer.len().min( output.len() - offset );
+            output[ offset..offset + length ].copy_from_slice( &buffer[ ..length ] );
+            offset += length;
+            if length < buffer.len() {
+                return Ok( (index, &buffer[ length.. ]) );
+            }
+
+            index += 1;
+        }
+
+        Ok( (index, &[]) )
+    }
+
+    #[inline]
+    fn advance_to( &mut self, (index, rest): (usize, &'a [u8]) ) {
+        self.index = index;
+        if !rest.is_empty() {
+            self.buffers[ index ] = rest;
+        }
+    }
+}
+
+impl< 'a, C: Context > Reader< 'a, C > for ChainedReader< 'a, C > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        let position = self.copy_to( output )?;
+        self.advance_to( position );
+        Ok(())
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        if length == 0 {
+            return Ok( Cow::Borrowed( &[] ) );
+        }
+
+        if let Some( &buffer ) = self.buffers.get( self.index ) {
+            if length <= buffer.len() {
+                let (head, tail) = buffer.split_at( length );
+                self.advance_to( (self.index + tail.is_empty() as usize, tail) );
+                return Ok( Cow::Borrowed( head ) );
+            }
+        }
+
+        let mut output = vec![ 0; length ];
+        self.read_bytes( &mut output )?;
+        Ok( Cow::Owned( output ) )
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.copy_to( output ).map( |_| () )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        Some( self.remaining_length() )
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        true
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let output = self.buffers[ self.index.. ].concat();
+        self.index = self.buffers.len();
+        Ok( output )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        &self.context
+    }
+}
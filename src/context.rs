@@ -7,6 +7,22 @@ use endianness::Endianness;
 
 pub trait Context {
     fn endianness( &self ) -> Endianness;
+
+    /// Whether a `bool` must be stored as either `0` or `1`.
+    ///
+    /// By default any non-zero byte is read as `true`; when this returns `true`
+    /// anything other than `0` and `1` is rejected with `io::ErrorKind::InvalidData`.
+    #[inline]
+    fn strict_bool( &self ) -> bool {
+        false
+    }
+
+    /// The endianness of the `u32` length prefixes of collections and framed messages,
+    /// if it's different from the endianness of everything else.
+    #[inline]
+    fn length_endianness( &self ) -> Option< Endianness > {
+        None
+    }
 }
 
 impl Context for Endianness {
@@ -30,6 +46,31 @@ impl Context for BigEndian {
     }
 }
 
+/// A context which is always little endian.
+///
+/// Unlike `Endianness::LittleEndian` this is known at compile time,
+/// so there's no need to check the endianness for every value.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct LittleEndianCtx;
+
+/// A context which is always big endian; see `LittleEndianCtx`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BigEndianCtx;
+
+impl Context for LittleEndianCtx {
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        Endianness::LittleEndian
+    }
+}
+
+impl Context for BigEndianCtx {
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        Endianness::BigEndian
+    }
+}
+
 impl Context for () {
     #[inline]
     fn endianness( &self ) -> Endianness {
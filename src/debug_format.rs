@@ -0,0 +1,154 @@
+use std::io;
+use std::borrow::Cow;
+
+use reader::Reader;
+use writer::Writer;
+use context::Context;
+use buffer_reader::BufferReader;
+use vec_writer::VecWriter;
+
+// These are used by `Writable::write_to_vec_debug` and `Readable::read_from_buffer_debug`;
+// every field of a derived type is preceded by its length-prefixed label, e.g. `Struct.field`.
+
+pub struct DebugWriter< C: Context > {
+    writer: VecWriter< C >
+}
+
+impl< C: Context > DebugWriter< C > {
+    #[inline]
+    pub fn new( context: C ) -> Self {
+        DebugWriter {
+            writer: VecWriter::new( context )
+        }
+    }
+
+    #[inline]
+    pub fn into_inner( self ) -> Vec< u8 > {
+        self.writer.into_inner()
+    }
+}
+
+impl< 'a, C: Context > Writer< 'a, C > for DebugWriter< C > {
+    #[inline]
+    fn write_bytes( &mut self, slice: &'a [u8] ) -> io::Result< () > {
+        self.writer.write_bytes( slice )
+    }
+
+    #[inline]
+    fn write_owned_bytes( &mut self, vec: Vec< u8 > ) -> io::Result< () > {
+        self.writer.write_owned_bytes( vec )
+    }
+
+    #[inline]
+    fn write_u8( &mut self, value: u8 ) -> io::Result< () > {
+        self.writer.write_u8( value )
+    }
+
+    #[inline]
+    fn write_u16( &mut self, value: u16 ) -> io::Result< () > {
+        self.writer.write_u16( value )
+    }
+
+    #[inline]
+    fn write_u32( &mut self, value: u32 ) -> io::Result< () > {
+        self.writer.write_u32( value )
+    }
+
+    #[inline]
+    fn write_u64( &mut self, value: u64 ) -> io::Result< () > {
+        self.writer.write_u64( value )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.writer.context()
+    }
+
+    fn begin_field( &mut self, label: &'static str ) {
+        // Writing into a `Vec` can't fail.
+        let _ = self.writer.write_u32( label.len() as u32 );
+        let _ = self.writer.write_bytes( label.as_bytes() );
+    }
+}
+
+pub struct DebugReader< 'a, C: Context > {
+    reader: BufferReader< 'a, C >,
+    error: Option< io::Error >
+}
+
+impl< 'a, C: Context > DebugReader< 'a, C > {
+    #[inline]
+    pub fn new( context: C, buffer: &'a [u8] ) -> Self {
+        DebugReader {
+            reader: BufferReader::new( context, buffer ),
+            error: None
+        }
+    }
+
+    /// Returns the error from the last label which didn't match, if any.
+    #[inline]
+    pub fn check( &mut self ) -> io::Result< () > {
+        match self.error.take() {
+            Some( error ) => Err( error ),
+            None => Ok(())
+        }
+    }
+
+    fn read_label( &mut self, label: &'static str ) -> io::Result< () > {
+        let length = self.reader.read_u32()? as usize;
+        let bytes = self.reader.read_cow_bytes( length )?;
+        if bytes != label.as_bytes() {
+            let message = format!( "expected field `{}`, found `{}`", label, String::from_utf8_lossy( &bytes ) );
+            return Err( io::Error::new( io::ErrorKind::InvalidData, message ) );
+        }
+
+        Ok(())
+    }
+}
+
+impl< 'a, C: Context > Reader< 'a, C > for DebugReader< 'a, C > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.check()?;
+        self.reader.read_bytes( output )
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        self.check()?;
+        self.reader.read_cow_bytes( length )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        self.reader.remaining_bytes_hint()
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.reader.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.check()?;
+        self.reader.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        self.check()?;
+        self.reader.read_remaining()
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+
+    fn begin_field( &mut self, label: &'static str ) {
+        if self.error.is_none() {
+            self.error = self.read_label( label ).err();
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Integers which are always serialized with the same endianness,
+//! regardless of the endianness of the context, e.g. for embedding
+//! fields of a foreign format.
+//!
+//! ```rust
+//! use speedy::{Endianness, Writable};
+//! use speedy::endian::U32Be;
+//!
+//! let bytes = U32Be( 0x01020304 ).write_to_vec( Endianness::LittleEndian ).unwrap();
+//! assert_eq!( bytes, [1, 2, 3, 4] );
+//! ```
+
+use std::io;
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+use endianness::Endianness;
+use schema::{Schema, SchemaNode};
+
+macro_rules! impl_wrapper {
+    ($(#[$attr:meta])* $name:ident, $type:ty, $endianness:expr, $schema_name:expr, $read_name:ident, $write_name:ident) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+        pub struct $name( pub $type );
+
+        impl $name {
+            #[inline]
+            pub fn get( self ) -> $type {
+                self.0
+            }
+        }
+
+        impl From< $type > for $name {
+            #[inline]
+            fn from( value: $type ) -> Self {
+                $name( value )
+            }
+        }
+
+        impl From< $name > for $type {
+            #[inline]
+            fn from( value: $name ) -> Self {
+                value.0
+            }
+        }
+
+        impl< 'a, C: Context > Readable< 'a, C > for $name {
+            #[inline]
+            fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+                let value = reader.$read_name()?;
+                if reader.endianness() != $endianness {
+                    return Ok( $name( value.swap_bytes() ) );
+                }
+
+                Ok( $name( value ) )
+            }
+
+            #[inline]
+            fn minimum_bytes_needed() -> usize {
+                ::std::mem::size_of::< $type >()
+            }
+        }
+
+        impl< C: Context > Writable< C > for $name {
+            #[inline]
+            fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+                let value = if writer.endianness() != $endianness { self.0.swap_bytes() } else { self.0 };
+                writer.$write_name( value )
+            }
+
+            #[inline]
+            fn bytes_needed( &self ) -> usize {
+                ::std::mem::size_of::< $type >()
+            }
+
+            #[inline]
+            fn max_bytes_len() -> Option< usize > {
+                Some( ::std::mem::size_of::< $type >() )
+            }
+        }
+
+        impl Schema for $name {
+            #[inline]
+            fn schema() -> SchemaNode {
+                SchemaNode::Primitive( $schema_name )
+            }
+        }
+    }
+}
+
+impl_wrapper!( /// An `u16` which is always little-endian.
+    U16Le, u16, Endianness::LittleEndian, "u16le", read_u16, write_u16 );
+impl_wrapper!( /// An `u16` which is always big-endian.
+    U16Be, u16, Endianness::BigEndian, "u16be", read_u16, write_u16 );
+impl_wrapper!( /// An `u32` which is always little-endian.
+    U32Le, u32, Endianness::LittleEndian, "u32le", read_u32, write_u32 );
+impl_wrapper!( /// An `u32` which is always big-endian.
+    U32Be, u32, Endianness::BigEndian, "u32be", read_u32, write_u32 );
+impl_wrapper!( /// An `u64` which is always little-endian.
+    U64Le, u64, Endianness::LittleEndian, "u64le", read_u64, write_u64 );
+impl_wrapper!( /// An `u64` which is always big-endian.
+    U64Be, u64, Endianness::BigEndian, "u64be", read_u64, write_u64 );
+impl_wrapper!( /// An `i16` which is always little-endian.
+    I16Le, i16, Endianness::LittleEndian, "i16le", read_i16, write_i16 );
+impl_wrapper!( /// An `i16` which is always big-endian.
+    I16Be, i16, Endianness::BigEndian, "i16be", read_i16, write_i16 );
+impl_wrapper!( /// An `i32` which is always little-endian.
+    I32Le, i32, Endianness::LittleEndian, "i32le", read_i32, write_i32 );
+impl_wrapper!( /// An `i32` which is always big-endian.
+    I32Be, i32, Endianness::BigEndian, "i32be", read_i32, write_i32 );
+impl_wrapper!( /// An `i64` which is always little-endian.
+    I64Le, i64, Endianness::LittleEndian, "i64le", read_i64, write_i64 );
+impl_wrapper!( /// An `i64` which is always big-endian.
+    I64Be, i64, Endianness::BigEndian, "i64be", read_i64, write_i64 );
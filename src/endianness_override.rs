@@ -0,0 +1,171 @@
+use std::io;
+use std::borrow::Cow;
+
+use reader::Reader;
+use writer::Writer;
+use context::Context;
+use endianness::Endianness;
+
+// These are used by the derived impls to serialize a type with
+// a fixed endianness, regardless of what the context says.
+
+#[doc(hidden)]
+pub struct EndiannessOverrideReader< 'r, R: 'r > {
+    reader: &'r mut R,
+    endianness: Endianness
+}
+
+impl< 'r, R > EndiannessOverrideReader< 'r, R > {
+    #[inline]
+    pub fn new( reader: &'r mut R, endianness: Endianness ) -> Self {
+        EndiannessOverrideReader { reader, endianness }
+    }
+}
+
+impl< 'a, 'r, C: Context, R: Reader< 'a, C > > Reader< 'a, C > for EndiannessOverrideReader< 'r, R > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.read_bytes( output )
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        self.reader.read_cow_bytes( length )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        self.reader.remaining_bytes_hint()
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.reader.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        self.reader.read_remaining()
+    }
+
+    #[cfg(feature = "bytes")]
+    #[inline]
+    fn speedy_read_shared_bytes( &mut self, length: usize ) -> io::Result< ::bytes::Bytes > {
+        self.reader.speedy_read_shared_bytes( length )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.reader.begin_field( label )
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.reader.end_field()
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.endianness
+    }
+
+    // Length prefixes follow the overridden endianness unless they have one of their own.
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        if self.reader.length_endianness() == self.reader.endianness() {
+            self.endianness
+        } else {
+            self.reader.length_endianness()
+        }
+    }
+}
+
+// The inner writer always writes with its own endianness,
+// so if it's not the one we want the values are swapped beforehand.
+#[doc(hidden)]
+pub struct EndiannessOverrideWriter< 'w, W: ?Sized + 'w > {
+    writer: &'w mut W,
+    endianness: Endianness
+}
+
+impl< 'w, W: ?Sized > EndiannessOverrideWriter< 'w, W > {
+    #[inline]
+    pub fn new( writer: &'w mut W, endianness: Endianness ) -> Self {
+        EndiannessOverrideWriter { writer, endianness }
+    }
+}
+
+impl< 'a, 'w, C: Context, W: ?Sized + Writer< 'a, C > > Writer< 'a, C > for EndiannessOverrideWriter< 'w, W > {
+    #[inline]
+    fn write_bytes( &mut self, slice: &'a [u8] ) -> io::Result< () > {
+        self.writer.write_bytes( slice )
+    }
+
+    #[inline]
+    fn write_owned_bytes( &mut self, vec: Vec< u8 > ) -> io::Result< () > {
+        self.writer.write_owned_bytes( vec )
+    }
+
+    #[inline]
+    fn write_u8( &mut self, value: u8 ) -> io::Result< () > {
+        self.writer.write_u8( value )
+    }
+
+    #[inline]
+    fn write_u16( &mut self, value: u16 ) -> io::Result< () > {
+        let value = if self.writer.endianness() != self.endianness { value.swap_bytes() } else { value };
+        self.writer.write_u16( value )
+    }
+
+    #[inline]
+    fn write_u32( &mut self, value: u32 ) -> io::Result< () > {
+        let value = if self.writer.endianness() != self.endianness { value.swap_bytes() } else { value };
+        self.writer.write_u32( value )
+    }
+
+    #[inline]
+    fn write_u64( &mut self, value: u64 ) -> io::Result< () > {
+        let value = if self.writer.endianness() != self.endianness { value.swap_bytes() } else { value };
+        self.writer.write_u64( value )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.writer.context()
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.writer.begin_field( label )
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.writer.end_field()
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.endianness
+    }
+
+    // Length prefixes follow the overridden endianness unless they have one of their own.
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        if self.writer.length_endianness() == self.writer.endianness() {
+            self.endianness
+        } else {
+            self.writer.length_endianness()
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use reader::Reader;
+use context::Context;
+
+/// The payload of an `io::Error` returned when a field of a type
+/// with derived `Readable` fails to deserialize.
+///
+/// The error has the same `io::ErrorKind` as the original error,
+/// which can be retrieved with `source`:
+///
+/// ```rust
+/// # use std::io;
+/// # use speedy::FieldError;
+/// fn failed_field( error: &io::Error ) -> Option< &str > {
+///     error.get_ref()
+///         .and_then( |error| error.downcast_ref::< FieldError >() )
+///         .map( |error| error.field_name() )
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FieldError {
+    type_name: &'static str,
+    field_name: &'static str,
+    field_index: usize,
+    bytes_consumed: Option< usize >,
+    source: io::Error
+}
+
+impl FieldError {
+    /// The name of the type (or of the enum variant) the field belongs to.
+    #[inline]
+    pub fn type_name( &self ) -> &'static str {
+        self.type_name
+    }
+
+    /// The name of the field; for tuple fields this is their index.
+    #[inline]
+    pub fn field_name( &self ) -> &'static str {
+        self.field_name
+    }
+
+    #[inline]
+    pub fn field_index( &self ) -> usize {
+        self.field_index
+    }
+
+    /// How many bytes of the value were consumed when reading the field failed,
+    /// counted from where the value starts; `None` if the reader doesn't know
+    /// how much input it has left.
+    #[inline]
+    pub fn bytes_consumed( &self ) -> Option< usize > {
+        self.bytes_consumed
+    }
+
+    #[inline]
+    pub fn into_source( self ) -> io::Error {
+        self.source
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt( &self, fmt: &mut fmt::Formatter ) -> fmt::Result {
+        write!( fmt, "failed to read field `{}` of `{}`: {}", self.field_name, self.type_name, self.source )
+    }
+}
+
+impl Error for FieldError {
+    fn source( &self ) -> Option< &(dyn Error + 'static) > {
+        Some( &self.source )
+    }
+}
+
+#[doc(hidden)]
+#[cold]
+pub fn field_error( source: io::Error, type_name: &'static str, field_name: &'static str, field_index: usize, bytes_consumed: Option< usize > ) -> io::Error {
+    io::Error::new( source.kind(), FieldError { type_name, field_name, field_index, bytes_consumed, source } )
+}
+
+// The derived impls remember how much input was left when they started reading,
+// which together with how much is left now says how much was consumed.
+#[doc(hidden)]
+#[inline]
+pub fn bytes_consumed< 'a, C: Context, R: Reader< 'a, C > >( start: Option< usize >, reader: &R ) -> Option< usize > {
+    start?.checked_sub( reader.remaining_bytes_hint()? )
+}
+
+// When the reader is replaced by one which is limited to a part of the input
+// the starting point is moved so that what it consumes is counted from the same place.
+#[doc(hidden)]
+#[inline]
+pub fn rebase_start< 'a, C: Context, R: Reader< 'a, C > >( consumed: Option< usize >, reader: &R ) -> Option< usize > {
+    consumed?.checked_add( reader.remaining_bytes_hint()? )
+}
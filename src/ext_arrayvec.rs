@@ -0,0 +1,53 @@
+use std::io;
+
+use arrayvec::ArrayVec;
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+use readable_impl::read_length;
+use writable_impl::{write_length, write_elements};
+
+// The wire format is the same as for a `Vec` of the same element type.
+
+impl< 'a, C: Context, T: Readable< 'a, C >, const CAP: usize > Readable< 'a, C > for ArrayVec< T, CAP > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, T >( reader )?;
+        if length > CAP {
+            return Err( io::Error::new( io::ErrorKind::InvalidData, "length prefix is bigger than the capacity of the ArrayVec" ) );
+        }
+
+        let mut vec = ArrayVec::new();
+        for _ in 0..length {
+            vec.push( reader.read_value()? );
+        }
+
+        Ok( vec )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C: Context, T: Writable< C >, const CAP: usize > Writable< C > for ArrayVec< T, CAP > {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        write_length( writer, self.len() )?;
+        write_elements( writer, self )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        Writable::< C >::bytes_needed( self.as_slice() )
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        T::max_bytes_len()?.checked_mul( CAP )?.checked_add( 4 )
+    }
+}
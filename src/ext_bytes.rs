@@ -0,0 +1,134 @@
+use std::io;
+use std::borrow::Cow;
+
+use bytes::{Bytes, BytesMut};
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+use buffer_reader::BufferReader;
+use readable_impl::read_length;
+
+// These use the same format as a `Vec< u8 >`, and unless the input is in memory
+// they're read as one, so that a bogus length can't make them allocate everything up front.
+
+impl< 'a, C: Context > Readable< 'a, C > for Bytes {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        if !reader.can_borrow() {
+            return reader.read_value::< Vec< u8 > >().map( Bytes::from );
+        }
+
+        let length = read_length::< C, R, u8 >( reader )?;
+        reader.speedy_read_shared_bytes( length )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for BytesMut {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        if !reader.can_borrow() {
+            return reader.read_value::< Vec< u8 > >().map( |vec| BytesMut::from( &vec[..] ) );
+        }
+
+        let length = read_length::< C, R, u8 >( reader )?;
+        Ok( BytesMut::from( &reader.read_cow_bytes( length )?[..] ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C: Context > Writable< C > for Bytes {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        self.as_ref().write_to( writer )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.len()
+    }
+}
+
+impl< C: Context > Writable< C > for BytesMut {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        self.as_ref().write_to( writer )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.len()
+    }
+}
+
+/// Reads from a `Bytes`, so that any `Bytes` read from it can share its allocation.
+pub struct SharedBytesReader< 'a, C: Context > {
+    reader: BufferReader< 'a, C >,
+    source: &'a Bytes
+}
+
+impl< 'a, C: Context > SharedBytesReader< 'a, C > {
+    #[inline]
+    pub fn new( context: C, source: &'a Bytes ) -> Self {
+        SharedBytesReader {
+            reader: BufferReader::new( context, source ),
+            source
+        }
+    }
+}
+
+impl< 'a, C: Context > Reader< 'a, C > for SharedBytesReader< 'a, C > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.read_bytes( output )
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        self.reader.read_cow_bytes( length )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        self.reader.remaining_bytes_hint()
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.reader.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        self.reader.read_remaining()
+    }
+
+    #[inline]
+    fn speedy_read_shared_bytes( &mut self, length: usize ) -> io::Result< Bytes > {
+        match self.reader.read_cow_bytes( length )? {
+            Cow::Borrowed( slice ) => Ok( self.source.slice_ref( slice ) ),
+            Cow::Owned( vec ) => Ok( Bytes::from( vec ) )
+        }
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+}
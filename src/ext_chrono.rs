@@ -0,0 +1,145 @@
+use std::io;
+
+use chrono::{DateTime, Utc, NaiveDateTime, NaiveDate, NaiveTime, Datelike, Timelike};
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+
+// A point in time is stored as seconds since the UNIX epoch as an `i64` followed by nanoseconds
+// as an `u32`, a date as days since the first day of the common era as an `i32`, and a time
+// as seconds since midnight followed by nanoseconds, both as an `u32`. Anything which is
+// out of range is rejected when read.
+
+#[inline]
+fn invalid( message: &'static str ) -> io::Error {
+    io::Error::new( io::ErrorKind::InvalidData, message )
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for DateTime< Utc > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let secs = reader.read_i64()?;
+        let nanos = reader.read_u32()?;
+        DateTime::from_timestamp( secs, nanos ).ok_or_else( || invalid( "invalid date and time: out of range" ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        12
+    }
+}
+
+impl< C: Context > Writable< C > for DateTime< Utc > {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        writer.write_i64( self.timestamp() )?;
+        writer.write_u32( self.timestamp_subsec_nanos() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        12
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 12 )
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for NaiveDateTime {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let date_time: DateTime< Utc > = reader.read_value()?;
+        Ok( date_time.naive_utc() )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        12
+    }
+}
+
+impl< C: Context > Writable< C > for NaiveDateTime {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        let date_time = self.and_utc();
+        writer.write_i64( date_time.timestamp() )?;
+        writer.write_u32( date_time.timestamp_subsec_nanos() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        12
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 12 )
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for NaiveDate {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let days = reader.read_i32()?;
+        NaiveDate::from_num_days_from_ce_opt( days ).ok_or_else( || invalid( "invalid date: out of range" ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C: Context > Writable< C > for NaiveDate {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        writer.write_i32( self.num_days_from_ce() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 4 )
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for NaiveTime {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let secs = reader.read_u32()?;
+        let nanos = reader.read_u32()?;
+        NaiveTime::from_num_seconds_from_midnight_opt( secs, nanos ).ok_or_else( || invalid( "invalid time: out of range" ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        8
+    }
+}
+
+impl< C: Context > Writable< C > for NaiveTime {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        writer.write_u32( self.num_seconds_from_midnight() )?;
+        writer.write_u32( self.nanosecond() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        8
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 8 )
+    }
+}
@@ -0,0 +1,104 @@
+use std::io;
+use std::hash::{Hash, BuildHasher};
+
+use indexmap::{IndexMap, IndexSet};
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+use readable_impl::{read_length, preallocated_capacity};
+use writable_impl::write_length;
+
+// These are written as a length followed by the entries, in insertion order;
+// a map's entries are written as the key followed by the value.
+
+impl< 'a, C, K, V, S > Readable< 'a, C > for IndexMap< K, V, S >
+    where C: Context,
+          K: Readable< 'a, C > + Hash + Eq,
+          V: Readable< 'a, C >,
+          S: BuildHasher + Default
+{
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, K >( reader )?;
+        let mut map = IndexMap::with_capacity_and_hasher( preallocated_capacity::< (K, V) >( length ), S::default() );
+        for _ in 0..length {
+            let key = reader.read_value()?;
+            let value = reader.read_value()?;
+            map.insert( key, value );
+        }
+
+        Ok( map )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C, K, V, S > Writable< C > for IndexMap< K, V, S >
+    where C: Context,
+          K: Writable< C >,
+          V: Writable< C >
+{
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        write_length( writer, self.len() )?;
+        for (key, value) in self.iter() {
+            writer.write_value( key )?;
+            writer.write_value( value )?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.iter().map( |(key, value)| key.bytes_needed() + value.bytes_needed() ).sum::< usize >()
+    }
+}
+
+impl< 'a, C, T, S > Readable< 'a, C > for IndexSet< T, S >
+    where C: Context,
+          T: Readable< 'a, C > + Hash + Eq,
+          S: BuildHasher + Default
+{
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, T >( reader )?;
+        let mut set = IndexSet::with_capacity_and_hasher( preallocated_capacity::< T >( length ), S::default() );
+        for _ in 0..length {
+            set.insert( reader.read_value()? );
+        }
+
+        Ok( set )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C, T, S > Writable< C > for IndexSet< T, S >
+    where C: Context,
+          T: Writable< C >
+{
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        write_length( writer, self.len() )?;
+        for element in self.iter() {
+            writer.write_value( element )?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.iter().map( Writable::< C >::bytes_needed ).sum::< usize >()
+    }
+}
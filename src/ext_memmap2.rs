@@ -0,0 +1,17 @@
+use std::io;
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+/// Maps the whole file at `path` into memory, so that it can be
+/// deserialized with `Readable::read_from_mmap` without reading it first.
+///
+/// # Safety
+///
+/// The file must not be modified (neither by this nor by any other process)
+/// for as long as the mapping, or anything borrowed from it, is alive.
+pub unsafe fn map_file< P: AsRef< Path > >( path: P ) -> io::Result< Mmap > {
+    let file = File::open( path )?;
+    Mmap::map( &file )
+}
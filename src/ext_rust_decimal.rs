@@ -0,0 +1,62 @@
+use std::io;
+
+use rust_decimal::Decimal;
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+
+// A decimal is stored the same way as `Decimal::serialize` does it: the flags (the scale
+// in bits 16 to 23 and the sign in bit 31) followed by the low, middle and high 32 bits of the
+// 96-bit mantissa, each as an `u32`. Flags with any other bit set or with a scale bigger
+// than `Decimal::MAX_SCALE` are rejected when read.
+
+const SCALE_SHIFT: u32 = 16;
+const SCALE_MASK: u32 = 0x00FF_0000;
+const SIGN_MASK: u32 = 0x8000_0000;
+
+impl< 'a, C: Context > Readable< 'a, C > for Decimal {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let flags = reader.read_u32()?;
+        let lo = reader.read_u32()?;
+        let mid = reader.read_u32()?;
+        let hi = reader.read_u32()?;
+
+        let scale = (flags & SCALE_MASK) >> SCALE_SHIFT;
+        if flags & !(SCALE_MASK | SIGN_MASK) != 0 || scale > Decimal::MAX_SCALE {
+            return Err( io::Error::new( io::ErrorKind::InvalidData, "invalid decimal: out of range scale" ) );
+        }
+
+        Ok( Decimal::from_parts( lo, mid, hi, flags & SIGN_MASK != 0, scale ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        16
+    }
+}
+
+impl< C: Context > Writable< C > for Decimal {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        let parts = self.unpack();
+        let sign = if parts.negative { SIGN_MASK } else { 0 };
+        writer.write_u32( sign | (parts.scale << SCALE_SHIFT) )?;
+        writer.write_u32( parts.lo )?;
+        writer.write_u32( parts.mid )?;
+        writer.write_u32( parts.hi )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        16
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 16 )
+    }
+}
@@ -0,0 +1,48 @@
+use std::io;
+
+use smallvec::{Array, SmallVec};
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+use readable_impl::{read_length, preallocated_capacity};
+use writable_impl::write_length;
+
+// The wire format is the same as for a `Vec` of the same element type.
+
+impl< 'a, C: Context, A: Array > Readable< 'a, C > for SmallVec< A > where A::Item: Readable< 'a, C > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, A::Item >( reader )?;
+        let mut vec = SmallVec::with_capacity( preallocated_capacity::< A::Item >( length ) );
+        for _ in 0..length {
+            vec.push( reader.read_value()? );
+        }
+
+        Ok( vec )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C: Context, A: Array > Writable< C > for SmallVec< A > where A::Item: Writable< C > {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        write_length( writer, self.len() )?;
+        for element in self.iter() {
+            writer.write_value( element )?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.iter().map( Writable::< C >::bytes_needed ).sum::< usize >()
+    }
+}
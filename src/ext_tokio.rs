@@ -0,0 +1,99 @@
+use std::io;
+use std::cmp;
+use std::mem;
+use std::pin::Pin;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::task::{Context as TaskContext, Poll};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+use readable::Readable;
+use context::Context;
+
+/// Values which can be deserialized straight from an `AsyncRead` stream.
+///
+/// This is implemented for every type which doesn't borrow from its input.
+///
+/// The value has to be prefixed with its length in bytes encoded as an `u32`,
+/// as written by `Writable::write_framed_to_stream`. The whole message is fetched
+/// from the stream first and then decoded in one go; nothing past its end is read.
+pub trait AsyncReadable< C: Context >: Sized {
+    fn read_from_async< R: AsyncRead + Unpin >( context: C, reader: R ) -> ReadFromAsync< Self, C, R >;
+}
+
+impl< C, T > AsyncReadable< C > for T where C: Context, T: for< 'a > Readable< 'a, C > {
+    #[inline]
+    fn read_from_async< R: AsyncRead + Unpin >( context: C, reader: R ) -> ReadFromAsync< Self, C, R > {
+        ReadFromAsync {
+            context: Some( context ),
+            reader,
+            buffer: Vec::new(),
+            frame_length: None,
+            phantom: PhantomData
+        }
+    }
+}
+
+/// The future returned by `AsyncReadable::read_from_async`.
+pub struct ReadFromAsync< T, C, R > {
+    context: Option< C >,
+    reader: R,
+    buffer: Vec< u8 >,
+    frame_length: Option< usize >,
+    phantom: PhantomData< fn() -> T >
+}
+
+// Nothing inside is ever pinned.
+impl< T, C, R: Unpin > Unpin for ReadFromAsync< T, C, R > {}
+
+// The frame's length can't be trusted, so the buffer only grows by this much at a time.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+impl< T, C, R > Future for ReadFromAsync< T, C, R >
+    where T: for< 'a > Readable< 'a, C >,
+          C: Context,
+          R: AsyncRead + Unpin
+{
+    type Output = io::Result< T >;
+
+    fn poll( self: Pin< &mut Self >, cx: &mut TaskContext ) -> Poll< Self::Output > {
+        let this = self.get_mut();
+        loop {
+            let wanted = this.frame_length.unwrap_or( 4 );
+            if this.buffer.len() == wanted {
+                if this.frame_length.is_none() {
+                    let context = this.context.as_ref().expect( "ReadFromAsync polled after completion" );
+                    let endianness = context.length_endianness().unwrap_or_else( || context.endianness() );
+                    this.frame_length = Some( endianness.read_u32( &this.buffer ) as usize );
+                    this.buffer.clear();
+                    continue;
+                }
+
+                let context = this.context.take().expect( "ReadFromAsync polled after completion" );
+                let buffer = mem::take( &mut this.buffer );
+                return Poll::Ready( T::read_from_buffer( context, &buffer ) );
+            }
+
+            let length = this.buffer.len();
+            this.buffer.resize( length + cmp::min( wanted - length, READ_CHUNK_SIZE ), 0 );
+
+            let result = {
+                let mut output = ReadBuf::new( &mut this.buffer[ length.. ] );
+                Pin::new( &mut this.reader ).poll_read( cx, &mut output ).map_ok( |_| output.filled().len() )
+            };
+
+            this.buffer.truncate( length + match result {
+                Poll::Ready( Ok( count ) ) => count,
+                _ => 0
+            });
+
+            match result {
+                Poll::Ready( Ok( 0 ) ) => return Poll::Ready( Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of stream" ) ) ),
+                Poll::Ready( Ok( _ ) ) => {},
+                Poll::Ready( Err( error ) ) => return Poll::Ready( Err( error ) ),
+                Poll::Pending => return Poll::Pending
+            }
+        }
+    }
+}
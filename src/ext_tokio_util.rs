@@ -0,0 +1,81 @@
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use readable::Readable;
+use writable::Writable;
+use context::Context;
+
+/// A codec which frames every message with its length in bytes encoded as an `u32`,
+/// so that a speedy type can be used with `tokio_util::codec::Framed`.
+///
+/// This is the same format as the one used by `Writable::write_framed_to_stream`
+/// and `Readable::stream_from_reader`.
+///
+/// Since the length comes from the peer, frames which are longer than
+/// `max_frame_length` (8 MiB by default) are rejected before any memory is reserved for them.
+pub struct SpeedyCodec< C: Context, T > {
+    context: C,
+    max_frame_length: usize,
+    phantom: PhantomData< fn() -> T >
+}
+
+const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+impl< C: Context, T > SpeedyCodec< C, T > {
+    #[inline]
+    pub fn new( context: C ) -> Self {
+        SpeedyCodec {
+            context,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+            phantom: PhantomData
+        }
+    }
+
+    /// The most bytes a frame can have, not counting its length prefix.
+    #[inline]
+    pub fn max_frame_length( &self ) -> usize {
+        self.max_frame_length
+    }
+
+    #[inline]
+    pub fn set_max_frame_length( &mut self, max_frame_length: usize ) {
+        self.max_frame_length = max_frame_length;
+    }
+}
+
+impl< C, T > Decoder for SpeedyCodec< C, T > where C: Context + Clone, T: for< 'a > Readable< 'a, C > {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode( &mut self, src: &mut BytesMut ) -> io::Result< Option< T > > {
+        if src.len() < 4 {
+            src.reserve( 4 - src.len() );
+            return Ok( None );
+        }
+
+        let endianness = self.context.length_endianness().unwrap_or_else( || self.context.endianness() );
+        let length = endianness.read_u32( &src[ ..4 ] ) as usize;
+        if length > self.max_frame_length {
+            return Err( io::Error::new( io::ErrorKind::InvalidData, "frame is longer than the maximum frame length" ) );
+        }
+
+        if src.len() - 4 < length {
+            src.reserve( length - (src.len() - 4) );
+            return Ok( None );
+        }
+
+        let frame = src.split_to( 4 + length );
+        T::read_from_buffer( self.context.clone(), &frame[ 4.. ] ).map( Some )
+    }
+}
+
+impl< C, T > Encoder< T > for SpeedyCodec< C, T > where C: Context + Clone, T: Writable< C > {
+    type Error = io::Error;
+
+    fn encode( &mut self, item: T, dst: &mut BytesMut ) -> io::Result< () > {
+        item.write_framed_to_stream( self.context.clone(), dst.writer() )
+    }
+}
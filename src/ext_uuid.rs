@@ -0,0 +1,42 @@
+use std::io;
+
+use uuid::Uuid;
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+
+// A UUID is a byte array, so it's always stored as its 16 bytes regardless of the endianness.
+
+impl< 'a, C: Context > Readable< 'a, C > for Uuid {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let mut bytes = [0; 16];
+        reader.read_bytes( &mut bytes )?;
+        Ok( Uuid::from_bytes( bytes ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        16
+    }
+}
+
+impl< C: Context > Writable< C > for Uuid {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        writer.write_bytes( self.as_bytes() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        16
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 16 )
+    }
+}
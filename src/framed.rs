@@ -0,0 +1,101 @@
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+use readable::Readable;
+use readable_impl::read_length_prefix;
+use reader::Reader;
+use writable::Writable;
+use writable_impl::write_length;
+use writer::Writer;
+use context::Context;
+use schema::{Schema, SchemaNode};
+use variant_frame::VariantFrameReader;
+use size_counting_writer::SizeCountingWriter;
+
+/// A value which is preceded by its length in bytes encoded as an `u32`.
+///
+/// This has the same layout as a `Vec< u8 >` holding the serialized value, so a reader
+/// which doesn't know the value's type can read it as one, or skip over it.
+/// When read anything which is left in the frame after the value is skipped,
+/// so fields which were appended to the value by a newer version of it are ignored.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Framed< T >( pub T );
+
+impl< T > Framed< T > {
+    #[inline]
+    pub fn into_inner( self ) -> T {
+        self.0
+    }
+}
+
+impl< T > From< T > for Framed< T > {
+    #[inline]
+    fn from( value: T ) -> Self {
+        Framed( value )
+    }
+}
+
+impl< T > Deref for Framed< T > {
+    type Target = T;
+
+    #[inline]
+    fn deref( &self ) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl< T > DerefMut for Framed< T > {
+    #[inline]
+    fn deref_mut( &mut self ) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl< 'a, C: Context, T: Readable< 'a, C > > Readable< 'a, C > for Framed< T > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length_prefix( reader )?;
+        let mut reader = VariantFrameReader::new( reader, length );
+        let value = T::read_from( &mut reader )?;
+        reader.skip_rest()?;
+        Ok( Framed( value ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< C: Context, T: Writable< C > > Writable< C > for Framed< T > {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        // The length is only known once the value is written, so it's written twice:
+        // once to count its bytes and once for real.
+        let length = {
+            let mut counter = SizeCountingWriter::new( &*writer );
+            self.0.write_to( &mut counter )?;
+            counter.size()
+        };
+
+        write_length( writer, length )?;
+        self.0.write_to( writer )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.0.bytes_needed()
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        T::max_bytes_len()?.checked_add( 4 )
+    }
+}
+
+impl< T: Schema > Schema for Framed< T > {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::Framed( Box::new( T::schema() ) )
+    }
+}
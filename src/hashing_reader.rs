@@ -0,0 +1,128 @@
+use std::io;
+use std::borrow::Cow;
+
+use reader::Reader;
+use context::Context;
+use endianness::Endianness;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Keeps a running 64-bit FNV-1a hash of every byte which is read
+/// through it from another reader, e.g. to check it against a trailing digest.
+///
+/// Peeked bytes aren't hashed until they're actually read. The hash is only meant
+/// to catch corruption; it's not cryptographically secure.
+///
+/// ```rust
+/// use speedy::{BufferReader, Endianness, HashingReader, Reader};
+///
+/// let data = b"foobar";
+/// let mut reader = BufferReader::new( Endianness::LittleEndian, data );
+/// let mut hashing_reader = HashingReader::new( &mut reader );
+/// let value: [u8; 6] = hashing_reader.read_value().unwrap();
+/// assert_eq!( &value, data );
+/// assert_eq!( hashing_reader.digest(), 0x85944171f73967e8 );
+/// ```
+pub struct HashingReader< 'r, R: 'r > {
+    reader: &'r mut R,
+    hash: u64
+}
+
+impl< 'r, R > HashingReader< 'r, R > {
+    #[inline]
+    pub fn new( reader: &'r mut R ) -> Self {
+        HashingReader {
+            reader,
+            hash: FNV_OFFSET_BASIS
+        }
+    }
+
+    /// The hash of everything which was read so far.
+    #[inline]
+    pub fn digest( &self ) -> u64 {
+        self.hash
+    }
+
+    /// Returns the underlying reader; anything read from it isn't hashed.
+    #[inline]
+    pub fn into_inner( self ) -> &'r mut R {
+        self.reader
+    }
+
+    #[inline]
+    fn update( &mut self, bytes: &[u8] ) {
+        self.hash = bytes.iter().fold( self.hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul( FNV_PRIME ) );
+    }
+}
+
+impl< 'a, 'r, C: Context, R: Reader< 'a, C > > Reader< 'a, C > for HashingReader< 'r, R > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.read_bytes( output )?;
+        self.update( output );
+        Ok(())
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        let bytes = self.reader.read_cow_bytes( length )?;
+        self.update( &bytes );
+        Ok( bytes )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        self.reader.remaining_bytes_hint()
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.reader.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let bytes = self.reader.read_remaining()?;
+        self.update( &bytes );
+        Ok( bytes )
+    }
+
+    #[cfg(feature = "bytes")]
+    #[inline]
+    fn speedy_read_shared_bytes( &mut self, length: usize ) -> io::Result< ::bytes::Bytes > {
+        let bytes = self.reader.speedy_read_shared_bytes( length )?;
+        self.update( &bytes );
+        Ok( bytes )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.reader.begin_field( label )
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.reader.end_field()
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.reader.endianness()
+    }
+
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.reader.length_endianness()
+    }
+}
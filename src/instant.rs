@@ -0,0 +1,105 @@
+use std::io;
+use std::time::{Duration, Instant};
+use std::sync::OnceLock;
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+
+/// A serializable stand-in for `std::time::Instant`.
+///
+/// An `Instant` is opaque and can't be serialized, so this wrapper stores
+/// the number of nanoseconds elapsed since an origin which is fixed the first
+/// time any `SerializableInstant` is created, and is serialized as an `u64`.
+///
+/// Since the origin is only fixed within a single process a deserialized value
+/// is only meaningful if it was serialized by the same run of the same process.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct SerializableInstant {
+    nanos: u64
+}
+
+fn origin() -> Instant {
+    static ORIGIN: OnceLock< Instant > = OnceLock::new();
+    *ORIGIN.get_or_init( Instant::now )
+}
+
+impl SerializableInstant {
+    #[inline]
+    pub fn now() -> Self {
+        Self::from_instant( Instant::now() )
+    }
+
+    /// Converts an `Instant` into a `SerializableInstant`.
+    ///
+    /// Instants from before the origin was fixed are clamped to the origin.
+    pub fn from_instant( instant: Instant ) -> Self {
+        let elapsed = instant.saturating_duration_since( origin() );
+        let nanos = elapsed.as_secs()
+            .saturating_mul( 1_000_000_000 )
+            .saturating_add( elapsed.subsec_nanos() as u64 );
+
+        SerializableInstant { nanos }
+    }
+
+    #[inline]
+    pub fn from_nanos( nanos: u64 ) -> Self {
+        SerializableInstant { nanos }
+    }
+
+    #[inline]
+    pub fn as_nanos( &self ) -> u64 {
+        self.nanos
+    }
+
+    #[inline]
+    pub fn to_instant( &self ) -> Instant {
+        origin() + Duration::from_nanos( self.nanos )
+    }
+}
+
+impl From< Instant > for SerializableInstant {
+    #[inline]
+    fn from( instant: Instant ) -> Self {
+        SerializableInstant::from_instant( instant )
+    }
+}
+
+impl From< SerializableInstant > for Instant {
+    #[inline]
+    fn from( instant: SerializableInstant ) -> Self {
+        instant.to_instant()
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for SerializableInstant {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let nanos = reader.read_u64()?;
+        Ok( SerializableInstant { nanos } )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        8
+    }
+}
+
+impl< C: Context > Writable< C > for SerializableInstant {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        writer.write_u64( self.nanos )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        8
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 8 )
+    }
+}
@@ -0,0 +1,210 @@
+use std::io;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use reader::Reader;
+use writer::Writer;
+use context::Context;
+use endianness::Endianness;
+
+/// A wrapper around a `Reader` or a `Writer` which keeps track of how many bytes
+/// were read or written by every field of the types with derived impls.
+///
+/// Fields are labeled as `Type.field` (or `Type::Variant.field` for enums).
+/// Bytes are attributed to the innermost field which is currently being
+/// processed, so the bytes of a nested struct's fields are not counted
+/// towards the field containing it. Bytes outside of any field (e.g. when
+/// serializing a bare `u32`) are not counted at all.
+///
+/// ```rust
+/// # #[macro_use] extern crate speedy_derive;
+/// # extern crate speedy;
+/// use speedy::{Endianness, Instrumented, VecWriter, Writable};
+///
+/// #[derive(Writable)]
+/// struct Point {
+///     x: u8,
+///     y: u32
+/// }
+///
+/// # fn main() {
+/// let mut writer = Instrumented::new( VecWriter::new( Endianness::LittleEndian ) );
+/// Point { x: 1, y: 2 }.write_to( &mut writer ).unwrap();
+/// assert_eq!( writer.histogram()[ "Point.y" ], 4 );
+/// # }
+/// ```
+pub struct Instrumented< T > {
+    inner: T,
+    labels: Vec< &'static str >,
+    histogram: BTreeMap< &'static str, usize >
+}
+
+impl< T > Instrumented< T > {
+    #[inline]
+    pub fn new( inner: T ) -> Self {
+        Instrumented {
+            inner,
+            labels: Vec::new(),
+            histogram: BTreeMap::new()
+        }
+    }
+
+    /// Returns the number of bytes processed so far for every field label.
+    #[inline]
+    pub fn histogram( &self ) -> &BTreeMap< &'static str, usize > {
+        &self.histogram
+    }
+
+    #[inline]
+    pub fn into_inner( self ) -> (T, BTreeMap< &'static str, usize >) {
+        (self.inner, self.histogram)
+    }
+
+    #[inline]
+    fn record( &mut self, count: usize ) {
+        if let Some( &label ) = self.labels.last() {
+            *self.histogram.entry( label ).or_insert( 0 ) += count;
+        }
+    }
+}
+
+impl< 'a, C: Context, R: Reader< 'a, C > > Reader< 'a, C > for Instrumented< R > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.inner.read_bytes( output )?;
+        self.record( output.len() );
+        Ok(())
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        let bytes = self.inner.read_cow_bytes( length )?;
+        self.record( length );
+        Ok( bytes )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        self.inner.remaining_bytes_hint()
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.inner.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.inner.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let bytes = self.inner.read_remaining()?;
+        self.record( bytes.len() );
+        Ok( bytes )
+    }
+
+    #[cfg(feature = "bytes")]
+    #[inline]
+    fn speedy_read_shared_bytes( &mut self, length: usize ) -> io::Result< ::bytes::Bytes > {
+        let bytes = self.inner.speedy_read_shared_bytes( length )?;
+        self.record( length );
+        Ok( bytes )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.inner.context()
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.labels.push( label );
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.labels.pop();
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.inner.endianness()
+    }
+
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.inner.length_endianness()
+    }
+}
+
+impl< 'a, C: Context, W: Writer< 'a, C > > Writer< 'a, C > for Instrumented< W > {
+    #[inline]
+    fn write_bytes( &mut self, slice: &'a [u8] ) -> io::Result< () > {
+        self.inner.write_bytes( slice )?;
+        self.record( slice.len() );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_owned_bytes( &mut self, vec: Vec< u8 > ) -> io::Result< () > {
+        let length = vec.len();
+        self.inner.write_owned_bytes( vec )?;
+        self.record( length );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u8( &mut self, value: u8 ) -> io::Result< () > {
+        self.inner.write_u8( value )?;
+        self.record( 1 );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u16( &mut self, value: u16 ) -> io::Result< () > {
+        self.inner.write_u16( value )?;
+        self.record( 2 );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u32( &mut self, value: u32 ) -> io::Result< () > {
+        self.inner.write_u32( value )?;
+        self.record( 4 );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u64( &mut self, value: u64 ) -> io::Result< () > {
+        self.inner.write_u64( value )?;
+        self.record( 8 );
+        Ok(())
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.inner.context()
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.labels.push( label );
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.labels.pop();
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.inner.endianness()
+    }
+
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.inner.length_endianness()
+    }
+}
@@ -38,7 +38,7 @@
 //!     };
 //!     let endian = Endianness::LittleEndian;
 //!     let bytes = original.write_to_vec( endian ).unwrap();
-//!     let deserialized: Struct< 'static > =
+//!     let deserialized: Struct =
 //!         Struct::read_from_buffer( endian, &bytes ).unwrap();
 //!
 //!     assert_eq!( original, deserialized );
@@ -47,6 +47,39 @@
 
 extern crate byteorder;
 
+#[cfg(feature = "smallvec")]
+extern crate smallvec;
+
+#[cfg(feature = "memmap2")]
+extern crate memmap2;
+
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
+
+#[cfg(feature = "arrayvec")]
+extern crate arrayvec;
+
+#[cfg(feature = "uuid")]
+extern crate uuid;
+
+#[cfg(feature = "chrono")]
+extern crate chrono;
+
+#[cfg(feature = "rust_decimal")]
+extern crate rust_decimal;
+
+#[cfg(feature = "tokio")]
+extern crate tokio;
+
+#[cfg(feature = "tokio-util")]
+extern crate tokio_util;
+
+#[cfg(feature = "bytes")]
+extern crate bytes;
+
+#[cfg(feature = "derive")]
+extern crate speedy_derive;
+
 mod utils;
 mod readable;
 mod readable_impl;
@@ -54,17 +87,133 @@ mod reader;
 mod writable;
 mod writable_impl;
 mod writer;
+mod size_counting_writer;
 mod context;
 mod endianness;
+mod error;
+mod instant;
+mod seekable_reader;
+mod buffer_reader;
+mod chained_reader;
+mod bit_reader;
+mod hashing_reader;
+mod vec_writer;
+mod endianness_override;
+mod payload_reader;
+mod variant_frame;
+mod debug_format;
+mod schema;
+mod raw_bytes;
+mod framed;
+
+pub mod endian;
+
+#[cfg(feature = "smallvec")]
+mod ext_smallvec;
+
+#[cfg(feature = "memmap2")]
+mod ext_memmap2;
+
+#[cfg(feature = "indexmap")]
+mod ext_indexmap;
+
+#[cfg(feature = "arrayvec")]
+mod ext_arrayvec;
+
+#[cfg(feature = "uuid")]
+mod ext_uuid;
+
+#[cfg(feature = "chrono")]
+mod ext_chrono;
+
+#[cfg(feature = "rust_decimal")]
+mod ext_rust_decimal;
 
-pub use readable::Readable;
+#[cfg(feature = "bytes")]
+mod ext_bytes;
+
+#[cfg(feature = "tokio")]
+mod ext_tokio;
+
+#[cfg(feature = "tokio-util")]
+mod ext_tokio_util;
+
+#[cfg(feature = "instrumentation")]
+mod instrumentation;
+
+#[cfg(feature = "bincode_compat")]
+pub mod bincode_compat;
+
+pub use readable::{Readable, MessageStream, read_bytes_from_buffer_into};
 pub use reader::Reader;
+pub use seekable_reader::SeekableReader;
+pub use buffer_reader::BufferReader;
+pub use chained_reader::ChainedReader;
+pub use bit_reader::BitReader;
+pub use hashing_reader::HashingReader;
 
 pub use writable::Writable;
 pub use writer::Writer;
+pub use vec_writer::VecWriter;
 
 pub use endianness::Endianness;
-pub use context::Context;
+pub use context::{Context, LittleEndianCtx, BigEndianCtx};
+pub use error::FieldError;
+pub use instant::SerializableInstant;
+pub use raw_bytes::RawBytes;
+pub use framed::Framed;
+pub use schema::{Schema, SchemaNode, SchemaField, SchemaVariant};
+
+#[cfg(feature = "memmap2")]
+pub use ext_memmap2::map_file;
+
+#[cfg(feature = "tokio")]
+pub use ext_tokio::{AsyncReadable, ReadFromAsync};
+
+#[cfg(feature = "tokio-util")]
+pub use ext_tokio_util::SpeedyCodec;
+
+#[cfg(feature = "instrumentation")]
+pub use instrumentation::Instrumented;
+
+/// The most commonly used items, so that a single glob import is enough.
+///
+/// With the `derive` feature enabled this also includes the derive macros.
+///
+/// ```rust
+/// use speedy::prelude::*;
+///
+/// let original: Vec< u16 > = vec![ 1, 2, 3 ];
+/// let bytes = original.write_to_vec( Endianness::BigEndian ).unwrap();
+/// assert_eq!( bytes, [0, 0, 0, 3, 0, 1, 0, 2, 0, 3] );
+///
+/// let deserialized: Vec< u16 > = Readable::read_from_buffer( Endianness::BigEndian, &bytes ).unwrap();
+/// assert_eq!( deserialized, original );
+/// ```
+pub mod prelude {
+    pub use readable::Readable;
+    pub use reader::Reader;
+    pub use writable::Writable;
+    pub use writer::Writer;
+    pub use endianness::Endianness;
+    pub use context::Context;
+
+    #[cfg(feature = "derive")]
+    pub use speedy_derive::{Readable, Writable, Schema};
+}
+
+#[doc(hidden)]
+pub mod private {
+    pub use utils::Primitive;
+    pub use error::{field_error, bytes_consumed, rebase_start};
+    pub use endianness_override::{EndiannessOverrideReader, EndiannessOverrideWriter};
+    pub use payload_reader::PayloadReader;
+    pub use variant_frame::VariantFrameReader;
+    pub use size_counting_writer::SizeCountingWriter;
+    pub use readable_impl::read_length_prefix;
+    pub use writable_impl::write_length;
+    pub use readable::ReadableOwned;
+}
 
 #[cfg(test)]
 mod tests {
@@ -95,8 +244,8 @@ mod tests {
         }
     }
 
-    impl< C: Context > Readable< C > for SimpleStruct {
-        fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
+    impl< 'a, C: Context > Readable< 'a, C > for SimpleStruct {
+        fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
             let a = reader.read_u8()?;
             let b = reader.read_u8()?;
             let c = reader.read_u8()?;
@@ -119,6 +268,44 @@ mod tests {
         assert_eq!( value, SimpleStruct { a: 1, b: 2, c: 3 } );
     }
 
+    #[test]
+    fn simple_read_from_stream_buffered() {
+        let data = vec![ 1, 2, 3 ];
+        let cursor = io::Cursor::new( data );
+        let value = SimpleStruct::read_from_stream_buffered( Endianness::default(), cursor ).unwrap();
+        assert_eq!( value, SimpleStruct { a: 1, b: 2, c: 3 } );
+    }
+
+    struct TrickleReader< 'a > {
+        data: &'a [u8]
+    }
+
+    impl< 'a > io::Read for TrickleReader< 'a > {
+        fn read( &mut self, output: &mut [u8] ) -> io::Result< usize > {
+            let count = if self.data.is_empty() || output.is_empty() { 0 } else { 1 };
+            output[ ..count ].copy_from_slice( &self.data[ ..count ] );
+            self.data = &self.data[ count.. ];
+            Ok( count )
+        }
+    }
+
+    #[test]
+    fn read_u64_vec_from_stream_buffered() {
+        let original: Vec< u64 > = (0..5000).collect();
+        let serialized = original.write_to_vec( Endianness::default() ).unwrap();
+
+        let deserialized: Vec< u64 > = Vec::< u64 >::read_from_stream_buffered( Endianness::default(), &serialized[..] ).unwrap();
+        assert_eq!( original, deserialized );
+
+        let reader = TrickleReader { data: &serialized };
+        let deserialized: Vec< u64 > = Vec::< u64 >::read_from_stream_buffered( Endianness::default(), reader ).unwrap();
+        assert_eq!( original, deserialized );
+
+        let reader = TrickleReader { data: &serialized[ ..serialized.len() - 1 ] };
+        let error = Vec::< u64 >::read_from_stream_buffered( Endianness::default(), reader ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+    }
+
     #[test]
     fn simple_read_from_buffer() {
         let data = vec![ 1, 2, 3 ];
@@ -142,6 +329,60 @@ mod tests {
         assert_eq!( original, deserialized );
     }
 
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn write_length_overflow() {
+        struct HugeCollection;
+
+        impl< C: Context > Writable< C > for HugeCollection {
+            fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+                ::writable_impl::write_length( writer, u32::MAX as usize + 1 )
+            }
+        }
+
+        let error = HugeCollection.write_to_vec( Endianness::default() ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidInput );
+
+        let mut buffer = [0; 8];
+        let error = HugeCollection.write_to_buffer( Endianness::default(), &mut buffer ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidInput );
+        assert_eq!( buffer, [0; 8] );
+    }
+
+    #[test]
+    fn byte_length_overflow() {
+        assert_eq!( ::readable_impl::byte_length::< u64 >( 3 ).unwrap(), 24 );
+
+        let error = ::readable_impl::byte_length::< u64 >( usize::MAX / 4 ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    }
+
+    #[test]
+    fn length_prefix_bigger_than_the_buffer() {
+        let mut data = vec![ 0; 12 ];
+        data[ ..4 ].copy_from_slice( &1_000_000_u32.to_le_bytes() );
+
+        let error = Vec::< u32 >::read_from_buffer( Endianness::LittleEndian, &data ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+        let error = ::std::collections::BinaryHeap::< u32 >::read_from_buffer( Endianness::LittleEndian, &data ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+        let error = ::std::borrow::Cow::< [u32] >::read_from_buffer( Endianness::LittleEndian, &data ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+        // Exactly the number of elements which fit in the rest of the buffer.
+        data[ ..4 ].copy_from_slice( &2_u32.to_le_bytes() );
+        assert_eq!( Vec::< u32 >::read_from_buffer( Endianness::LittleEndian, &data ).unwrap(), vec![ 0, 0 ] );
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn read_u64_vec_with_overflowing_length() {
+        let error = Vec::< u64 >::read_from_buffer( Endianness::LittleEndian, &[0xff, 0xff, 0xff, 0xff] ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    }
+
     #[test]
     fn read_write_string() {
         let original: String = "Hello world!".to_owned();
@@ -0,0 +1,61 @@
+use std::io;
+use std::marker::PhantomData;
+
+use reader::Reader;
+use context::Context;
+use endianness::Endianness;
+
+// This is used by the derived impls of enums with a trailing tag; the payload is read
+// into a buffer first, and once the tag is known it's decoded from that buffer.
+
+#[doc(hidden)]
+pub struct PayloadReader< 'r, 'p, C: 'r, R: 'r > {
+    reader: &'r R,
+    payload: &'p [u8],
+    phantom: PhantomData< &'r C >
+}
+
+impl< 'r, 'p, C, R > PayloadReader< 'r, 'p, C, R > {
+    #[inline]
+    pub fn new( reader: &'r R, payload: &'p [u8] ) -> Self {
+        PayloadReader {
+            reader,
+            payload,
+            phantom: PhantomData
+        }
+    }
+}
+
+impl< 'a, 'r, 'p, C: Context, R: Reader< 'a, C > > Reader< 'a, C > for PayloadReader< 'r, 'p, C, R > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        if output.len() > self.payload.len() {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of payload" ) );
+        }
+
+        let (head, tail) = self.payload.split_at( output.len() );
+        output.copy_from_slice( head );
+        self.payload = tail;
+        Ok(())
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        Some( self.payload.len() )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.reader.endianness()
+    }
+
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.reader.length_endianness()
+    }
+}
@@ -0,0 +1,87 @@
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+use readable::Readable;
+use reader::Reader;
+use writable::Writable;
+use writer::Writer;
+use context::Context;
+use schema::{Schema, SchemaNode};
+
+/// Bytes which are written verbatim, without a length prefix.
+///
+/// This is useful to embed a message which was already serialized.
+/// Since nothing says where the bytes end they consume the rest of the input
+/// when read, so this can only be used as the last field of a struct; otherwise
+/// use `RawBytes::read_with_length` with a length which is known from elsewhere.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct RawBytes( pub Vec< u8 > );
+
+impl RawBytes {
+    /// Reads exactly `length` bytes.
+    #[inline]
+    pub fn read_with_length< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R, length: usize ) -> io::Result< Self > {
+        reader.read_cow_bytes( length ).map( |bytes| RawBytes( bytes.into_owned() ) )
+    }
+
+    #[inline]
+    pub fn into_inner( self ) -> Vec< u8 > {
+        self.0
+    }
+}
+
+impl From< Vec< u8 > > for RawBytes {
+    #[inline]
+    fn from( bytes: Vec< u8 > ) -> Self {
+        RawBytes( bytes )
+    }
+}
+
+impl From< RawBytes > for Vec< u8 > {
+    #[inline]
+    fn from( bytes: RawBytes ) -> Self {
+        bytes.0
+    }
+}
+
+impl Deref for RawBytes {
+    type Target = Vec< u8 >;
+
+    #[inline]
+    fn deref( &self ) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for RawBytes {
+    #[inline]
+    fn deref_mut( &mut self ) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for RawBytes {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        reader.read_remaining().map( RawBytes )
+    }
+}
+
+impl< C: Context > Writable< C > for RawBytes {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        writer.write_bytes( &self.0 )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        self.0.len()
+    }
+}
+
+impl Schema for RawBytes {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::Remaining( Box::new( SchemaNode::Primitive( "u8" ) ) )
+    }
+}
@@ -1,22 +1,40 @@
+use std::cmp;
+use std::fs;
+use std::path::Path;
 use std::io::{
     self,
     Read
 };
+use std::marker::PhantomData;
 
 use reader::Reader;
+use buffer_reader::BufferReader;
+use chained_reader::ChainedReader;
+use debug_format::DebugReader;
 use context::Context;
+use endianness::Endianness;
+use readable_impl::{read_length_prefix, read_elements};
+
+const STREAM_BUFFER_SIZE: usize = 8 * 1024;
 
 struct DirectSyncReader< C: Context, S: Read > {
     context: C,
     reader: S
 }
 
-impl< C: Context, S: Read > Reader< C > for DirectSyncReader< C, S > {
+impl< 'a, C: Context, S: Read > Reader< 'a, C > for DirectSyncReader< C, S > {
     #[inline]
     fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
         self.reader.read_exact( output )
     }
 
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let mut output = Vec::new();
+        self.reader.read_to_end( &mut output )?;
+        Ok( output )
+    }
+
     #[inline]
     fn context( &self ) -> &C {
         &self.context
@@ -25,27 +43,443 @@ impl< C: Context, S: Read > Reader< C > for DirectSyncReader< C, S > {
 
 impl< C: Context, S: Read > DirectSyncReader< C, S > {
     #[inline]
-    fn deserialize< T: Readable< C > >( context: C, reader: S ) -> io::Result< T > {
+    fn deserialize< 'a, T: Readable< 'a, C > >( context: C, reader: S ) -> io::Result< T > {
         let mut reader = DirectSyncReader { context, reader };
         T::read_from( &mut reader )
     }
 }
 
-pub trait Readable< C: Context >: Sized {
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self >;
+struct BufferedStreamReader< C: Context, S: Read > {
+    context: C,
+    reader: S,
+    buffer: Vec< u8 >,
+    position: usize
+}
+
+impl< C: Context, S: Read > BufferedStreamReader< C, S > {
+    #[inline]
+    fn new( context: C, reader: S ) -> Self {
+        BufferedStreamReader {
+            context,
+            reader,
+            buffer: Vec::with_capacity( STREAM_BUFFER_SIZE ),
+            position: 0
+        }
+    }
+
+    #[inline]
+    fn deserialize< 'a, T: Readable< 'a, C > >( context: C, reader: S ) -> io::Result< T > {
+        let mut reader = BufferedStreamReader::new( context, reader );
+        T::read_from( &mut reader )
+    }
+
+    /// Refills the internal buffer; returns `false` if the stream has ended.
+    fn fill_buffer( &mut self ) -> io::Result< bool > {
+        self.buffer.resize( STREAM_BUFFER_SIZE, 0 );
+        self.position = 0;
+        loop {
+            match self.reader.read( &mut self.buffer ) {
+                Ok( count ) => {
+                    self.buffer.truncate( count );
+                    return Ok( count != 0 );
+                },
+                Err( ref error ) if error.kind() == io::ErrorKind::Interrupted => {},
+                Err( error ) => {
+                    self.buffer.clear();
+                    return Err( error );
+                }
+            }
+        }
+    }
+
+    #[inline(never)]
+    fn read_bytes_slow( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        let (head, mut tail) = output.split_at_mut( self.buffer.len() - self.position );
+        head.copy_from_slice( &self.buffer[ self.position.. ] );
+        self.position = self.buffer.len();
+
+        if tail.len() >= STREAM_BUFFER_SIZE {
+            return self.reader.read_exact( tail );
+        }
+
+        while !tail.is_empty() {
+            if !self.fill_buffer()? {
+                return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of stream" ) );
+            }
+
+            let chunk = cmp::min( tail.len(), self.buffer.len() );
+            let (current, rest) = tail.split_at_mut( chunk );
+            current.copy_from_slice( &self.buffer[ ..chunk ] );
+            self.position = chunk;
+            tail = rest;
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure at least `count` unread bytes are buffered;
+    /// returns `false` if the stream ends before that.
+    fn fill_buffer_to( &mut self, count: usize ) -> io::Result< bool > {
+        if self.buffer.len() - self.position >= count {
+            return Ok( true );
+        }
+
+        self.buffer.drain( ..self.position );
+        self.position = 0;
+        while self.buffer.len() < count {
+            let length = self.buffer.len();
+            self.buffer.resize( cmp::max( STREAM_BUFFER_SIZE, count ), 0 );
+            let result = self.reader.read( &mut self.buffer[ length.. ] );
+            self.buffer.truncate( length + *result.as_ref().unwrap_or( &0 ) );
+            match result {
+                Ok( 0 ) => return Ok( false ),
+                Ok( _ ) => {},
+                Err( ref error ) if error.kind() == io::ErrorKind::Interrupted => {},
+                Err( error ) => return Err( error )
+            }
+        }
+
+        Ok( true )
+    }
+
+    fn is_at_end( &mut self ) -> io::Result< bool > {
+        if self.position < self.buffer.len() {
+            return Ok( false );
+        }
+
+        self.fill_buffer().map( |more| !more )
+    }
+
+    fn skip_bytes( &mut self, mut count: usize ) -> io::Result< () > {
+        while count > 0 {
+            if self.position == self.buffer.len() && !self.fill_buffer()? {
+                return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of stream" ) );
+            }
+
+            let chunk = cmp::min( count, self.buffer.len() - self.position );
+            self.position += chunk;
+            count -= chunk;
+        }
+
+        Ok(())
+    }
+}
+
+impl< 'a, C: Context, S: Read > Reader< 'a, C > for BufferedStreamReader< C, S > {
+    #[inline(always)]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        let end = self.position + output.len();
+        if end <= self.buffer.len() {
+            output.copy_from_slice( &self.buffer[ self.position..end ] );
+            self.position = end;
+            Ok(())
+        } else {
+            self.read_bytes_slow( output )
+        }
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        if !self.fill_buffer_to( output.len() )? {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "unexpected end of stream" ) );
+        }
+
+        output.copy_from_slice( &self.buffer[ self.position..self.position + output.len() ] );
+        Ok(())
+    }
+
+    #[inline]
+    fn skip_bytes( &mut self, count: usize ) -> io::Result< () > {
+        BufferedStreamReader::skip_bytes( self, count )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let mut output = self.buffer[ self.position.. ].to_vec();
+        self.position = self.buffer.len();
+        self.reader.read_to_end( &mut output )?;
+        Ok( output )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        &self.context
+    }
+}
+
+struct FrameReader< 'r, C: Context + 'r, S: Read + 'r > {
+    reader: &'r mut BufferedStreamReader< C, S >,
+    remaining: usize
+}
+
+impl< 'a, 'r, C: Context, S: Read > Reader< 'a, C > for FrameReader< 'r, C, S > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        if output.len() > self.remaining {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "message is longer than its frame" ) );
+        }
+
+        Reader::< 'a, C >::read_bytes( self.reader, output )?;
+        self.remaining -= output.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        Some( self.remaining )
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        if output.len() > self.remaining {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "message is longer than its frame" ) );
+        }
+
+        Reader::< 'a, C >::peek_bytes( self.reader, output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        // The frame's length can't be trusted, so this allocates as the bytes come in.
+        let length = self.remaining;
+        read_elements( self, length )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+}
+
+/// An iterator over a stream of length-prefixed messages.
+///
+/// Every message is preceded by its length in bytes encoded as an `u32`.
+/// Created by `Readable::stream_from_reader`. Since the messages are
+/// read from a stream they can't borrow anything, so it only iterates
+/// over types which are `Readable< 'static, C >`.
+pub struct MessageStream< C: Context, S: Read, T > {
+    reader: BufferedStreamReader< C, S >,
+    finished: bool,
+    phantom: PhantomData< T >
+}
+
+impl< C: Context, S: Read, T: Readable< 'static, C > > MessageStream< C, S, T > {
+    fn read_message( &mut self ) -> io::Result< Option< T > > {
+        if self.reader.is_at_end()? {
+            return Ok( None );
+        }
+
+        let length = read_length_prefix( &mut self.reader )?;
+        let mut reader = FrameReader {
+            reader: &mut self.reader,
+            remaining: length
+        };
+
+        let value = T::read_from( &mut reader )?;
+        let remaining = reader.remaining;
+        self.reader.skip_bytes( remaining )?;
+
+        Ok( Some( value ) )
+    }
+}
+
+impl< C: Context, S: Read, T: Readable< 'static, C > > Iterator for MessageStream< C, S, T > {
+    type Item = io::Result< T >;
+
+    fn next( &mut self ) -> Option< Self::Item > {
+        if self.finished {
+            return None;
+        }
+
+        match self.read_message() {
+            Ok( Some( value ) ) => Some( Ok( value ) ),
+            Ok( None ) => {
+                self.finished = true;
+                None
+            },
+            Err( error ) => {
+                self.finished = true;
+                Some( Err( error ) )
+            }
+        }
+    }
+}
+
+// Implemented for every type which doesn't borrow from its input. A method of `Readable< 'a, C >`
+// can't require `Self: for< 'b > Readable< 'b, C >` directly since that'd be ambiguous.
+#[doc(hidden)]
+pub trait ReadableOwned< C: Context >: Sized {
+    fn speedy_read_from_any_buffer( context: C, buffer: &[u8] ) -> io::Result< Self >;
+}
+
+impl< C: Context, T > ReadableOwned< C > for T where T: for< 'b > Readable< 'b, C > {
+    #[inline]
+    fn speedy_read_from_any_buffer( context: C, buffer: &[u8] ) -> io::Result< Self > {
+        T::read_from_buffer( context, buffer )
+    }
+}
+
+pub trait Readable< 'a, C: Context >: Sized {
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self >;
+
+    /// Same as `read_from`, except the value is overwritten in place,
+    /// which allows it to reuse its existing allocations (e.g. of its `Vec`s).
+    ///
+    /// If this fails the value is left in a valid, but unspecified state.
+    #[inline]
+    fn read_in_place< R: Reader< 'a, C > >( &mut self, reader: &mut R ) -> io::Result< () > {
+        *self = Self::read_from( reader )?;
+        Ok(())
+    }
 
     #[inline]
     fn minimum_bytes_needed() -> usize {
         0
     }
 
+    // These are only implemented by the primitive types, which allows
+    // containers to read a whole slice of them at once.
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_is_primitive() -> bool {
+        false
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    unsafe fn speedy_slice_as_bytes_mut( _: &mut [Self] ) -> &mut [u8] {
+        panic!()
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_convert_slice_endianness( _: Endianness, _: &mut [Self] ) {}
+
+    // These decide how an `Option< Self >` is read, which allows types
+    // with a niche (e.g. `NonZeroU32`) to get by without a discriminant.
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_read_option< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Option< Self > > {
+        match reader.read_u8()? {
+            0 => Ok( None ),
+            1 => Ok( Some( reader.read_value()? ) ),
+            _ => Err( io::Error::new( io::ErrorKind::InvalidData, "invalid option discriminant" ) )
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_option_minimum_bytes_needed() -> usize {
+        1
+    }
+
+    /// Deserializes the value from a buffer; whenever possible
+    /// the value will borrow its data straight from the `buffer`.
+    ///
+    /// A `Cow` of a multi-byte primitive slice (e.g. `Cow< [u32] >`) is only
+    /// borrowed when the buffer's endianness matches the native one and its data
+    /// is properly aligned within the `buffer`; otherwise it'll be an owned copy.
+    #[inline]
+    fn read_from_buffer( context: C, buffer: &'a [u8] ) -> io::Result< Self > {
+        let mut reader = BufferReader::new( context, buffer );
+        Self::read_from( &mut reader )
+    }
+
+    /// Same as `read_from_buffer`, except the value is read from several buffers
+    /// one after another as if they were concatenated; see `ChainedReader`.
+    #[inline]
+    fn read_from_buffers( context: C, buffers: &[&'a [u8]] ) -> io::Result< Self > {
+        let mut reader = ChainedReader::new( context, buffers );
+        Self::read_from( &mut reader )
+    }
+
+    /// Same as `read_from_buffer`, except an existing value is overwritten; see `read_in_place`.
+    #[inline]
+    fn read_into( &mut self, context: C, buffer: &'a [u8] ) -> io::Result< () > {
+        let mut reader = BufferReader::new( context, buffer );
+        self.read_in_place( &mut reader )
+    }
+
+    /// Same as `read_from_buffer`, except it takes anything which can be viewed
+    /// as bytes, e.g. a `Vec< u8 >` or an array, which can be passed by value.
+    ///
+    /// Since the buffer may not outlive this call this only works
+    /// for types which don't borrow anything from it.
+    #[inline]
+    fn read_from_owned_buffer< B: AsRef< [u8] > >( context: C, buffer: B ) -> io::Result< Self > where Self: ReadableOwned< C > {
+        Self::speedy_read_from_any_buffer( context, buffer.as_ref() )
+    }
+
+    /// Same as `read_from_buffer`, except any `Bytes` are read
+    /// without copying by sharing the allocation of the `buffer`.
+    #[cfg(feature = "bytes")]
     #[inline]
-    fn read_from_buffer( context: C, mut buffer: &[u8] ) -> io::Result< Self > {
-        DirectSyncReader::deserialize( context, &mut buffer )
+    fn read_from_bytes( context: C, buffer: &'a ::bytes::Bytes ) -> io::Result< Self > {
+        let mut reader = ::ext_bytes::SharedBytesReader::new( context, buffer );
+        Self::read_from( &mut reader )
+    }
+
+    /// Deserializes the value from the whole contents of a file.
+    #[inline]
+    fn read_from_file< P: AsRef< Path > >( context: C, path: P ) -> io::Result< Self > where Self: ReadableOwned< C > {
+        let buffer = fs::read( path )?;
+        Self::read_from_owned_buffer( context, buffer )
+    }
+
+    /// Deserializes a value written by `Writable::write_to_vec_debug`.
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if the field names don't match.
+    #[inline]
+    fn read_from_buffer_debug( context: C, buffer: &'a [u8] ) -> io::Result< Self > {
+        let mut reader = DebugReader::new( context, buffer );
+        let value = Self::read_from( &mut reader )?;
+        reader.check()?;
+        Ok( value )
+    }
+
+    /// Deserializes the value from a memory mapped file (see `map_file`),
+    /// borrowing from the mapping exactly like `read_from_buffer` would.
+    #[cfg(feature = "memmap2")]
+    #[inline]
+    fn read_from_mmap( context: C, mmap: &'a ::memmap2::Mmap ) -> io::Result< Self > {
+        Self::read_from_buffer( context, mmap )
     }
 
     #[inline]
     fn read_from_stream< S: Read >( context: C, stream: S ) -> io::Result< Self > {
         DirectSyncReader::deserialize( context, stream )
     }
+
+    /// Same as `read_from_stream`, except the stream is read in bigger chunks
+    /// through an internal buffer.
+    ///
+    /// This is a lot faster when the stream is unbuffered (e.g. a raw `File`
+    /// or `TcpStream`), however more bytes than necessary may be consumed from the stream.
+    #[inline]
+    fn read_from_stream_buffered< S: Read >( context: C, stream: S ) -> io::Result< Self > {
+        BufferedStreamReader::deserialize( context, stream )
+    }
+
+    /// Returns an iterator which decodes a stream of length-prefixed messages.
+    ///
+    /// The iterator ends cleanly when the stream ends at a frame boundary;
+    /// running out of data in the middle of a frame is an error.
+    #[inline]
+    fn stream_from_reader< S: Read >( context: C, stream: S ) -> MessageStream< C, S, Self > {
+        MessageStream {
+            reader: BufferedStreamReader::new( context, stream ),
+            finished: false,
+            phantom: PhantomData
+        }
+    }
+}
+
+/// Reads a length-prefixed byte blob, e.g. a serialized `Vec< u8 >`, from `buffer`
+/// straight into `output` without allocating, returning its length.
+///
+/// This is the same as `Reader::read_bytes_into_slice` on a `BufferReader`.
+#[inline]
+pub fn read_bytes_from_buffer_into< C: Context >( context: C, buffer: &[u8], output: &mut [u8] ) -> io::Result< usize > {
+    let mut reader = BufferReader::new( context, buffer );
+    reader.read_bytes_into_slice( output )
 }
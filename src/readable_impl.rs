@@ -1,21 +1,34 @@
 use std::io;
-use std::mem;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::cmp;
+use std::slice;
+use std::str;
 use std::borrow::Cow;
+use std::convert::Infallible;
+use std::collections::BinaryHeap;
+use std::num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64};
+use std::sync::atomic::{AtomicU8, AtomicU16, AtomicU32, AtomicI8, AtomicI16, AtomicI32};
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicU64, AtomicI64};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use readable::Readable;
 use reader::Reader;
 
 use context::Context;
+use endianness::Endianness;
 use utils::as_bytes_mut;
 
-impl< C: Context > Readable< C > for bool {
+impl< 'a, C: Context > Readable< 'a, C > for bool {
     #[inline]
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
         let value = try!( reader.read_u8() );
-        if value == 0 {
-            Ok( false )
-        } else {
-            Ok( true )
+        match value {
+            0 => Ok( false ),
+            1 => Ok( true ),
+            _ if reader.context().strict_bool() => Err( io::Error::new( io::ErrorKind::InvalidData, "invalid bool value" ) ),
+            _ => Ok( true )
         }
     }
 
@@ -26,10 +39,10 @@ impl< C: Context > Readable< C > for bool {
 }
 
 macro_rules! impl_for_primitive {
-    ($type:ty, $getter:ident) => {
-        impl< C: Context > Readable< C > for $type {
+    ($type:ty, $getter:ident, |$endianness:ident, $slice:ident| $convert:expr) => {
+        impl< 'a, C: Context > Readable< 'a, C > for $type {
             #[inline]
-            fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
+            fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
                 reader.$getter()
             }
 
@@ -37,30 +50,155 @@ macro_rules! impl_for_primitive {
             fn minimum_bytes_needed() -> usize {
                 mem::size_of::< Self >()
             }
+
+            #[inline]
+            fn speedy_is_primitive() -> bool {
+                true
+            }
+
+            #[inline]
+            unsafe fn speedy_slice_as_bytes_mut( slice: &mut [Self] ) -> &mut [u8] {
+                as_bytes_mut( slice )
+            }
+
+            #[inline]
+            fn speedy_convert_slice_endianness( $endianness: Endianness, $slice: &mut [Self] ) {
+                $convert
+            }
         }
     }
 }
 
-impl_for_primitive!( i8, read_i8 );
-impl_for_primitive!( i16, read_i16 );
-impl_for_primitive!( i32, read_i32 );
-impl_for_primitive!( i64, read_i64 );
-impl_for_primitive!( u8, read_u8 );
-impl_for_primitive!( u16, read_u16 );
-impl_for_primitive!( u32, read_u32 );
-impl_for_primitive!( u64, read_u64 );
-impl_for_primitive!( f32, read_f32 );
-impl_for_primitive!( f64, read_f64 );
+impl_for_primitive!( i8, read_i8, |_endianness, _slice| {} );
+impl_for_primitive!( i16, read_i16, |endianness, slice| endianness.swap_slice_i16( slice ) );
+impl_for_primitive!( i32, read_i32, |endianness, slice| endianness.swap_slice_i32( slice ) );
+impl_for_primitive!( i64, read_i64, |endianness, slice| endianness.swap_slice_i64( slice ) );
+impl_for_primitive!( u8, read_u8, |_endianness, _slice| {} );
+impl_for_primitive!( u16, read_u16, |endianness, slice| endianness.swap_slice_u16( slice ) );
+impl_for_primitive!( u32, read_u32, |endianness, slice| endianness.swap_slice_u32( slice ) );
+impl_for_primitive!( u64, read_u64, |endianness, slice| endianness.swap_slice_u64( slice ) );
+impl_for_primitive!( f32, read_f32, |endianness, slice| endianness.swap_slice_f32( slice ) );
+impl_for_primitive!( f64, read_f64, |endianness, slice| endianness.swap_slice_f64( slice ) );
 
-impl< C: Context > Readable< C > for Vec< u8 > {
-    #[inline]
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
-        let length = try!( reader.read_u32() ) as usize;
-        let mut vec = Vec::with_capacity( length );
-        unsafe { vec.set_len( length ); }
-        try!( reader.read_bytes( &mut vec[..] ) );
+/// At most this many bytes are preallocated for a collection's elements
+/// up front; any more are only allocated as the elements are actually read.
+const MAX_PREALLOCATED_BYTES: usize = 1024 * 1024;
+
+/// Reads an `u32` length prefix in the reader's `length_endianness`.
+#[inline]
+pub fn read_length_prefix< 'a, C: Context, R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< usize > {
+    let length = reader.read_u32()?;
+    if reader.length_endianness() != reader.endianness() {
+        return Ok( length.swap_bytes() as usize );
+    }
+
+    Ok( length as usize )
+}
+
+/// Reads the length prefix of a collection whose elements are of type `T`,
+/// rejecting it if the reader can tell the input is too short for it.
+#[inline]
+pub fn read_length< 'a, C: Context, R: Reader< 'a, C >, T: Readable< 'a, C > >( reader: &mut R ) -> io::Result< usize > {
+    let length = read_length_prefix( reader )?;
+    let minimum_bytes_needed = T::minimum_bytes_needed();
+    if let Some( remaining ) = reader.remaining_bytes_hint() {
+        if minimum_bytes_needed > 0 && length > remaining / minimum_bytes_needed {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "length prefix is bigger than the remaining input" ) );
+        }
+    }
+
+    Ok( length )
+}
+
+/// Returns how many elements of type `T` should be preallocated
+/// for a collection of the given length.
+#[inline]
+pub fn preallocated_capacity< T >( length: usize ) -> usize {
+    cmp::min( length, MAX_PREALLOCATED_BYTES / cmp::max( mem::size_of::< T >(), 1 ) )
+}
 
+// Reads the elements straight into the memory of the `Vec`, which only grows once they're read,
+// so it never contains any elements which weren't read, even if the input was truncated.
+//
+// Unless the whole input is in memory and the reader knows that enough of it is left
+// the length could be bogus (even the remaining length of a frame read from a stream
+// is only as trustworthy as its own prefix), so then the memory is allocated piece by
+// piece as the input actually comes in, which also bounds how much is read at a time.
+#[inline]
+fn read_primitive_elements_into< 'a, C: Context, R: Reader< 'a, C >, T: Readable< 'a, C > >( reader: &mut R, length: usize, vec: &mut Vec< T > ) -> io::Result< () > {
+    byte_length::< T >( length )?;
+    vec.clear();
+    let is_in_memory = reader.can_borrow() && reader.remaining_bytes_hint().is_some();
+    while vec.len() < length {
+        let count = if is_in_memory {
+            length - vec.len()
+        } else {
+            preallocated_capacity::< T >( length - vec.len() )
+        };
+
+        let position = vec.len();
+        vec.reserve( count );
+        unsafe {
+            let pointer = vec.as_mut_ptr().add( position );
+            ptr::write_bytes( pointer, 0, count );
+            reader.read_bytes( T::speedy_slice_as_bytes_mut( slice::from_raw_parts_mut( pointer, count ) ) )?;
+            vec.set_len( position + count );
+        }
+    }
+
+    T::speedy_convert_slice_endianness( reader.endianness(), vec );
+    Ok(())
+}
+
+/// Reads `length` elements which aren't prefixed with their length.
+#[inline]
+pub fn read_elements< 'a, C: Context, R: Reader< 'a, C >, T: Readable< 'a, C > >( reader: &mut R, length: usize ) -> io::Result< Vec< T > > {
+    if T::speedy_is_primitive() {
+        let mut vec = Vec::new();
+        read_primitive_elements_into( reader, length, &mut vec )?;
         Ok( vec )
+    } else {
+        let mut vec = Vec::with_capacity( preallocated_capacity::< T >( length ) );
+        for _ in 0..length {
+            vec.push( reader.read_value()? );
+        }
+
+        Ok( vec )
+    }
+}
+
+/// Same as `read_elements`, except the elements are read into an existing `Vec`,
+/// reusing its allocation and the allocations of the elements which it already has.
+#[inline]
+pub fn read_elements_into< 'a, C: Context, R: Reader< 'a, C >, T: Readable< 'a, C > >( reader: &mut R, length: usize, vec: &mut Vec< T > ) -> io::Result< () > {
+    if T::speedy_is_primitive() {
+        read_primitive_elements_into( reader, length, vec )?;
+    } else {
+        vec.truncate( length );
+        for element in vec.iter_mut() {
+            reader.read_value_into( element )?;
+        }
+
+        vec.reserve( preallocated_capacity::< T >( length - vec.len() ) );
+        while vec.len() < length {
+            vec.push( reader.read_value()? );
+        }
+    }
+
+    Ok(())
+}
+
+impl< 'a, C: Context, T: Readable< 'a, C > > Readable< 'a, C > for Vec< T > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, T >( reader )?;
+        read_elements( reader, length )
+    }
+
+    #[inline]
+    fn read_in_place< R: Reader< 'a, C > >( &mut self, reader: &mut R ) -> io::Result< () > {
+        let length = read_length::< C, R, T >( reader )?;
+        read_elements_into( reader, length, self )
     }
 
     #[inline]
@@ -69,49 +207,156 @@ impl< C: Context > Readable< C > for Vec< u8 > {
     }
 }
 
-impl< 'a, C: Context > Readable< C > for Cow< 'a, [u8] > {
+impl< 'a, C: Context > Readable< 'a, C > for Cow< 'a, [u8] > {
     #[inline]
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
-        let bytes: Vec< u8 > = try!( reader.read_value() );
-        Ok( bytes.into() )
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        if !reader.can_borrow() {
+            let vec: Vec< u8 > = reader.read_value()?;
+            return Ok( Cow::Owned( vec ) );
+        }
+
+        let length = read_length::< C, R, u8 >( reader )?;
+        reader.read_cow_bytes( length )
     }
 
     #[inline]
     fn minimum_bytes_needed() -> usize {
-        <Vec< u8 > as Readable< C >>::minimum_bytes_needed()
+        <Vec< u8 > as Readable< 'a, C >>::minimum_bytes_needed()
     }
 }
 
-impl< C: Context > Readable< C > for Vec< i8 > {
+impl< 'a, C: Context > Readable< 'a, C > for Cow< 'a, [i8] > {
     #[inline]
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
-        let vec: Vec< u8 > = try!( reader.read_value() );
-        let vec: Vec< i8 > = unsafe { mem::transmute( vec ) };
-        Ok( vec )
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let bytes: Cow< 'a, [u8] > = reader.read_value()?;
+        let bytes = match bytes {
+            Cow::Borrowed( bytes ) => Cow::Borrowed( unsafe { slice::from_raw_parts( bytes.as_ptr() as *const i8, bytes.len() ) } ),
+            Cow::Owned( bytes ) => Cow::Owned( unsafe { mem::transmute::< Vec< u8 >, Vec< i8 > >( bytes ) } )
+        };
+
+        Ok( bytes )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        <Vec< i8 > as Readable< 'a, C >>::minimum_bytes_needed()
+    }
+}
+
+/// Borrows the bytes straight from the input, so this can only be read
+/// from a reader which is backed by a buffer, e.g. by `read_from_buffer`.
+impl< 'a, C: Context > Readable< 'a, C > for &'a [u8] {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, u8 >( reader )?;
+        if !reader.can_borrow() {
+            return Err( io::Error::new( io::ErrorKind::InvalidInput, "a borrowed slice can only be read from a buffer" ) );
+        }
+
+        match reader.read_cow_bytes( length )? {
+            Cow::Borrowed( bytes ) => Ok( bytes ),
+            Cow::Owned( _ ) => Err( io::Error::new( io::ErrorKind::InvalidInput, "a borrowed slice can only be read from a buffer" ) )
+        }
     }
 
     #[inline]
     fn minimum_bytes_needed() -> usize {
-        <Vec< u8 > as Readable< C >>::minimum_bytes_needed()
+        <Vec< u8 > as Readable< 'a, C >>::minimum_bytes_needed()
     }
 }
 
-impl< 'a, C: Context > Readable< C > for Cow< 'a, [i8] > {
+/// Borrows the string straight from the input, so just like `&[u8]` this
+/// can only be read from a reader which is backed by a buffer.
+impl< 'a, C: Context > Readable< 'a, C > for &'a str {
     #[inline]
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
-        let bytes: Vec< i8 > = try!( reader.read_value() );
-        Ok( bytes.into() )
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let bytes: &'a [u8] = reader.read_value()?;
+        str::from_utf8( bytes ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )
     }
 
     #[inline]
     fn minimum_bytes_needed() -> usize {
-        <Vec< i8 > as Readable< C >>::minimum_bytes_needed()
+        <Vec< u8 > as Readable< 'a, C >>::minimum_bytes_needed()
     }
 }
 
-impl< C: Context > Readable< C > for String {
+impl< 'a, C: Context, T: Readable< 'a, C > > Readable< 'a, C > for Option< T > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        T::speedy_read_option( reader )
+    }
+
     #[inline]
-    fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
+    fn minimum_bytes_needed() -> usize {
+        T::speedy_option_minimum_bytes_needed()
+    }
+}
+
+impl< 'a, C: Context, T: Readable< 'a, C >, E: Readable< 'a, C > > Readable< 'a, C > for Result< T, E > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        match reader.read_u8()? {
+            0 => Ok( Ok( reader.read_value()? ) ),
+            1 => Ok( Err( reader.read_value()? ) ),
+            _ => Err( io::Error::new( io::ErrorKind::InvalidData, "invalid result discriminant" ) )
+        }
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        1 + cmp::min( T::minimum_bytes_needed(), E::minimum_bytes_needed() )
+    }
+}
+
+// There are no values of this type, so there's nothing which could be read.
+impl< 'a, C: Context > Readable< 'a, C > for Infallible {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( _: &mut R ) -> io::Result< Self > {
+        Err( io::Error::new( io::ErrorKind::InvalidData, "a value of an uninhabited type can't be read" ) )
+    }
+}
+
+macro_rules! impl_for_non_zero {
+    ($type:ident, $inner:ty) => {
+        impl< 'a, C: Context > Readable< 'a, C > for $type {
+            #[inline]
+            fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+                let value: $inner = reader.read_value()?;
+                $type::new( value ).ok_or_else( || io::Error::new( io::ErrorKind::InvalidData, "expected a non-zero integer" ) )
+            }
+
+            #[inline]
+            fn minimum_bytes_needed() -> usize {
+                mem::size_of::< $inner >()
+            }
+
+            // A `None` is encoded as a zero.
+            #[inline]
+            fn speedy_read_option< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Option< Self > > {
+                let value: $inner = reader.read_value()?;
+                Ok( $type::new( value ) )
+            }
+
+            #[inline]
+            fn speedy_option_minimum_bytes_needed() -> usize {
+                mem::size_of::< $inner >()
+            }
+        }
+    }
+}
+
+impl_for_non_zero!( NonZeroU8, u8 );
+impl_for_non_zero!( NonZeroU16, u16 );
+impl_for_non_zero!( NonZeroU32, u32 );
+impl_for_non_zero!( NonZeroU64, u64 );
+impl_for_non_zero!( NonZeroI8, i8 );
+impl_for_non_zero!( NonZeroI16, i16 );
+impl_for_non_zero!( NonZeroI32, i32 );
+impl_for_non_zero!( NonZeroI64, i64 );
+
+impl< 'a, C: Context > Readable< 'a, C > for String {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
         let bytes: Vec< u8 > = try!( reader.read_value() );
         match String::from_utf8( bytes ) {
             Err( error ) => Err( io::Error::new( io::ErrorKind::InvalidData, error ) ),
@@ -119,52 +364,226 @@ impl< C: Context > Readable< C > for String {
         }
     }
 
+    #[inline]
+    fn read_in_place< R: Reader< 'a, C > >( &mut self, reader: &mut R ) -> io::Result< () > {
+        let mut bytes = mem::take( self ).into_bytes();
+        reader.read_value_into( &mut bytes )?;
+        *self = String::from_utf8( bytes ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )?;
+        Ok(())
+    }
+
     #[inline]
     fn minimum_bytes_needed() -> usize {
-        <Vec< u8 > as Readable< C >>::minimum_bytes_needed()
+        <Vec< u8 > as Readable< 'a, C >>::minimum_bytes_needed()
     }
 }
 
+impl< 'a, C: Context > Readable< 'a, C > for Cow< 'a, str > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let string = match reader.read_value()? {
+            Cow::Borrowed( bytes ) => str::from_utf8( bytes ).map( Cow::Borrowed ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )?,
+            Cow::Owned( bytes ) => String::from_utf8( bytes ).map( Cow::Owned ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )?
+        };
+
+        Ok( string )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        <Vec< u8 > as Readable< 'a, C >>::minimum_bytes_needed()
+    }
+}
+
+/// Returns the size in bytes of `length` elements of type `T`, failing
+/// instead of overflowing if the length prefix was bogus.
+#[inline]
+pub fn byte_length< T >( length: usize ) -> io::Result< usize > {
+    length.checked_mul( mem::size_of::< T >() )
+        .ok_or_else( || io::Error::new( io::ErrorKind::InvalidData, "length overflow: the slice is too big to fit in memory" ) )
+}
+
 macro_rules! impl_for_primitive_slice {
-    ($type:ty, $endianness_swap:ident) => {
-        impl< C: Context > Readable< C > for Vec< $type > {
+    ($type:ty) => {
+        /// Borrows the slice straight from the buffer if no endianness
+        /// conversion is necessary and the data in the buffer is suitably
+        /// aligned for the element type; otherwise falls back to an owned copy.
+        impl< 'a, C: Context > Readable< 'a, C > for Cow< 'a, [$type] > {
             #[inline]
-            fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
-                let length = try!( reader.read_u32() ) as usize;
-                let mut vec = Vec::with_capacity( length );
-                unsafe { vec.set_len( length ); }
-                try!( reader.read_bytes( as_bytes_mut( &mut vec ) ) );
-                reader.endianness().$endianness_swap( &mut vec );
+            fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+                if reader.endianness().conversion_necessary() || !reader.can_borrow() {
+                    let vec: Vec< $type > = reader.read_value()?;
+                    return Ok( Cow::Owned( vec ) );
+                }
 
-                Ok( vec )
+                let length = read_length::< C, R, $type >( reader )?;
+                let bytes = reader.read_cow_bytes( byte_length::< $type >( length )? )?;
+                match bytes {
+                    Cow::Borrowed( bytes ) if bytes.as_ptr() as usize % mem::align_of::< $type >() == 0 => {
+                        let slice = unsafe { slice::from_raw_parts( bytes.as_ptr() as *const $type, length ) };
+                        Ok( Cow::Borrowed( slice ) )
+                    },
+                    bytes => {
+                        let mut vec: Vec< $type > = vec![ 0 as $type; length ];
+                        as_bytes_mut( &mut vec ).copy_from_slice( &bytes );
+                        Ok( Cow::Owned( vec ) )
+                    }
+                }
             }
 
             #[inline]
             fn minimum_bytes_needed() -> usize {
-                <Vec< u8 > as Readable< C >>::minimum_bytes_needed()
+                <Vec< $type > as Readable< 'a, C >>::minimum_bytes_needed()
             }
         }
+    }
+}
+
+impl_for_primitive_slice!( i16 );
+impl_for_primitive_slice!( i32 );
+impl_for_primitive_slice!( i64 );
+impl_for_primitive_slice!( u16 );
+impl_for_primitive_slice!( u32 );
+impl_for_primitive_slice!( u64 );
+impl_for_primitive_slice!( f32 );
+impl_for_primitive_slice!( f64 );
+
+impl< 'a, C: Context, T: Readable< 'a, C >, const N: usize > Readable< 'a, C > for [T; N] {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let mut array: [MaybeUninit< T >; N] = unsafe { MaybeUninit::uninit().assume_init() };
+        for index in 0..N {
+            match reader.read_value() {
+                Ok( value ) => array[ index ] = MaybeUninit::new( value ),
+                Err( error ) => {
+                    for element in &mut array[ ..index ] {
+                        unsafe { ptr::drop_in_place( element.as_mut_ptr() ); }
+                    }
+                    return Err( error );
+                }
+            }
+        }
+
+        Ok( unsafe { ptr::read( array.as_ptr() as *const [T; N] ) } )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        T::minimum_bytes_needed() * N
+    }
+}
+
+// The elements of a tuple are simply stored one after another.
+macro_rules! impl_for_tuple {
+    ($($type:ident $index:tt),+) => {
+        impl< 'a, C: Context, $($type: Readable< 'a, C >),+ > Readable< 'a, C > for ($($type,)+) {
+            #[inline]
+            fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+                Ok( ($(reader.read_value::< $type >()?,)+) )
+            }
+
+            #[inline]
+            fn read_in_place< R: Reader< 'a, C > >( &mut self, reader: &mut R ) -> io::Result< () > {
+                $(reader.read_value_into( &mut self.$index )?;)+
+                Ok(())
+            }
+
+            #[inline]
+            fn minimum_bytes_needed() -> usize {
+                0 $(+ <$type as Readable< 'a, C >>::minimum_bytes_needed())+
+            }
+        }
+    }
+}
+
+impl_for_tuple!( T0 0 );
+impl_for_tuple!( T0 0, T1 1 );
+impl_for_tuple!( T0 0, T1 1, T2 2 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11 );
+
+impl< 'a, C: Context, T: Readable< 'a, C > + Ord > Readable< 'a, C > for BinaryHeap< T > {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let length = read_length::< C, R, T >( reader )?;
+        let mut vec = Vec::with_capacity( preallocated_capacity::< T >( length ) );
+        for _ in 0..length {
+            vec.push( reader.read_value()? );
+        }
+
+        Ok( BinaryHeap::from( vec ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for Duration {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let secs = reader.read_u64()?;
+        let nanos = reader.read_u32()?;
+        if nanos >= 1_000_000_000 {
+            return Err( io::Error::new( io::ErrorKind::InvalidData, "invalid duration: too many nanoseconds" ) );
+        }
+
+        Ok( Duration::new( secs, nanos ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        12
+    }
+}
+
+impl< 'a, C: Context > Readable< 'a, C > for SystemTime {
+    #[inline]
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+        let duration: Duration = reader.read_value()?;
+        UNIX_EPOCH.checked_add( duration )
+            .ok_or_else( || io::Error::new( io::ErrorKind::InvalidData, "invalid system time: out of range" ) )
+    }
+
+    #[inline]
+    fn minimum_bytes_needed() -> usize {
+        <Duration as Readable< 'a, C >>::minimum_bytes_needed()
+    }
+}
 
-        impl< 'a, C: Context > Readable< C > for Cow< 'a, [$type] > {
+macro_rules! impl_for_atomic {
+    ($type:ident, $inner:ty) => {
+        /// The value is read into a new, independent atomic.
+        impl< 'a, C: Context > Readable< 'a, C > for $type {
             #[inline]
-            fn read_from< R: Reader< C > >( reader: &mut R ) -> io::Result< Self > {
-                let bytes: Vec< $type > = try!( reader.read_value() );
-                Ok( bytes.into() )
+            fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> io::Result< Self > {
+                let value: $inner = reader.read_value()?;
+                Ok( $type::new( value ) )
             }
 
             #[inline]
             fn minimum_bytes_needed() -> usize {
-                <Vec< $type > as Readable< C >>::minimum_bytes_needed()
+                mem::size_of::< $inner >()
             }
         }
     }
 }
 
-impl_for_primitive_slice!( i16, swap_slice_i16 );
-impl_for_primitive_slice!( i32, swap_slice_i32 );
-impl_for_primitive_slice!( i64, swap_slice_i64 );
-impl_for_primitive_slice!( u16, swap_slice_u16 );
-impl_for_primitive_slice!( u32, swap_slice_u32 );
-impl_for_primitive_slice!( u64, swap_slice_u64 );
-impl_for_primitive_slice!( f32, swap_slice_f32 );
-impl_for_primitive_slice!( f64, swap_slice_f64 );
+impl_for_atomic!( AtomicU8, u8 );
+impl_for_atomic!( AtomicU16, u16 );
+impl_for_atomic!( AtomicU32, u32 );
+#[cfg(target_has_atomic = "64")]
+impl_for_atomic!( AtomicU64, u64 );
+impl_for_atomic!( AtomicI8, i8 );
+impl_for_atomic!( AtomicI16, i16 );
+impl_for_atomic!( AtomicI32, i32 );
+#[cfg(target_has_atomic = "64")]
+impl_for_atomic!( AtomicI64, i64 );
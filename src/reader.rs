@@ -1,14 +1,66 @@
 use std::io;
+use std::cmp;
 use std::mem;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::iter::FromIterator;
 
 use endianness::Endianness;
 use readable::Readable;
 use context::Context;
+use readable_impl::{read_length, read_length_prefix, read_elements, preallocated_capacity};
 
-pub trait Reader< C: Context >: Sized {
+pub trait Reader< 'a, C: Context >: Sized {
     fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () >;
     fn context( &self ) -> &C;
 
+    /// Reads `length` bytes, borrowing them straight from the input
+    /// if the reader is backed by a buffer, or copying them otherwise.
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        let mut vec = vec![ 0; length ];
+        self.read_bytes( &mut vec )?;
+        Ok( Cow::Owned( vec ) )
+    }
+
+    /// Returns the number of bytes which can still be read, if known.
+    ///
+    /// This is used to reject bogus length prefixes before allocating memory for them.
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        None
+    }
+
+    /// Whether `read_cow_bytes` borrows from the input instead of copying it.
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        false
+    }
+
+    /// Fills `output` with the upcoming bytes without consuming them.
+    ///
+    /// Not every reader supports this, e.g. one reading straight from
+    /// an unbuffered stream fails with `io::ErrorKind::Unsupported`.
+    #[inline]
+    fn peek_bytes( &mut self, _output: &mut [u8] ) -> io::Result< () > {
+        Err( io::Error::new( io::ErrorKind::Unsupported, "peeking is not supported by this reader" ) )
+    }
+
+    #[inline]
+    fn peek_u8( &mut self ) -> io::Result< u8 > {
+        let mut slice = [0; 1];
+        self.peek_bytes( &mut slice )?;
+        Ok( slice[0] )
+    }
+
+    /// Peeks an `u32`, e.g. the tag of an enum derived with the default tag type.
+    #[inline]
+    fn peek_u32( &mut self ) -> io::Result< u32 > {
+        let mut slice = [0; 4];
+        self.peek_bytes( &mut slice )?;
+        Ok( self.endianness().read_u32( &slice ) )
+    }
+
     #[inline]
     fn read_u8( &mut self ) -> io::Result< u8 > {
         let mut slice: [u8; 1] = unsafe { mem::uninitialized() };
@@ -25,65 +77,393 @@ pub trait Reader< C: Context >: Sized {
     fn read_u16( &mut self ) -> io::Result< u16 > {
         let mut slice: [u8; 2] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_u16( &slice ) )
+        Ok( self.endianness().read_u16( &slice ) )
     }
 
     #[inline]
     fn read_i16( &mut self ) -> io::Result< i16 > {
         let mut slice: [u8; 2] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_i16( &slice ) )
+        Ok( self.endianness().read_i16( &slice ) )
     }
 
     #[inline]
     fn read_u32( &mut self ) -> io::Result< u32 > {
         let mut slice: [u8; 4] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_u32( &slice ) )
+        Ok( self.endianness().read_u32( &slice ) )
     }
 
     #[inline]
     fn read_i32( &mut self ) -> io::Result< i32 > {
         let mut slice: [u8; 4] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_i32( &slice ) )
+        Ok( self.endianness().read_i32( &slice ) )
     }
 
     #[inline]
     fn read_u64( &mut self ) -> io::Result< u64 > {
         let mut slice: [u8; 8] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_u64( &slice ) )
+        Ok( self.endianness().read_u64( &slice ) )
     }
 
     #[inline]
     fn read_i64( &mut self ) -> io::Result< i64 > {
         let mut slice: [u8; 8] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_i64( &slice ) )
+        Ok( self.endianness().read_i64( &slice ) )
     }
 
     #[inline]
     fn read_f32( &mut self ) -> io::Result< f32 > {
         let mut slice: [u8; 4] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_f32( &slice ) )
+        Ok( self.endianness().read_f32( &slice ) )
     }
 
     #[inline]
     fn read_f64( &mut self ) -> io::Result< f64 > {
         let mut slice: [u8; 8] = unsafe { mem::uninitialized() };
         try!( self.read_bytes( &mut slice ) );
-        Ok( self.context().endianness().read_f64( &slice ) )
+        Ok( self.endianness().read_f64( &slice ) )
+    }
+
+    /// Reads an `u64` written by `Writer::write_varint_u64`.
+    #[inline]
+    fn read_varint_u64( &mut self ) -> io::Result< u64 > {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            if shift == 63 && byte > 1 {
+                return Err( io::Error::new( io::ErrorKind::InvalidData, "varint doesn't fit into an u64" ) );
+            }
+
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok( value );
+            }
+
+            shift += 7;
+        }
+    }
+
+    /// Reads everything which is left, e.g. the rest of a buffer or a stream until it ends.
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let mut output = Vec::new();
+        loop {
+            match self.read_u8() {
+                Ok( byte ) => output.push( byte ),
+                Err( ref error ) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok( output ),
+                Err( error ) => return Err( error )
+            }
+        }
+    }
+
+    /// Reads a length-prefixed byte blob (e.g. a serialized `Vec< u8 >`)
+    /// straight into `output` without allocating, returning its length.
+    ///
+    /// Fails if `output` is too small, in which case only the length prefix is consumed.
+    #[inline]
+    fn read_bytes_into_slice( &mut self, output: &mut [u8] ) -> io::Result< usize > {
+        let length = read_length_prefix( self )?;
+        if length > output.len() {
+            return Err( io::Error::new( io::ErrorKind::InvalidData, "destination buffer is too small" ) );
+        }
+
+        self.read_bytes( &mut output[ ..length ] )?;
+        Ok( length )
+    }
+
+    /// Consumes `count` bytes without looking at them.
+    #[inline]
+    fn skip_bytes( &mut self, count: usize ) -> io::Result< () > {
+        if self.can_borrow() {
+            self.read_cow_bytes( count )?;
+            return Ok(());
+        }
+
+        let mut buffer = [0; 1024];
+        let mut remaining = count;
+        while remaining > 0 {
+            let length = cmp::min( remaining, buffer.len() );
+            self.read_bytes( &mut buffer[ ..length ] )?;
+            remaining -= length;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a string stored in exactly `length` bytes and padded with trailing NULs.
+    #[inline]
+    fn read_fixed_string( &mut self, length: usize ) -> io::Result< String > {
+        let mut bytes = vec![ 0; length ];
+        self.read_bytes( &mut bytes )?;
+
+        let end = bytes.iter().rposition( |&byte| byte != 0 ).map( |index| index + 1 ).unwrap_or( 0 );
+        bytes.truncate( end );
+        String::from_utf8( bytes ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )
+    }
+
+    /// Reads exactly `count` elements which are not prefixed with their length.
+    #[inline]
+    fn read_fixed_count< T: Readable< 'a, C > >( &mut self, count: usize ) -> io::Result< Vec< T > > {
+        read_elements( self, count )
+    }
+
+    /// Reads elements which are not prefixed with their length until the input ends.
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if the input ends in the middle of an element.
+    /// This needs to know where the input ends, so it's unsupported when reading straight
+    /// from an unbuffered stream.
+    #[inline]
+    fn read_remaining_elements< T: Readable< 'a, C > >( &mut self ) -> io::Result< Vec< T > > {
+        let mut elements = Vec::new();
+        loop {
+            let is_at_end = match self.remaining_bytes_hint() {
+                Some( remaining ) => remaining == 0,
+                None => match self.peek_u8() {
+                    Ok( _ ) => false,
+                    Err( ref error ) if error.kind() == io::ErrorKind::UnexpectedEof => true,
+                    Err( error ) => return Err( error )
+                }
+            };
+
+            if is_at_end {
+                return Ok( elements );
+            }
+
+            match self.read_value() {
+                Ok( element ) => elements.push( element ),
+                Err( ref error ) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err( io::Error::new( io::ErrorKind::InvalidData, "the input ends in the middle of an element" ) );
+                },
+                Err( error ) => return Err( error )
+            }
+        }
+    }
+
+    /// Reads a length-prefixed collection (e.g. a serialized `Vec< T >`) and calls `callback`
+    /// with every element as soon as it's read instead of collecting them; returns their count.
+    ///
+    /// ```rust
+    /// use speedy::{BufferReader, Endianness, Reader, Writable};
+    ///
+    /// let data = vec![ 1_u32, 2, 3 ].write_to_vec( Endianness::LittleEndian ).unwrap();
+    /// let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+    /// let mut sum = 0;
+    /// let count = reader.read_collection_with( |value: u32| sum += value ).unwrap();
+    /// assert_eq!( (count, sum), (3, 6) );
+    /// ```
+    #[inline]
+    fn read_collection_with< T: Readable< 'a, C >, F: FnMut( T ) >( &mut self, mut callback: F ) -> io::Result< usize > {
+        let length = read_length::< C, Self, T >( self )?;
+        for _ in 0..length {
+            callback( self.read_value()? );
+        }
+
+        Ok( length )
+    }
+
+    /// Reads a length-prefixed collection into any collection which implements `FromIterator`,
+    /// e.g. a `Vec`, a `HashSet` or a `BTreeSet`, so they're all read the same way.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeSet;
+    /// use speedy::{BufferReader, Endianness, Reader, Writable};
+    ///
+    /// let data = vec![ 3_u32, 1, 3 ].write_to_vec( Endianness::LittleEndian ).unwrap();
+    /// let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+    /// let set: BTreeSet< u32 > = reader.read_into_collection().unwrap();
+    /// assert_eq!( set.into_iter().collect::< Vec< _ > >(), vec![ 1, 3 ] );
+    /// ```
+    #[inline]
+    fn read_into_collection< T: Readable< 'a, C >, U: FromIterator< T > >( &mut self ) -> io::Result< U > {
+        let length = read_length::< C, Self, T >( self )?;
+        (0..length).map( |_| self.read_value() ).collect()
+    }
+
+    /// Reads strings written by `Writer::write_front_coded_strings`.
+    #[inline]
+    fn read_front_coded_strings( &mut self ) -> io::Result< Vec< String > > {
+        let length = read_length::< C, Self, String >( self )?;
+        let mut strings: Vec< String > = Vec::with_capacity( preallocated_capacity::< String >( length ) );
+        for _ in 0..length {
+            let prefix = self.read_u8()? as usize;
+            let suffix: Cow< 'a, [u8] > = self.read_value()?;
+            let mut bytes = match strings.last() {
+                Some( previous ) if prefix <= previous.len() => previous.as_bytes()[ ..prefix ].to_vec(),
+                None if prefix == 0 => Vec::new(),
+                _ => return Err( io::Error::new( io::ErrorKind::InvalidData, "front-coded prefix is longer than the previous string" ) )
+            };
+
+            bytes.extend_from_slice( &suffix );
+            strings.push( String::from_utf8( bytes ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )? );
+        }
+
+        Ok( strings )
     }
 
+    /// Reads strings written by `Writer::write_split_length_strings`.
     #[inline]
-    fn read_value< T: Readable< C > >( &mut self ) -> io::Result< T > {
+    fn read_split_length_strings( &mut self ) -> io::Result< Vec< String > > {
+        let count = read_length::< C, Self, u32 >( self )?;
+        let mut lengths = Vec::with_capacity( preallocated_capacity::< usize >( count ) );
+        let mut total_length: usize = 0;
+        for _ in 0..count {
+            let length = read_length_prefix( self )?;
+            total_length = total_length.checked_add( length ).ok_or_else( || io::Error::new( io::ErrorKind::InvalidData, "total length of the strings overflows" ) )?;
+            lengths.push( length );
+        }
+
+        if let Some( remaining ) = self.remaining_bytes_hint() {
+            if total_length > remaining {
+                return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "length prefixes are bigger than the remaining input" ) );
+            }
+        }
+
+        let mut strings = Vec::with_capacity( preallocated_capacity::< String >( count ) );
+
+        // Unless the whole input is in memory the lengths could be bogus, so every string
+        // is read on its own, allocating its memory piece by piece just like a `Vec< u8 >`.
+        if !(self.can_borrow() && self.remaining_bytes_hint().is_some()) {
+            for length in lengths {
+                let bytes: Vec< u8 > = read_elements( self, length )?;
+                strings.push( String::from_utf8( bytes ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )? );
+            }
+
+            return Ok( strings );
+        }
+
+        let bytes = self.read_cow_bytes( total_length )?;
+        let mut bytes: &[u8] = &bytes;
+        for length in lengths {
+            let (string, rest) = bytes.split_at( length );
+            let string = ::std::str::from_utf8( string ).map_err( |error| io::Error::new( io::ErrorKind::InvalidData, error ) )?;
+            strings.push( string.to_owned() );
+            bytes = rest;
+        }
+
+        Ok( strings )
+    }
+
+    #[inline]
+    fn read_value< T: Readable< 'a, C > >( &mut self ) -> io::Result< T > {
         T::read_from( self )
     }
 
+    /// Reads a value into an existing one; see `Readable::read_in_place`.
+    #[inline]
+    fn read_value_into< T: Readable< 'a, C > >( &mut self, value: &mut T ) -> io::Result< () > {
+        value.read_in_place( self )
+    }
+
+    /// Reads a value with a different context, e.g. to decode a nested
+    /// part of the data which uses a different version of the format.
+    ///
+    /// The endianness of the value is taken from the new context.
+    #[inline]
+    fn read_value_with< T: Readable< 'a, C2 >, C2: Context >( &mut self, context: C2 ) -> io::Result< T > {
+        let mut reader = ContextOverrideReader {
+            reader: self,
+            context,
+            phantom: PhantomData
+        };
+
+        T::read_from( &mut reader )
+    }
+
+    // Reads `length` bytes as a `Bytes`, sharing the allocation of the input if it's a `Bytes` too.
+    #[cfg(feature = "bytes")]
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_read_shared_bytes( &mut self, length: usize ) -> io::Result< ::bytes::Bytes > {
+        Ok( match self.read_cow_bytes( length )? {
+            Cow::Borrowed( slice ) => ::bytes::Bytes::copy_from_slice( slice ),
+            Cow::Owned( vec ) => ::bytes::Bytes::from( vec )
+        })
+    }
+
+    /// Called by the derived `Readable` impls before every field is read;
+    /// only used for instrumentation.
+    #[doc(hidden)]
+    #[inline]
+    fn begin_field( &mut self, _label: &'static str ) {}
+
+    /// Called by the derived `Readable` impls after every field is read.
+    #[doc(hidden)]
+    #[inline]
+    fn end_field( &mut self ) {}
+
     #[inline]
     fn endianness( &self ) -> Endianness {
         self.context().endianness()
     }
+
+    /// The endianness of the length prefixes; see `Context::length_endianness`.
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.context().length_endianness().unwrap_or_else( || self.endianness() )
+    }
+}
+
+struct ContextOverrideReader< 'r, C: 'r, R: 'r, C2 > {
+    reader: &'r mut R,
+    context: C2,
+    phantom: PhantomData< &'r C >
+}
+
+impl< 'a, 'r, C: Context, R: Reader< 'a, C >, C2: Context > Reader< 'a, C2 > for ContextOverrideReader< 'r, C, R, C2 > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.read_bytes( output )
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        self.reader.read_cow_bytes( length )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        self.reader.remaining_bytes_hint()
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.reader.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        self.reader.read_remaining()
+    }
+
+    #[cfg(feature = "bytes")]
+    #[inline]
+    fn speedy_read_shared_bytes( &mut self, length: usize ) -> io::Result< ::bytes::Bytes > {
+        self.reader.speedy_read_shared_bytes( length )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C2 {
+        &self.context
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.reader.begin_field( label )
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.reader.end_field()
+    }
 }
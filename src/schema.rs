@@ -0,0 +1,423 @@
+use std::borrow::Cow;
+use std::collections::BinaryHeap;
+use std::convert::Infallible;
+use std::time::{Duration, SystemTime};
+use std::num::{
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64
+};
+
+/// A description of how a value is laid out in its serialized form.
+///
+/// Every length prefix is an `u32`, and everything which is wider than
+/// a single byte is stored in the endianness of the context.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SchemaNode {
+    /// A value with a fixed size, e.g. `u16`; a `bool` is a single byte.
+    ///
+    /// With a `le` or `be` suffix (e.g. `u32be`) the value has its own endianness.
+    Primitive( &'static str ),
+    /// An UTF-8 string preceded by its length in bytes.
+    String,
+    /// Elements preceded by their count.
+    LengthPrefixed( Box< SchemaNode > ),
+    /// A fixed number of elements without a length prefix.
+    FixedCount {
+        count: usize,
+        element: Box< SchemaNode >
+    },
+    /// Elements which take up the rest of the input.
+    Remaining( Box< SchemaNode > ),
+    /// A string stored in exactly this many bytes and padded with trailing NULs.
+    FixedLengthString( usize ),
+    /// This many bytes which are written as zeros and ignored when read.
+    Reserved( usize ),
+    /// Strings written by `Writer::write_front_coded_strings`.
+    FrontCodedStrings,
+    /// Strings written by `Writer::write_split_length_strings`.
+    SplitLengthStrings,
+    /// A value preceded by its length in bytes.
+    Framed( Box< SchemaNode > ),
+    /// A byte which is `1` if the value is present, followed by the value.
+    Optional( Box< SchemaNode > ),
+    /// Fields stored one after another, optionally preceded by a type hash.
+    ///
+    /// If any of the fields are packed then they're stored as the bits of a leading bitmap,
+    /// in declaration order. If any of the fields are skipped if they're default then those
+    /// fields are preceded by a bitmap with a bit for each one of them which is set if it's present.
+    ///
+    /// With a `version` everything is preceded by a byte with the version it was written with,
+    /// which is at most `version`, and only the fields which are present in that version follow.
+    /// With an `end_marker` the fields are followed by a constant of the given type.
+    Struct {
+        name: &'static str,
+        type_hash: Option< u64 >,
+        version: Option< u8 >,
+        fields: Vec< SchemaField >,
+        end_marker: Option< (&'static str, u64) >
+    },
+    /// A tag of the given type which selects the variant, and the variant's fields,
+    /// which may be preceded by their length in bytes.
+    Enum {
+        name: &'static str,
+        tag_type: &'static str,
+        is_tag_trailing: bool,
+        are_variants_length_prefixed: bool,
+        variants: Vec< SchemaVariant >
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SchemaField {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub is_skipped_if_default: bool,
+    pub is_packed: bool,
+    /// The first version of a versioned struct which has this field.
+    pub since: Option< u8 >,
+    /// The first version of a versioned struct which doesn't have this field anymore.
+    pub until: Option< u8 >,
+    pub schema: SchemaNode
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SchemaVariant {
+    pub name: &'static str,
+    pub tag: u64,
+    pub fields: Vec< SchemaField >
+}
+
+/// Types which can describe their serialized form.
+///
+/// This can be derived alongside `Readable` and `Writable`, and takes
+/// the same `#[speedy(...)]` attributes into account.
+///
+/// ```rust
+/// use speedy::{Schema, SchemaNode};
+///
+/// assert_eq!( <Vec< u16 >>::schema(), SchemaNode::LengthPrefixed( Box::new( SchemaNode::Primitive( "u16" ) ) ) );
+/// assert_eq!( <Vec< u16 >>::schema().to_json(), r#"{"kind":"length_prefixed","element":{"kind":"primitive","type":"u16"}}"# );
+/// ```
+pub trait Schema {
+    fn schema() -> SchemaNode;
+}
+
+fn write_json_string( output: &mut String, value: &str ) {
+    output.push( '"' );
+    for ch in value.chars() {
+        match ch {
+            '"' => output.push_str( "\\\"" ),
+            '\\' => output.push_str( "\\\\" ),
+            '\n' => output.push_str( "\\n" ),
+            ch if (ch as u32) < 0x20 => output.push_str( &format!( "\\u{:04x}", ch as u32 ) ),
+            ch => output.push( ch )
+        }
+    }
+    output.push( '"' );
+}
+
+fn write_json_fields( output: &mut String, fields: &[SchemaField] ) {
+    output.push( '[' );
+    for (index, field) in fields.iter().enumerate() {
+        if index != 0 {
+            output.push( ',' );
+        }
+
+        output.push_str( "{\"name\":" );
+        write_json_string( output, field.name );
+        output.push_str( ",\"type\":" );
+        write_json_string( output, field.ty );
+        if field.is_skipped_if_default {
+            output.push_str( ",\"skip_if_default\":true" );
+        }
+        if field.is_packed {
+            output.push_str( ",\"packed\":true" );
+        }
+        if let Some( since ) = field.since {
+            output.push_str( &format!( ",\"since\":{}", since ) );
+        }
+        if let Some( until ) = field.until {
+            output.push_str( &format!( ",\"until\":{}", until ) );
+        }
+        output.push_str( ",\"schema\":" );
+        field.schema.write_json( output );
+        output.push( '}' );
+    }
+    output.push( ']' );
+}
+
+impl SchemaNode {
+    /// Returns the schema as a JSON object with a `kind` of either
+    /// `primitive`, `string`, `length_prefixed`, `fixed_count`, `remaining`,
+    /// `fixed_length_string`, `reserved`, `front_coded_strings`, `split_length_strings`,
+    /// `framed`, `optional`, `struct` or `enum`.
+    pub fn to_json( &self ) -> String {
+        let mut output = String::new();
+        self.write_json( &mut output );
+        output
+    }
+
+    fn write_json( &self, output: &mut String ) {
+        match *self {
+            SchemaNode::Primitive( ty ) => {
+                output.push_str( "{\"kind\":\"primitive\",\"type\":" );
+                write_json_string( output, ty );
+            },
+            SchemaNode::String => output.push_str( "{\"kind\":\"string\"" ),
+            SchemaNode::LengthPrefixed( ref element ) => {
+                output.push_str( "{\"kind\":\"length_prefixed\",\"element\":" );
+                element.write_json( output );
+            },
+            SchemaNode::FixedCount { count, ref element } => {
+                output.push_str( &format!( "{{\"kind\":\"fixed_count\",\"count\":{},\"element\":", count ) );
+                element.write_json( output );
+            },
+            SchemaNode::Remaining( ref element ) => {
+                output.push_str( "{\"kind\":\"remaining\",\"element\":" );
+                element.write_json( output );
+            },
+            SchemaNode::FixedLengthString( length ) => output.push_str( &format!( "{{\"kind\":\"fixed_length_string\",\"length\":{}", length ) ),
+            SchemaNode::Reserved( length ) => output.push_str( &format!( "{{\"kind\":\"reserved\",\"length\":{}", length ) ),
+            SchemaNode::FrontCodedStrings => output.push_str( "{\"kind\":\"front_coded_strings\"" ),
+            SchemaNode::SplitLengthStrings => output.push_str( "{\"kind\":\"split_length_strings\"" ),
+            SchemaNode::Framed( ref value ) => {
+                output.push_str( "{\"kind\":\"framed\",\"value\":" );
+                value.write_json( output );
+            },
+            SchemaNode::Optional( ref value ) => {
+                output.push_str( "{\"kind\":\"optional\",\"value\":" );
+                value.write_json( output );
+            },
+            SchemaNode::Struct { name, type_hash, version, ref fields, end_marker } => {
+                output.push_str( "{\"kind\":\"struct\",\"name\":" );
+                write_json_string( output, name );
+                if let Some( type_hash ) = type_hash {
+                    output.push_str( &format!( ",\"type_hash\":{}", type_hash ) );
+                }
+                if let Some( version ) = version {
+                    output.push_str( &format!( ",\"version\":{}", version ) );
+                }
+                output.push_str( ",\"fields\":" );
+                write_json_fields( output, fields );
+                if let Some( (ty, value) ) = end_marker {
+                    output.push_str( ",\"end_marker\":{\"type\":" );
+                    write_json_string( output, ty );
+                    output.push_str( &format!( ",\"value\":{}}}", value ) );
+                }
+            },
+            SchemaNode::Enum { name, tag_type, is_tag_trailing, are_variants_length_prefixed, ref variants } => {
+                output.push_str( "{\"kind\":\"enum\",\"name\":" );
+                write_json_string( output, name );
+                output.push_str( ",\"tag_type\":" );
+                write_json_string( output, tag_type );
+                if is_tag_trailing {
+                    output.push_str( ",\"tag_position\":\"trailing\"" );
+                }
+                if are_variants_length_prefixed {
+                    output.push_str( ",\"length_prefixed_variants\":true" );
+                }
+                output.push_str( ",\"variants\":[" );
+                for (index, variant) in variants.iter().enumerate() {
+                    if index != 0 {
+                        output.push( ',' );
+                    }
+
+                    output.push_str( "{\"name\":" );
+                    write_json_string( output, variant.name );
+                    output.push_str( &format!( ",\"tag\":{},\"fields\":", variant.tag ) );
+                    write_json_fields( output, &variant.fields );
+                    output.push( '}' );
+                }
+                output.push( ']' );
+            }
+        }
+
+        output.push( '}' );
+    }
+}
+
+macro_rules! impl_for_primitive {
+    ($type:ty, $name:expr) => {
+        impl Schema for $type {
+            #[inline]
+            fn schema() -> SchemaNode {
+                SchemaNode::Primitive( $name )
+            }
+        }
+    }
+}
+
+impl_for_primitive!( bool, "bool" );
+impl_for_primitive!( i8, "i8" );
+impl_for_primitive!( i16, "i16" );
+impl_for_primitive!( i32, "i32" );
+impl_for_primitive!( i64, "i64" );
+impl_for_primitive!( u8, "u8" );
+impl_for_primitive!( u16, "u16" );
+impl_for_primitive!( u32, "u32" );
+impl_for_primitive!( u64, "u64" );
+impl_for_primitive!( f32, "f32" );
+impl_for_primitive!( f64, "f64" );
+impl_for_primitive!( NonZeroU8, "u8" );
+impl_for_primitive!( NonZeroU16, "u16" );
+impl_for_primitive!( NonZeroU32, "u32" );
+impl_for_primitive!( NonZeroU64, "u64" );
+impl_for_primitive!( NonZeroI8, "i8" );
+impl_for_primitive!( NonZeroI16, "i16" );
+impl_for_primitive!( NonZeroI32, "i32" );
+impl_for_primitive!( NonZeroI64, "i64" );
+
+impl Schema for String {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::String
+    }
+}
+
+impl< 'a > Schema for Cow< 'a, str > {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::String
+    }
+}
+
+impl< T: Schema > Schema for Vec< T > {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::LengthPrefixed( Box::new( T::schema() ) )
+    }
+}
+
+impl< T: Schema > Schema for BinaryHeap< T > {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::LengthPrefixed( Box::new( T::schema() ) )
+    }
+}
+
+impl< 'a, T: Schema + Clone > Schema for Cow< 'a, [T] > {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::LengthPrefixed( Box::new( T::schema() ) )
+    }
+}
+
+impl Schema for &str {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::String
+    }
+}
+
+impl Schema for &[u8] {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::LengthPrefixed( Box::new( SchemaNode::Primitive( "u8" ) ) )
+    }
+}
+
+impl< T: Schema, const N: usize > Schema for [T; N] {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::FixedCount {
+            count: N,
+            element: Box::new( T::schema() )
+        }
+    }
+}
+
+macro_rules! impl_for_tuple {
+    ($($type:ident $index:tt),+) => {
+        impl< $($type: Schema),+ > Schema for ($($type,)+) {
+            fn schema() -> SchemaNode {
+                SchemaNode::Struct {
+                    name: "tuple",
+                    type_hash: None,
+                    version: None,
+                    fields: vec![ $(SchemaField { name: stringify!( $index ), ty: stringify!( $type ), is_skipped_if_default: false, is_packed: false, since: None, until: None, schema: $type::schema() }),+ ],
+                    end_marker: None
+                }
+            }
+        }
+    }
+}
+
+impl_for_tuple!( T0 0 );
+impl_for_tuple!( T0 0, T1 1 );
+impl_for_tuple!( T0 0, T1 1, T2 2 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11 );
+
+impl< T: Schema > Schema for Option< T > {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::Optional( Box::new( T::schema() ) )
+    }
+}
+
+impl< T: Schema, E: Schema > Schema for Result< T, E > {
+    fn schema() -> SchemaNode {
+        let variant = |name, tag, ty, schema| SchemaVariant {
+            name,
+            tag,
+            fields: vec![ SchemaField { name: "0", ty, is_skipped_if_default: false, is_packed: false, since: None, until: None, schema } ]
+        };
+
+        SchemaNode::Enum {
+            name: "Result",
+            tag_type: "u8",
+            is_tag_trailing: false,
+            are_variants_length_prefixed: false,
+            variants: vec![ variant( "Ok", 0, "T", T::schema() ), variant( "Err", 1, "E", E::schema() ) ]
+        }
+    }
+}
+
+impl Schema for Infallible {
+    #[inline]
+    fn schema() -> SchemaNode {
+        SchemaNode::Enum {
+            name: "Infallible",
+            tag_type: "u8",
+            is_tag_trailing: false,
+            are_variants_length_prefixed: false,
+            variants: Vec::new()
+        }
+    }
+}
+
+impl Schema for Duration {
+    fn schema() -> SchemaNode {
+        SchemaNode::Struct {
+            name: "Duration",
+            type_hash: None,
+            version: None,
+            fields: vec![
+                SchemaField { name: "secs", ty: "u64", is_skipped_if_default: false, is_packed: false, since: None, until: None, schema: SchemaNode::Primitive( "u64" ) },
+                SchemaField { name: "nanos", ty: "u32", is_skipped_if_default: false, is_packed: false, since: None, until: None, schema: SchemaNode::Primitive( "u32" ) }
+            ],
+            end_marker: None
+        }
+    }
+}
+
+// Stored as the duration since the UNIX epoch.
+impl Schema for SystemTime {
+    #[inline]
+    fn schema() -> SchemaNode {
+        Duration::schema()
+    }
+}
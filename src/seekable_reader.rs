@@ -0,0 +1,86 @@
+use std::io::{
+    self,
+    Read,
+    Seek,
+    SeekFrom
+};
+
+use reader::Reader;
+use readable::Readable;
+use context::Context;
+
+/// A reader over a seekable stream, e.g. a `File` or an `io::Cursor`
+/// wrapping a buffer, for formats which need random access.
+///
+/// ```rust
+/// use std::io::Cursor;
+/// use speedy::{Endianness, SeekableReader};
+///
+/// let data = [1, 0, 0, 0, 2, 0, 0, 0];
+/// let mut reader = SeekableReader::new( Endianness::LittleEndian, Cursor::new( &data[..] ) );
+/// let value: u32 = reader.read_value_at( 4 ).unwrap();
+/// assert_eq!( value, 2 );
+/// ```
+pub struct SeekableReader< C: Context, S: Read + Seek > {
+    context: C,
+    reader: S
+}
+
+impl< C: Context, S: Read + Seek > SeekableReader< C, S > {
+    #[inline]
+    pub fn new( context: C, reader: S ) -> Self {
+        SeekableReader { context, reader }
+    }
+
+    /// Moves the reader to the given absolute offset from the start of the stream.
+    #[inline]
+    pub fn seek_to( &mut self, offset: u64 ) -> io::Result< () > {
+        self.reader.seek( SeekFrom::Start( offset ) )?;
+        Ok(())
+    }
+
+    /// Returns the current absolute offset from the start of the stream.
+    #[inline]
+    pub fn position( &mut self ) -> io::Result< u64 > {
+        self.reader.stream_position()
+    }
+
+    /// Reads a value from the given absolute offset.
+    #[inline]
+    pub fn read_value_at< 'a, T: Readable< 'a, C > >( &mut self, offset: u64 ) -> io::Result< T > {
+        self.seek_to( offset )?;
+        self.read_value()
+    }
+
+    #[inline]
+    pub fn into_inner( self ) -> S {
+        self.reader
+    }
+}
+
+impl< 'a, C: Context, S: Read + Seek > Reader< 'a, C > for SeekableReader< C, S > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.reader.read_exact( output )
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        let position = self.reader.stream_position()?;
+        let result = self.reader.read_exact( output );
+        self.reader.seek( SeekFrom::Start( position ) )?;
+        result
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        let mut output = Vec::new();
+        self.reader.read_to_end( &mut output )?;
+        Ok( output )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        &self.context
+    }
+}
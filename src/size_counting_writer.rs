@@ -0,0 +1,77 @@
+use std::io;
+
+use writer::Writer;
+use context::Context;
+use endianness::Endianness;
+
+// Only counts the bytes which would be written, but otherwise behaves just like the wrapped writer.
+#[doc(hidden)]
+pub struct SizeCountingWriter< 'w, W: ?Sized + 'w > {
+    writer: &'w W,
+    size: usize
+}
+
+impl< 'w, W: ?Sized > SizeCountingWriter< 'w, W > {
+    #[inline]
+    pub fn new( writer: &'w W ) -> Self {
+        SizeCountingWriter { writer, size: 0 }
+    }
+
+    #[inline]
+    pub fn size( &self ) -> usize {
+        self.size
+    }
+}
+
+impl< 'a, 'w, C: Context, W: ?Sized + Writer< 'a, C > > Writer< 'a, C > for SizeCountingWriter< 'w, W > {
+    #[inline]
+    fn write_bytes( &mut self, slice: &'a [u8] ) -> io::Result< () > {
+        self.size += slice.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn write_owned_bytes( &mut self, vec: Vec< u8 > ) -> io::Result< () > {
+        self.size += vec.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u8( &mut self, _: u8 ) -> io::Result< () > {
+        self.size += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u16( &mut self, _: u16 ) -> io::Result< () > {
+        self.size += 2;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u32( &mut self, _: u32 ) -> io::Result< () > {
+        self.size += 4;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u64( &mut self, _: u64 ) -> io::Result< () > {
+        self.size += 8;
+        Ok(())
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.writer.context()
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.writer.endianness()
+    }
+
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.writer.length_endianness()
+    }
+}
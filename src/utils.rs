@@ -1,17 +1,37 @@
+use std::io;
 use std::slice;
 use std::mem;
 
-pub unsafe trait Primitive {}
-unsafe impl Primitive for i8 {}
-unsafe impl Primitive for u8 {}
-unsafe impl Primitive for i16 {}
-unsafe impl Primitive for u16 {}
-unsafe impl Primitive for i32 {}
-unsafe impl Primitive for u32 {}
-unsafe impl Primitive for i64 {}
-unsafe impl Primitive for u64 {}
-unsafe impl Primitive for f32 {}
-unsafe impl Primitive for f64 {}
+use writer::Writer;
+use context::Context;
+
+pub unsafe trait Primitive: Copy {
+    // Used by `#[speedy(pod)]` structs to write their fields by value,
+    // since references to the fields of a packed struct aren't allowed.
+    fn write_by_value< 'a, C: Context, W: ?Sized + Writer< 'a, C > >( self, writer: &mut W ) -> io::Result< () >;
+}
+
+macro_rules! impl_primitive {
+    ($type:ty, $write:ident) => {
+        unsafe impl Primitive for $type {
+            #[inline]
+            fn write_by_value< 'a, C: Context, W: ?Sized + Writer< 'a, C > >( self, writer: &mut W ) -> io::Result< () > {
+                writer.$write( self )
+            }
+        }
+    }
+}
+
+impl_primitive!( i8, write_i8 );
+impl_primitive!( u8, write_u8 );
+impl_primitive!( i16, write_i16 );
+impl_primitive!( u16, write_u16 );
+impl_primitive!( i32, write_i32 );
+impl_primitive!( u32, write_u32 );
+impl_primitive!( i64, write_i64 );
+impl_primitive!( u64, write_u64 );
+impl_primitive!( f32, write_f32 );
+impl_primitive!( f64, write_f64 );
 
 #[inline]
 pub fn as_bytes< T: Primitive >( slice: &[T] ) -> &[u8] {
@@ -0,0 +1,108 @@
+use std::io;
+use std::cmp;
+use std::borrow::Cow;
+
+use reader::Reader;
+use context::Context;
+use endianness::Endianness;
+use readable_impl::read_elements;
+
+// These are used by the derived impls of enums with `#[speedy(length_prefixed_variants)]`,
+// where every variant's fields are preceded by their length in bytes so that
+// any trailing fields which the reader doesn't know about can be skipped.
+
+#[doc(hidden)]
+pub struct VariantFrameReader< 'r, R: 'r > {
+    reader: &'r mut R,
+    remaining: usize
+}
+
+impl< 'r, R > VariantFrameReader< 'r, R > {
+    #[inline]
+    pub fn new( reader: &'r mut R, length: usize ) -> Self {
+        VariantFrameReader { reader, remaining: length }
+    }
+
+    #[inline]
+    fn consume( &mut self, length: usize ) -> io::Result< () > {
+        if length > self.remaining {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "variant is longer than its frame" ) );
+        }
+
+        self.remaining -= length;
+        Ok(())
+    }
+
+    /// Skips whatever is left of the frame.
+    pub fn skip_rest< 'a, C: Context >( &mut self ) -> io::Result< () > where R: Reader< 'a, C > {
+        self.reader.skip_bytes( self.remaining )?;
+        self.remaining = 0;
+        Ok(())
+    }
+}
+
+impl< 'a, 'r, C: Context, R: Reader< 'a, C > > Reader< 'a, C > for VariantFrameReader< 'r, R > {
+    #[inline]
+    fn read_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        self.consume( output.len() )?;
+        self.reader.read_bytes( output )
+    }
+
+    #[inline]
+    fn read_cow_bytes( &mut self, length: usize ) -> io::Result< Cow< 'a, [u8] > > {
+        self.consume( length )?;
+        self.reader.read_cow_bytes( length )
+    }
+
+    #[inline]
+    fn remaining_bytes_hint( &self ) -> Option< usize > {
+        Some( self.reader.remaining_bytes_hint().map_or( self.remaining, |remaining| cmp::min( remaining, self.remaining ) ) )
+    }
+
+    #[inline]
+    fn can_borrow( &self ) -> bool {
+        self.reader.can_borrow()
+    }
+
+    #[inline]
+    fn peek_bytes( &mut self, output: &mut [u8] ) -> io::Result< () > {
+        if output.len() > self.remaining {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "variant is longer than its frame" ) );
+        }
+
+        self.reader.peek_bytes( output )
+    }
+
+    #[inline]
+    fn read_remaining( &mut self ) -> io::Result< Vec< u8 > > {
+        // Unless the input is in memory the frame's length can't be trusted,
+        // so this allocates as the bytes come in.
+        let length = self.remaining;
+        read_elements( self, length )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        self.reader.context()
+    }
+
+    #[inline]
+    fn begin_field( &mut self, label: &'static str ) {
+        self.reader.begin_field( label )
+    }
+
+    #[inline]
+    fn end_field( &mut self ) {
+        self.reader.end_field()
+    }
+
+    #[inline]
+    fn endianness( &self ) -> Endianness {
+        self.reader.endianness()
+    }
+
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.reader.length_endianness()
+    }
+}
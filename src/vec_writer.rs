@@ -0,0 +1,88 @@
+use std::io;
+
+use byteorder::WriteBytesExt;
+
+use writer::Writer;
+use context::Context;
+
+/// A writer which appends everything to a `Vec< u8 >`.
+///
+/// This is what `Writable::write_to_vec` uses internally, but it can also
+/// be used directly to hand-roll a format on top of speedy's primitives.
+///
+/// ```rust
+/// use speedy::{VecWriter, Endianness, Writer};
+///
+/// let mut writer = VecWriter::new( Endianness::BigEndian );
+/// writer.write_u32( 1 ).unwrap();
+/// writer.write_u16( 2 ).unwrap();
+/// assert_eq!( writer.into_inner(), [0, 0, 0, 1, 0, 2] );
+/// ```
+pub struct VecWriter< C: Context > {
+    context: C,
+    buffer: Vec< u8 >
+}
+
+impl< C: Context > VecWriter< C > {
+    #[inline]
+    pub fn new( context: C ) -> Self {
+        VecWriter::with_capacity( context, 0 )
+    }
+
+    #[inline]
+    pub fn with_capacity( context: C, capacity: usize ) -> Self {
+        VecWriter {
+            context,
+            buffer: Vec::with_capacity( capacity )
+        }
+    }
+
+    #[inline]
+    pub fn as_slice( &self ) -> &[u8] {
+        &self.buffer
+    }
+
+    #[inline]
+    pub fn into_inner( self ) -> Vec< u8 > {
+        self.buffer
+    }
+}
+
+impl< 'a, C: Context > Writer< 'a, C > for VecWriter< C > {
+    #[inline]
+    fn write_bytes( &mut self, slice: &'a [u8] ) -> io::Result< () > {
+        self.buffer.extend_from_slice( slice );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_owned_bytes( &mut self, vec: Vec< u8 > ) -> io::Result< () > {
+        self.buffer.extend_from_slice( &vec );
+        Ok(())
+    }
+
+    #[inline]
+    fn write_u8( &mut self, value: u8 ) -> io::Result< () > {
+        self.buffer.write_u8( value )
+    }
+
+    #[inline]
+    fn write_u16( &mut self, value: u16 ) -> io::Result< () > {
+        self.context.endianness().write_to_stream_u16( &mut self.buffer, value )
+    }
+
+    #[inline]
+    fn write_u32( &mut self, value: u32 ) -> io::Result< () > {
+        self.context.endianness().write_to_stream_u32( &mut self.buffer, value )
+    }
+
+    #[inline]
+    fn write_u64( &mut self, value: u64 ) -> io::Result< () > {
+        self.context.endianness().write_to_stream_u64( &mut self.buffer, value )
+    }
+
+    #[inline]
+    fn context( &self ) -> &C {
+        &self.context
+    }
+}
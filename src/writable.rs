@@ -1,14 +1,21 @@
+use std::fs::File;
+use std::path::Path;
 use std::io::{
     self,
     Write,
-    Cursor
+    Cursor,
+    BufWriter
 };
 
 use byteorder::WriteBytesExt;
 
 use writer::Writer;
+use vec_writer::VecWriter;
+use size_counting_writer::SizeCountingWriter;
+use debug_format::DebugWriter;
 use context::Context;
 use endianness::Endianness;
+use writable_impl::write_length;
 
 struct WritingCollector< C: Context, T: Write > {
     context: C,
@@ -98,6 +105,11 @@ impl< 'a, C: Context > Writer< 'a, C > for SizeCalculatorCollector {
         Endianness::default()
     }
 
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        Endianness::default()
+    }
+
     #[inline]
     fn context( &self ) -> &C {
         panic!();
@@ -115,9 +127,29 @@ pub trait Writable< C: Context > {
 
     #[inline]
     fn write_to_vec( &self, context: C ) -> io::Result< Vec< u8 > > {
-        let mut vec = Vec::new();
-        try!( self.write_to_stream( context, &mut vec ) );
-        Ok( vec )
+        self.write_to_vec_with_capacity( context, 0 )
+    }
+
+    /// Same as `write_to_vec`, except the vector is preallocated with
+    /// the given `capacity`, which is useful when calculating
+    /// `bytes_needed` would be too expensive.
+    #[inline]
+    fn write_to_vec_with_capacity( &self, context: C, capacity: usize ) -> io::Result< Vec< u8 > > {
+        let mut writer = VecWriter::with_capacity( context, capacity );
+        self.write_to( &mut writer )?;
+        Ok( writer.into_inner() )
+    }
+
+    /// Same as `write_to_vec`, except every field of a derived type is preceded
+    /// by its name, which makes the output a lot easier to correlate with a hexdump.
+    ///
+    /// The names are written as length-prefixed strings, e.g. `Struct.field`;
+    /// the output can be read back with `Readable::read_from_buffer_debug`.
+    #[inline]
+    fn write_to_vec_debug( &self, context: C ) -> io::Result< Vec< u8 > > {
+        let mut writer = DebugWriter::new( context );
+        self.write_to( &mut writer )?;
+        Ok( writer.into_inner() )
     }
 
     #[inline]
@@ -130,6 +162,77 @@ pub trait Writable< C: Context > {
         self.write_to( &mut writer )
     }
 
+    /// Writes the value into a file, replacing the file if it already exists.
+    #[inline]
+    fn write_to_file< P: AsRef< Path > >( &self, context: C, path: P ) -> io::Result< () > {
+        let mut stream = BufWriter::new( File::create( path )? );
+        self.write_to_stream( context, &mut stream )?;
+        stream.flush()
+    }
+
+    /// Writes the value prefixed with its length in bytes encoded as an `u32`.
+    ///
+    /// This is the format expected by `Readable::stream_from_reader`.
+    #[inline]
+    fn write_framed_to_stream< S: Write >( &self, context: C, stream: S ) -> io::Result< () > {
+        let mut writer = WritingCollector {
+            context,
+            writer: stream
+        };
+
+        // Unlike `bytes_needed` this doesn't panic if the value can't be written.
+        let length = {
+            let mut counter = SizeCountingWriter::new( &writer );
+            self.write_to( &mut counter )?;
+            counter.size()
+        };
+
+        write_length( &mut writer, length )?;
+        self.write_to( &mut writer )
+    }
+
+    // These are only implemented by the primitive types, which allows
+    // containers to write a whole slice of them at once.
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_is_primitive() -> bool where Self: Sized {
+        false
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_slice_as_bytes( _: &[Self] ) -> &[u8] where Self: Sized {
+        panic!()
+    }
+
+    // These decide how an `Option< Self >` is written; see `Readable::speedy_read_option`.
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_write_option< 'a, T: ?Sized + Writer< 'a, C > >( value: &'a Option< Self >, writer: &mut T ) -> io::Result< () > where Self: Sized {
+        match *value {
+            None => writer.write_u8( 0 ),
+            Some( ref value ) => {
+                writer.write_u8( 1 )?;
+                writer.write_value( value )
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_option_bytes_needed( value: &Option< Self > ) -> usize where Self: Sized {
+        match *value {
+            None => 1,
+            Some( ref value ) => 1 + value.bytes_needed()
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn speedy_option_max_bytes_len() -> Option< usize > where Self: Sized {
+        Self::max_bytes_len()?.checked_add( 1 )
+    }
+
     #[inline]
     fn bytes_needed( &self ) -> usize {
         let mut writer = SizeCalculatorCollector {
@@ -139,4 +242,14 @@ pub trait Writable< C: Context > {
         self.write_to( &mut writer ).unwrap();
         writer.size
     }
+
+    /// The most bytes a value of this type can ever take when written,
+    /// or `None` if there's no such bound, e.g. when it contains a `Vec` or a `String`.
+    ///
+    /// Unlike `bytes_needed` this doesn't need a value, so it can be used
+    /// to size a fixed-capacity buffer up front.
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        None
+    }
 }
@@ -2,6 +2,13 @@ use std::io;
 
 use std::mem;
 use std::borrow::Cow;
+use std::convert::Infallible;
+use std::collections::BinaryHeap;
+use std::num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64};
+use std::sync::atomic::{AtomicU8, AtomicU16, AtomicU32, AtomicI8, AtomicI16, AtomicI32, Ordering};
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicU64, AtomicI64};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use writable::Writable;
 use writer::Writer;
@@ -9,6 +16,19 @@ use writer::Writer;
 use context::Context;
 use utils::as_bytes;
 
+#[inline]
+pub fn write_length< 'a, C: Context, T: ?Sized + Writer< 'a, C > >( writer: &mut T, length: usize ) -> io::Result< () > {
+    if length as u64 > u32::MAX as u64 {
+        return Err( io::Error::new( io::ErrorKind::InvalidInput, "length doesn't fit into an u32 length prefix" ) );
+    }
+
+    if writer.length_endianness() != writer.endianness() {
+        return writer.write_u32( (length as u32).swap_bytes() );
+    }
+
+    writer.write_u32( length as u32 )
+}
+
 macro_rules! impl_for_primitive {
     ($type:ty, $write_name:ident) => {
         impl< C: Context > Writable< C > for $type {
@@ -21,6 +41,21 @@ macro_rules! impl_for_primitive {
             fn bytes_needed( &self ) -> usize {
                 mem::size_of::< Self >()
             }
+
+            #[inline]
+            fn max_bytes_len() -> Option< usize > {
+                Some( mem::size_of::< Self >() )
+            }
+
+            #[inline]
+            fn speedy_is_primitive() -> bool {
+                true
+            }
+
+            #[inline]
+            fn speedy_slice_as_bytes( slice: &[Self] ) -> &[u8] {
+                as_bytes( slice )
+            }
         }
     }
 }
@@ -46,24 +81,78 @@ impl< C: Context > Writable< C > for bool {
     fn bytes_needed( &self ) -> usize {
         1
     }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 1 )
+    }
 }
 
-impl< C: Context > Writable< C > for [u8] {
+// Has to be a multiple of the size of every primitive.
+const SWAPPED_CHUNK_SIZE: usize = 16 * 1024;
+
+macro_rules! swap_chunks {
+    ($bytes:expr, $type:ty, $size:expr) => {
+        for chunk in $bytes.chunks_exact_mut( $size ) {
+            let mut value = [0; $size];
+            value.copy_from_slice( chunk );
+            chunk.copy_from_slice( &<$type>::from_ne_bytes( value ).swap_bytes().to_ne_bytes() );
+        }
+    }
+}
+
+/// Writes the elements without prefixing them with their length.
+#[inline]
+pub fn write_elements< 'a, C: Context, W: ?Sized + Writer< 'a, C >, T: Writable< C > >( writer: &mut W, slice: &'a [T] ) -> io::Result< () > {
+    if T::speedy_is_primitive() {
+        if mem::size_of::< T >() == 1 || !writer.endianness().conversion_necessary() {
+            return writer.write_bytes( T::speedy_slice_as_bytes( slice ) );
+        }
+
+        // The elements are swapped in copies, so that many of them can still be written at once;
+        // the copies are kept small since big allocations are a lot more expensive.
+        for chunk in T::speedy_slice_as_bytes( slice ).chunks( SWAPPED_CHUNK_SIZE ) {
+            let mut bytes = chunk.to_vec();
+            match mem::size_of::< T >() {
+                2 => swap_chunks!( bytes, u16, 2 ),
+                4 => swap_chunks!( bytes, u32, 4 ),
+                8 => swap_chunks!( bytes, u64, 8 ),
+                _ => unreachable!()
+            }
+
+            writer.write_owned_bytes( bytes )?;
+        }
+
+        return Ok(());
+    }
+
+    for element in slice {
+        writer.write_value( element )?;
+    }
+
+    Ok(())
+}
+
+impl< C: Context, T: Writable< C > > Writable< C > for [T] {
     #[inline]
-    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-        try!( writer.write_u32( self.len() as _ ) );
-        writer.write_bytes( self )
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        write_length( writer, self.len() )?;
+        write_elements( writer, self )
     }
 
     #[inline]
     fn bytes_needed( &self ) -> usize {
-        4 + self.len()
+        if T::speedy_is_primitive() {
+            4 + mem::size_of_val( self )
+        } else {
+            4 + self.iter().map( Writable::< C >::bytes_needed ).sum::< usize >()
+        }
     }
 }
 
-impl< 'r, C: Context > Writable< C > for Cow< 'r, [u8] > {
+impl< 'r, C: Context, T: Writable< C > + Clone > Writable< C > for Cow< 'r, [T] > {
     #[inline]
-    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
         self.as_ref().write_to( writer )
     }
 
@@ -73,9 +162,9 @@ impl< 'r, C: Context > Writable< C > for Cow< 'r, [u8] > {
     }
 }
 
-impl< C: Context > Writable< C > for Vec< u8 > {
+impl< C: Context, T: Writable< C > > Writable< C > for Vec< T > {
     #[inline]
-    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
         self.as_slice().write_to( writer )
     }
 
@@ -85,42 +174,132 @@ impl< C: Context > Writable< C > for Vec< u8 > {
     }
 }
 
-impl< C: Context > Writable< C > for [i8] {
+impl< C: Context, T: ?Sized + Writable< C > > Writable< C > for &T {
     #[inline]
-    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-        as_bytes( self ).write_to( writer )
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        (**self).write_to( writer )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        Writable::< C >::bytes_needed( &**self )
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        T::max_bytes_len()
+    }
+}
+
+impl< C: Context, T: Writable< C > > Writable< C > for Option< T > {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        T::speedy_write_option( self, writer )
     }
 
     #[inline]
     fn bytes_needed( &self ) -> usize {
-        Writable::< C >::bytes_needed( as_bytes( self ) )
+        T::speedy_option_bytes_needed( self )
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        T::speedy_option_max_bytes_len()
     }
 }
 
-impl< 'r, C: Context > Writable< C > for Cow< 'r, [i8] > {
+impl< C: Context, T: Writable< C >, E: Writable< C > > Writable< C > for Result< T, E > {
     #[inline]
-    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-        self.as_ref().write_to( writer )
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        match *self {
+            Ok( ref value ) => {
+                writer.write_u8( 0 )?;
+                writer.write_value( value )
+            },
+            Err( ref error ) => {
+                writer.write_u8( 1 )?;
+                writer.write_value( error )
+            }
+        }
     }
 
     #[inline]
     fn bytes_needed( &self ) -> usize {
-        Writable::< C >::bytes_needed( self.as_ref() )
+        match *self {
+            Ok( ref value ) => 1 + Writable::< C >::bytes_needed( value ),
+            Err( ref error ) => 1 + Writable::< C >::bytes_needed( error )
+        }
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        T::max_bytes_len()?.max( E::max_bytes_len()? ).checked_add( 1 )
     }
 }
 
-impl< C: Context > Writable< C > for Vec< i8 > {
+impl< C: Context > Writable< C > for Infallible {
     #[inline]
-    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-        self.as_slice().write_to( writer )
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, _: &mut W ) -> io::Result< () > {
+        match *self {}
     }
 
     #[inline]
     fn bytes_needed( &self ) -> usize {
-        Writable::< C >::bytes_needed( self.as_slice() )
+        match *self {}
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 0 )
     }
 }
 
+macro_rules! impl_for_non_zero {
+    ($type:ident, $inner:ty, $write_name:ident) => {
+        impl< C: Context > Writable< C > for $type {
+            #[inline]
+            fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+                writer.$write_name( self.get() )
+            }
+
+            #[inline]
+            fn bytes_needed( &self ) -> usize {
+                mem::size_of::< $inner >()
+            }
+
+            #[inline]
+            fn max_bytes_len() -> Option< usize > {
+                Some( mem::size_of::< $inner >() )
+            }
+
+            // A `None` is encoded as a zero.
+            #[inline]
+            fn speedy_write_option< 'a, W: ?Sized + Writer< 'a, C > >( value: &'a Option< Self >, writer: &mut W ) -> io::Result< () > {
+                writer.$write_name( value.map( $type::get ).unwrap_or( 0 ) )
+            }
+
+            #[inline]
+            fn speedy_option_bytes_needed( _: &Option< Self > ) -> usize {
+                mem::size_of::< $inner >()
+            }
+
+            #[inline]
+            fn speedy_option_max_bytes_len() -> Option< usize > {
+                Some( mem::size_of::< $inner >() )
+            }
+        }
+    }
+}
+
+impl_for_non_zero!( NonZeroU8, u8, write_u8 );
+impl_for_non_zero!( NonZeroU16, u16, write_u16 );
+impl_for_non_zero!( NonZeroU32, u32, write_u32 );
+impl_for_non_zero!( NonZeroU64, u64, write_u64 );
+impl_for_non_zero!( NonZeroI8, i8, write_i8 );
+impl_for_non_zero!( NonZeroI16, i16, write_i16 );
+impl_for_non_zero!( NonZeroI32, i32, write_i32 );
+impl_for_non_zero!( NonZeroI64, i64, write_i64 );
+
 impl< C: Context > Writable< C > for String {
     #[inline]
     fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
@@ -145,59 +324,164 @@ impl< C: Context > Writable< C > for str {
     }
 }
 
-macro_rules! impl_for_primitive_slice {
-    ($type:ty, $writer:ident) => {
-        impl< C: Context > Writable< C > for [$type] {
+impl< 'r, C: Context > Writable< C > for Cow< 'r, str > {
+    #[inline]
+    fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
+        self.as_bytes().write_to( writer )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        Writable::< C >::bytes_needed( self.as_bytes() )
+    }
+}
+
+impl< C: Context, T: Writable< C >, const N: usize > Writable< C > for [T; N] {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        for element in self.iter() {
+            writer.write_value( element )?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        self.iter().map( Writable::< C >::bytes_needed ).sum()
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        T::max_bytes_len()?.checked_mul( N )
+    }
+}
+
+macro_rules! impl_for_tuple {
+    ($($type:ident $index:tt),+) => {
+        impl< C: Context, $($type: Writable< C >),+ > Writable< C > for ($($type,)+) {
             #[inline]
-            fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-                try!( writer.write_u32( self.len() as _ ) );
-                if writer.endianness().conversion_necessary() {
-                    for &value in self {
-                        try!( writer.$writer( value ) );
-                    }
-                    Ok(())
-                } else {
-                    writer.write_bytes( as_bytes( self ) )
-                }
+            fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+                $(writer.write_value( &self.$index )?;)+
+                Ok(())
             }
 
             #[inline]
             fn bytes_needed( &self ) -> usize {
-                4 + self.len() * mem::size_of::< $type >()
+                0 $(+ Writable::< C >::bytes_needed( &self.$index ))+
             }
-        }
 
-        impl< 'r, C: Context > Writable< C > for Cow< 'r, [$type] > {
             #[inline]
-            fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-                self.as_ref().write_to( writer )
+            fn max_bytes_len() -> Option< usize > {
+                let size = 0_usize;
+                $(let size = size.checked_add( $type::max_bytes_len()? )?;)+
+                Some( size )
             }
+        }
+    }
+}
 
-            #[inline]
-            fn bytes_needed( &self ) -> usize {
-                Writable::< C >::bytes_needed( self.as_ref() )
-            }
+impl_for_tuple!( T0 0 );
+impl_for_tuple!( T0 0, T1 1 );
+impl_for_tuple!( T0 0, T1 1, T2 2 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10 );
+impl_for_tuple!( T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11 );
+
+// The elements are written in an unspecified order; the heap is rebuilt when read.
+impl< C: Context, T: Writable< C > + Ord > Writable< C > for BinaryHeap< T > {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        write_length( writer, self.len() )?;
+        for element in self.iter() {
+            writer.write_value( element )?;
         }
 
-        impl< C: Context > Writable< C > for Vec< $type > {
+        Ok(())
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        4 + self.iter().map( Writable::< C >::bytes_needed ).sum::< usize >()
+    }
+}
+
+impl< C: Context > Writable< C > for Duration {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        writer.write_u64( self.as_secs() )?;
+        writer.write_u32( self.subsec_nanos() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        12
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 12 )
+    }
+}
+
+// Written as the time elapsed since the Unix epoch.
+impl< C: Context > Writable< C > for SystemTime {
+    #[inline]
+    fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+        let duration = self.duration_since( UNIX_EPOCH )
+            .map_err( |_| io::Error::new( io::ErrorKind::InvalidInput, "system times before the Unix epoch are not supported" ) )?;
+
+        writer.write_u64( duration.as_secs() )?;
+        writer.write_u32( duration.subsec_nanos() )
+    }
+
+    #[inline]
+    fn bytes_needed( &self ) -> usize {
+        12
+    }
+
+    #[inline]
+    fn max_bytes_len() -> Option< usize > {
+        Some( 12 )
+    }
+}
+
+macro_rules! impl_for_atomic {
+    ($type:ident, $inner:ty, $write_name:ident) => {
+        /// This writes a snapshot of the value, loaded with `Ordering::SeqCst`;
+        /// concurrent modifications while the rest of the data is written aren't reflected.
+        impl< C: Context > Writable< C > for $type {
             #[inline]
-            fn write_to< 'a, T: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut T ) -> io::Result< () > {
-                self.as_slice().write_to( writer )
+            fn write_to< 'a, W: ?Sized + Writer< 'a, C > >( &'a self, writer: &mut W ) -> io::Result< () > {
+                writer.$write_name( self.load( Ordering::SeqCst ) )
             }
 
             #[inline]
             fn bytes_needed( &self ) -> usize {
-                Writable::< C >::bytes_needed( self.as_slice() )
+                mem::size_of::< $inner >()
+            }
+
+            #[inline]
+            fn max_bytes_len() -> Option< usize > {
+                Some( mem::size_of::< $inner >() )
             }
         }
     }
 }
 
-impl_for_primitive_slice!( i16, write_i16 );
-impl_for_primitive_slice!( i32, write_i32 );
-impl_for_primitive_slice!( i64, write_i64 );
-impl_for_primitive_slice!( u16, write_u16 );
-impl_for_primitive_slice!( u32, write_u32 );
-impl_for_primitive_slice!( u64, write_u64 );
-impl_for_primitive_slice!( f32, write_f32 );
-impl_for_primitive_slice!( f64, write_f64 );
+impl_for_atomic!( AtomicU8, u8, write_u8 );
+impl_for_atomic!( AtomicU16, u16, write_u16 );
+impl_for_atomic!( AtomicU32, u32, write_u32 );
+#[cfg(target_has_atomic = "64")]
+impl_for_atomic!( AtomicU64, u64, write_u64 );
+impl_for_atomic!( AtomicI8, i8, write_i8 );
+impl_for_atomic!( AtomicI16, i16, write_i16 );
+impl_for_atomic!( AtomicI32, i32, write_i32 );
+#[cfg(target_has_atomic = "64")]
+impl_for_atomic!( AtomicI64, i64, write_i64 );
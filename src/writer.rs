@@ -4,6 +4,7 @@ use std::mem;
 use context::Context;
 use endianness::Endianness;
 use writable::Writable;
+use writable_impl::{write_length, write_elements};
 
 pub trait Writer< 'a, C: Context > {
     fn write_bytes( &mut self, slice: &'a [u8] ) -> io::Result< () >;
@@ -47,11 +48,107 @@ pub trait Writer< 'a, C: Context > {
         self.write_u64( value )
     }
 
+    /// Writes an `u64` as a LEB128 varint: seven bits at a time, starting with
+    /// the least significant ones, with the high bit set on every byte except the last.
+    #[inline]
+    fn write_varint_u64( &mut self, mut value: u64 ) -> io::Result< () > {
+        while value >= 0x80 {
+            self.write_u8( (value as u8) | 0x80 )?;
+            value >>= 7;
+        }
+
+        self.write_u8( value as u8 )
+    }
+
+    /// Writes a string into exactly `length` bytes, padding it with trailing NULs.
+    ///
+    /// Fails if the string is longer than `length` bytes.
+    #[inline]
+    fn write_fixed_string( &mut self, value: &str, length: usize ) -> io::Result< () > {
+        if value.len() > length {
+            return Err( io::Error::new( io::ErrorKind::InvalidInput, "string is longer than its fixed length" ) );
+        }
+
+        let mut bytes = Vec::with_capacity( length );
+        bytes.extend_from_slice( value.as_bytes() );
+        bytes.resize( length, 0 );
+        self.write_owned_bytes( bytes )
+    }
+
+    /// Writes exactly `count` elements without prefixing them with their length.
+    ///
+    /// Fails if there aren't exactly `count` elements.
+    #[inline]
+    fn write_fixed_count< T: Writable< C > >( &mut self, values: &'a [T], count: usize ) -> io::Result< () > {
+        if values.len() != count {
+            return Err( io::Error::new( io::ErrorKind::InvalidInput, "the number of elements doesn't match their fixed count" ) );
+        }
+
+        write_elements( self, values )
+    }
+
+    /// Writes the strings front-coded: each one is stored as the length of the prefix
+    /// it shares with the previous string (at most 255 bytes) followed by the rest of it.
+    ///
+    /// The strings are written in the given order, so this works best if they're sorted.
+    #[inline]
+    fn write_front_coded_strings( &mut self, strings: &'a [String] ) -> io::Result< () > {
+        write_length( self, strings.len() )?;
+        let mut previous: &[u8] = &[];
+        for string in strings {
+            let bytes = string.as_bytes();
+            let prefix = bytes.iter().zip( previous ).take( 255 ).take_while( |&(a, b)| a == b ).count();
+            self.write_u8( prefix as u8 )?;
+            let suffix = &bytes[ prefix.. ];
+            write_length( self, suffix.len() )?;
+            self.write_bytes( suffix )?;
+            previous = bytes;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the count of the strings, then the lengths of all of them,
+    /// and finally all of their bytes one after another, so that the bytes
+    /// of consecutive strings are also next to each other in memory when read.
+    ///
+    /// For example `["ab", "c"]` is written as `2`, `2`, `1` (each as an `u32`) and then `abc`.
+    #[inline]
+    fn write_split_length_strings( &mut self, strings: &'a [String] ) -> io::Result< () > {
+        write_length( self, strings.len() )?;
+        for string in strings {
+            write_length( self, string.len() )?;
+        }
+
+        for string in strings {
+            self.write_bytes( string.as_bytes() )?;
+        }
+
+        Ok(())
+    }
+
+    /// Called by the derived `Writable` impls before every field is written;
+    /// only used for instrumentation.
+    #[doc(hidden)]
+    #[inline]
+    fn begin_field( &mut self, _label: &'static str ) {}
+
+    /// Called by the derived `Writable` impls after every field is written.
+    #[doc(hidden)]
+    #[inline]
+    fn end_field( &mut self ) {}
+
     #[inline]
     fn endianness( &self ) -> Endianness {
         self.context().endianness()
     }
 
+    /// The endianness of the length prefixes; see `Context::length_endianness`.
+    #[inline]
+    fn length_endianness( &self ) -> Endianness {
+        self.context().length_endianness().unwrap_or_else( || self.endianness() )
+    }
+
     #[inline]
     fn write_value< T: Writable< C > >( &mut self, item: &'a T ) -> io::Result< () > {
         item.write_to( self )
@@ -4,7 +4,7 @@ use std::borrow::Cow;
 extern crate speedy_derive;
 extern crate speedy;
 
-#[derive(PartialEq, Debug, Readable, Writable)]
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
 struct DerivedStruct {
     a: u8,
     b: u16,
@@ -27,6 +27,66 @@ enum DerivedSimpleEnum {
     C
 }
 
+#[derive(PartialEq, Debug, Readable, Writable)]
+enum DerivedStatus {
+    Ok = 200,
+    NotFound = 404
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(tag_type = "varint")]
+enum DerivedEnumWithVarIntTag {
+    Ok = 1,
+    Retry = 1000,
+    Fatal = 50000
+}
+
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
+#[speedy(tag_type = "u8", tag_position = "trailing")]
+enum DerivedEnumWithTrailingTag {
+    Point { x: u16, y: u16 },
+    Size( u32 )
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+enum DerivedEmptyEnum {}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(tag_type = "u8")]
+enum DerivedEnumWithU8Tag {
+    A = 1,
+    B = 255
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(tag_type = "auto")]
+enum DerivedEnumWithAutoTag {
+    A,
+    B,
+    C
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(tag_type = "auto")]
+enum DerivedEnumWithAutoTagAndBigDiscriminant {
+    Small = 1,
+    Big = 300
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[repr(u16)]
+enum DerivedEnumWithU16Repr {
+    A = 1,
+    B = 0x1234
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[repr(u16)]
+#[speedy(tag_type = "u8")]
+enum DerivedEnumWithOverriddenRepr {
+    A = 1
+}
+
 #[derive(PartialEq, Debug, Readable, Writable)]
 enum DerivedEnum {
     A,
@@ -43,6 +103,12 @@ struct DerivedStructWithLifetime< 'a > {
     bytes: Cow< 'a, [u8] >
 }
 
+#[derive(PartialEq, Debug, Readable, Writable)]
+enum DerivedEnumWithBorrowedFields< 'a > {
+    Text( &'a str ),
+    Blob { bytes: &'a [u8] }
+}
+
 #[derive(PartialEq, Debug, Readable, Writable)]
 struct DerivedStructWithGenericRef< 'a, T: 'a + ?Sized > {
     inner: &'a T
@@ -53,6 +119,33 @@ struct DerivedStructWithGeneric< T > {
     inner: T
 }
 
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithConstGenerics< const R: usize, const C: usize > {
+    data: [[f32; C]; R]
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Readable, Writable)]
+#[repr(C, packed)]
+#[speedy(pod)]
+struct DerivedPodStruct {
+    a: u8,
+    b: u16,
+    c: u32
+}
+
+// Without `packed` there would be padding between these fields.
+#[derive(Copy, Clone, PartialEq, Debug, Readable, Writable)]
+#[repr(C, packed)]
+#[speedy(pod)]
+struct DerivedUnalignedPodStruct {
+    a: u8,
+    b: u32
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(transparent)]
+struct DerivedTransparentStruct( f64 );
+
 macro_rules! define_test {
     ($($name:ident: $value:expr, $serialized:expr)*) => { $(
         #[test]
@@ -102,6 +195,62 @@ define_test!(
         DerivedSimpleEnum::C,
         &[11, 0, 0, 0]
 
+    test_derived_enum_with_big_discriminant_ok:
+        DerivedStatus::Ok,
+        &[200, 0, 0, 0]
+
+    test_derived_enum_with_big_discriminant_not_found:
+        DerivedStatus::NotFound,
+        &[0x94, 0x01, 0, 0]
+
+    test_derived_enum_with_u8_tag_a:
+        DerivedEnumWithU8Tag::A,
+        &[1]
+
+    test_derived_enum_with_u8_tag_b:
+        DerivedEnumWithU8Tag::B,
+        &[255]
+
+    test_derived_enum_with_auto_tag:
+        DerivedEnumWithAutoTag::C,
+        &[2]
+
+    test_derived_enum_with_auto_tag_and_big_discriminant:
+        DerivedEnumWithAutoTagAndBigDiscriminant::Big,
+        &[0x2c, 0x01]
+
+    test_derived_enum_with_varint_tag_small:
+        DerivedEnumWithVarIntTag::Ok,
+        &[1]
+
+    test_derived_enum_with_varint_tag_medium:
+        DerivedEnumWithVarIntTag::Retry,
+        &[0xe8, 0x07]
+
+    test_derived_enum_with_varint_tag_large:
+        DerivedEnumWithVarIntTag::Fatal,
+        &[0xd0, 0x86, 0x03]
+
+    test_derived_enum_with_trailing_tag_point:
+        DerivedEnumWithTrailingTag::Point { x: 1, y: 2 },
+        &[1, 0, 2, 0, 0]
+
+    test_derived_enum_with_trailing_tag_size:
+        DerivedEnumWithTrailingTag::Size( 0x01020304 ),
+        &[4, 3, 2, 1, 1]
+
+    test_derived_enum_with_u16_repr_a:
+        DerivedEnumWithU16Repr::A,
+        &[1, 0]
+
+    test_derived_enum_with_u16_repr_b:
+        DerivedEnumWithU16Repr::B,
+        &[0x34, 0x12]
+
+    test_derived_enum_with_overridden_repr:
+        DerivedEnumWithOverriddenRepr::A,
+        &[1]
+
     test_derived_enum_unit_variant:
         DerivedEnum::A,
         &[0, 0, 0, 0]
@@ -121,4 +270,1625 @@ define_test!(
     test_derived_struct_with_generic:
         DerivedStructWithGeneric { inner: Cow::Borrowed( &[1_u8, 2_u8, 3_u8][..] ) },
         &[3, 0, 0, 0, 1, 2, 3]
+
+    test_derived_pod_struct:
+        DerivedPodStruct { a: 1, b: 2, c: 3 },
+        &[1, 2, 0, 3, 0, 0, 0]
+
+    test_derived_transparent_struct:
+        DerivedTransparentStruct( 1.0 ),
+        &[0, 0, 0, 0, 0, 0, 240, 63]
+
+    test_derived_struct_with_const_generics:
+        DerivedStructWithConstGenerics::< 2, 3 > { data: [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]] },
+        &[
+            0, 0, 128, 63,   0, 0, 0, 64,   0, 0, 64, 64,
+            0, 0, 128, 64,   0, 0, 160, 64, 0, 0, 192, 64
+        ]
 );
+
+fn framed( messages: &[&[u8]] ) -> Vec< u8 > {
+    let mut buffer = Vec::new();
+    for message in messages {
+        buffer.extend_from_slice( &[message.len() as u8, 0, 0, 0] );
+        buffer.extend_from_slice( message );
+    }
+    buffer
+}
+
+#[test]
+fn test_stream_from_reader() {
+    use speedy::{Readable, Endianness};
+
+    let buffer = framed( &[
+        &[1, 2, 0, 3, 0, 0, 0],
+        &[4, 5, 0, 6, 0, 0, 0],
+        &[7, 8, 0, 9, 0, 0, 0]
+    ]);
+
+    let values: Vec< DerivedStruct > = DerivedStruct::stream_from_reader( Endianness::LittleEndian, &buffer[..] )
+        .collect::< Result< _, _ > >()
+        .unwrap();
+
+    assert_eq!( values, vec![
+        DerivedStruct { a: 1, b: 2, c: 3 },
+        DerivedStruct { a: 4, b: 5, c: 6 },
+        DerivedStruct { a: 7, b: 8, c: 9 }
+    ]);
+}
+
+#[test]
+fn test_stream_from_reader_truncated_frame() {
+    use std::io;
+    use speedy::{Readable, Endianness};
+
+    let mut buffer = framed( &[
+        &[1, 2, 0, 3, 0, 0, 0],
+        &[4, 5, 0, 6, 0, 0, 0]
+    ]);
+    buffer.pop();
+
+    let mut stream = DerivedStruct::stream_from_reader( Endianness::LittleEndian, &buffer[..] );
+    assert_eq!( stream.next().unwrap().unwrap(), DerivedStruct { a: 1, b: 2, c: 3 } );
+    assert_eq!( stream.next().unwrap().unwrap_err().kind(), io::ErrorKind::UnexpectedEof );
+    assert!( stream.next().is_none() );
+}
+
+#[test]
+fn test_derived_enum_with_big_discriminants_rejects_other_tags() {
+    use std::io;
+    use speedy::{Readable, Endianness};
+
+    for &tag in &[0_u32, 1, 201, 403, 405] {
+        let error = DerivedStatus::read_from_buffer( Endianness::LittleEndian, &tag.to_le_bytes() ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    }
+
+    assert_eq!( DerivedStatus::read_from_buffer( Endianness::BigEndian, &[0, 0, 0x01, 0x94] ).unwrap(), DerivedStatus::NotFound );
+}
+
+#[test]
+fn test_derived_empty_enum_read_fails() {
+    use std::io;
+    use speedy::{Readable, Endianness};
+
+    let error = DerivedEmptyEnum::read_from_buffer( Endianness::LittleEndian, &[0, 0, 0, 0] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+}
+
+#[test]
+fn test_derived_pod_struct_matches_field_by_field() {
+    use speedy::{Readable, Writable, Endianness};
+
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let pod = DerivedPodStruct { a: 1, b: 0x0203, c: 0x04050607 };
+        let plain = DerivedStruct { a: 1, b: 0x0203, c: 0x04050607 };
+
+        let serialized = pod.write_to_vec( endianness ).unwrap();
+        assert_eq!( serialized, plain.write_to_vec( endianness ).unwrap() );
+
+        let deserialized = DerivedPodStruct::read_from_buffer( endianness, &serialized ).unwrap();
+        assert_eq!( deserialized, pod );
+    }
+
+    let pod = DerivedUnalignedPodStruct { a: 1, b: 2 };
+    let serialized = pod.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 2, 0, 0, 0] );
+    assert_eq!( DerivedUnalignedPodStruct::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), pod );
+
+    let serialized = pod.write_to_vec( Endianness::BigEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 0, 0, 2] );
+    assert_eq!( DerivedUnalignedPodStruct::read_from_buffer( Endianness::BigEndian, &serialized ).unwrap(), pod );
+}
+
+#[test]
+fn test_write_framed_to_stream() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let originals = vec![
+        DerivedStruct { a: 1, b: 2, c: 3 },
+        DerivedStruct { a: 4, b: 5, c: 6 },
+        DerivedStruct { a: 7, b: 8, c: 9 }
+    ];
+
+    let mut buffer = Vec::new();
+    for value in &originals {
+        value.write_framed_to_stream( Endianness::LittleEndian, &mut buffer ).unwrap();
+    }
+
+    assert_eq!( &buffer[ ..11 ], &[7, 0, 0, 0, 1, 2, 0, 3, 0, 0, 0] );
+
+    let values: Vec< DerivedStruct > = DerivedStruct::stream_from_reader( Endianness::LittleEndian, &buffer[..] )
+        .collect::< Result< _, _ > >()
+        .unwrap();
+
+    assert_eq!( values, originals );
+
+    // A value which can't be written is an error instead of a panic, and nothing is written.
+    struct Unwritable;
+    impl< C: speedy::Context > Writable< C > for Unwritable {
+        fn write_to< 'a, W: ?Sized + speedy::Writer< 'a, C > >( &'a self, _: &mut W ) -> std::io::Result< () > {
+            Err( std::io::Error::new( std::io::ErrorKind::InvalidInput, "can't be written" ) )
+        }
+    }
+
+    let mut buffer = Vec::new();
+    let error = Unwritable.write_framed_to_stream( Endianness::LittleEndian, &mut buffer ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidInput );
+    assert!( buffer.is_empty() );
+}
+
+#[test]
+fn test_seekable_reader() {
+    use std::io::Cursor;
+    use speedy::{Reader, Writable, Endianness, SeekableReader};
+
+    let first = DerivedStructWithLifetime { bytes: Cow::Borrowed( &[1, 2, 3] ) };
+    let second = DerivedStruct { a: 4, b: 5, c: 6 };
+
+    let mut buffer = Vec::new();
+    let first_offset = buffer.len() as u64;
+    first.write_to_stream( Endianness::LittleEndian, &mut buffer ).unwrap();
+    let second_offset = buffer.len() as u64;
+    second.write_to_stream( Endianness::LittleEndian, &mut buffer ).unwrap();
+
+    let mut reader = SeekableReader::new( Endianness::LittleEndian, Cursor::new( &buffer[..] ) );
+    let value: DerivedStruct = reader.read_value_at( second_offset ).unwrap();
+    assert_eq!( value, second );
+    assert_eq!( reader.position().unwrap(), buffer.len() as u64 );
+
+    reader.seek_to( first_offset ).unwrap();
+    assert_eq!( reader.peek_u32().unwrap(), 3 );
+    assert_eq!( reader.position().unwrap(), first_offset );
+    let value: DerivedStructWithLifetime = reader.read_value().unwrap();
+    assert_eq!( value, first );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithString {
+    a: u8,
+    b: u16,
+    name: String
+}
+
+#[test]
+fn test_derived_struct_field_error() {
+    use std::io;
+    use speedy::{Readable, Endianness, FieldError};
+
+    let error = DerivedStructWithString::read_from_buffer( Endianness::LittleEndian, &[1, 2, 0, 2, 0, 0, 0, 0xff, 0xfe] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    let field_error = error.get_ref().unwrap().downcast_ref::< FieldError >().unwrap();
+    assert_eq!( field_error.type_name(), "DerivedStructWithString" );
+    assert_eq!( field_error.field_name(), "name" );
+    assert_eq!( field_error.field_index(), 2 );
+    assert!( error.to_string().contains( "`name`" ) );
+
+    let error = DerivedEnum::read_from_buffer( Endianness::LittleEndian, &[1, 0, 0, 0, 10, 20] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+    let field_error = error.get_ref().unwrap().downcast_ref::< FieldError >().unwrap();
+    assert_eq!( field_error.type_name(), "DerivedEnum::B" );
+    assert_eq!( field_error.field_name(), "1" );
+
+    // A frame knows how much of it is left, so the consumed bytes can be counted.
+    let framed = [9, 0, 0, 0, 1, 2, 0, 2, 0, 0, 0, 0xff, 0xfe];
+    let error = DerivedStructWithString::stream_from_reader( Endianness::LittleEndian, &framed[..] ).next().unwrap().unwrap_err();
+    let field_error = error.get_ref().unwrap().downcast_ref::< FieldError >().unwrap();
+    assert_eq!( field_error.field_name(), "name" );
+    assert_eq!( field_error.bytes_consumed(), Some( 9 ) );
+
+    // What's read through a variant's frame is still counted from the start of the enum.
+    let error = DerivedEventV2::read_from_buffer( Endianness::LittleEndian, &[0, 4, 0, 0, 0, 1, 0, 2, 0] ).unwrap_err();
+    let field_error = error.get_ref().unwrap().downcast_ref::< FieldError >().unwrap();
+    assert_eq!( field_error.field_name(), "button" );
+    assert_eq!( field_error.bytes_consumed(), Some( 9 ) );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithDefaultedFields {
+    a: u8,
+    #[speedy(skip, default_value = 42)]
+    b: u32,
+    #[speedy(default_on_eof)]
+    c: u16,
+    #[speedy(default_on_eof, default_value = 7)]
+    d: u8
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+enum DerivedEnumWithSkippedFields {
+    A {
+        #[speedy(skip)]
+        a: u8,
+        b: u8
+    },
+    B( #[speedy(skip)] u8, u8 )
+}
+
+#[test]
+fn test_derived_struct_with_defaulted_fields() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithDefaultedFields { a: 1, b: 100, c: 2, d: 3 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 2, 0, 3] );
+
+    let deserialized = DerivedStructWithDefaultedFields::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, DerivedStructWithDefaultedFields { a: 1, b: 42, c: 2, d: 3 } );
+
+    let deserialized = DerivedStructWithDefaultedFields::read_from_buffer( Endianness::LittleEndian, &[1] ).unwrap();
+    assert_eq!( deserialized, DerivedStructWithDefaultedFields { a: 1, b: 42, c: 0, d: 7 } );
+
+    let serialized = DerivedEnumWithSkippedFields::A { a: 1, b: 2 }.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0, 0, 0, 0, 2] );
+    let deserialized = DerivedEnumWithSkippedFields::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, DerivedEnumWithSkippedFields::A { a: 0, b: 2 } );
+
+    let serialized = DerivedEnumWithSkippedFields::B( 1, 2 ).write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 0, 0, 2] );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedTupleStructWithSkippedField( u32, #[speedy(skip)] String, u8 );
+
+#[test]
+fn test_derived_tuple_struct_with_skipped_field() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedTupleStructWithSkippedField( 0x01020304, "cache".to_owned(), 5 );
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [4, 3, 2, 1, 5] );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &original ), 5 );
+    assert_eq!( <DerivedTupleStructWithSkippedField as Readable< Endianness >>::minimum_bytes_needed(), 5 );
+
+    let deserialized = DerivedTupleStructWithSkippedField::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, DerivedTupleStructWithSkippedField( 0x01020304, String::new(), 5 ) );
+
+    let mut value = original;
+    value.read_into( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( value, deserialized );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithFixedString {
+    id: u8,
+    #[speedy(fixed_length = 16)]
+    name: String
+}
+
+#[test]
+fn test_derived_struct_with_fixed_string() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithFixedString { id: 1, name: "speedy".to_owned() };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized.len(), 1 + 16 );
+    assert_eq!( &serialized[ 1..7 ], b"speedy" );
+    assert!( serialized[ 7.. ].iter().all( |&byte| byte == 0 ) );
+
+    let deserialized = DerivedStructWithFixedString::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( original, deserialized );
+
+    let too_long = DerivedStructWithFixedString { id: 1, name: "a name which is way too long".to_owned() };
+    let error = too_long.write_to_vec( Endianness::LittleEndian ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidInput );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithOptionalFields {
+    a: u8,
+    #[speedy(skip_if_default)]
+    b: u32,
+    #[speedy(skip_if_default)]
+    c: String
+}
+
+#[test]
+fn test_derived_struct_with_optional_fields() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let full = DerivedStructWithOptionalFields { a: 1, b: 2, c: "x".to_owned() };
+    let serialized = full.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0b11, 1, 2, 0, 0, 0, 1, 0, 0, 0, b'x'] );
+    assert_eq!( DerivedStructWithOptionalFields::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), full );
+
+    let sparse = DerivedStructWithOptionalFields { a: 1, b: 0, c: "x".to_owned() };
+    let serialized = sparse.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0b10, 1, 1, 0, 0, 0, b'x'] );
+    assert_eq!( DerivedStructWithOptionalFields::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), sparse );
+}
+
+#[derive(PartialEq, Debug, Default, Readable, Writable)]
+#[speedy(pack_bools)]
+struct DerivedStructWithPackedBools {
+    f0: bool,
+    f1: bool,
+    id: u16,
+    f2: bool,
+    f3: bool,
+    f4: bool,
+    f5: bool,
+    name: String,
+    f6: bool,
+    f7: bool,
+    f8: bool,
+    f9: bool
+}
+
+#[test]
+fn test_derived_struct_with_packed_bools() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithPackedBools {
+        f0: true,
+        f3: true,
+        f7: true,
+        f8: true,
+        id: 0x1234,
+        name: "x".to_owned(),
+        ..DerivedStructWithPackedBools::default()
+    };
+
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0b1000_1001, 0b01, 0x34, 0x12, 1, 0, 0, 0, b'x'] );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &original ), serialized.len() );
+    assert_eq!( <DerivedStructWithPackedBools as Readable< Endianness >>::minimum_bytes_needed(), 2 + 2 + 4 );
+
+    let deserialized = DerivedStructWithPackedBools::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let mut in_place = DerivedStructWithPackedBools { f1: true, f9: true, ..DerivedStructWithPackedBools::default() };
+    in_place.read_into( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( in_place, original );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(validate = "validate_range")]
+struct DerivedValidatedRange {
+    start: u32,
+    end: u32
+}
+
+fn validate_range( range: &DerivedValidatedRange ) -> Result< (), &'static str > {
+    if range.start <= range.end {
+        Ok(())
+    } else {
+        Err( "range starts after it ends" )
+    }
+}
+
+#[test]
+fn test_derived_struct_validation() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let valid = DerivedValidatedRange { start: 1, end: 2 };
+    let serialized = valid.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( DerivedValidatedRange::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), valid );
+
+    let invalid = DerivedValidatedRange { start: 2, end: 1 };
+    let serialized = invalid.write_to_vec( Endianness::LittleEndian ).unwrap();
+    let error = DerivedValidatedRange::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    assert_eq!( error.to_string(), "range starts after it ends" );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(bound(read = "T: ::speedy::Readable< 'a_, C_ > + Copy", write = "T: ::speedy::Writable< C_ >"))]
+struct DerivedStructWithCustomBound< T > {
+    items: [T; 2]
+}
+
+#[test]
+fn test_derived_struct_with_custom_bound() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithCustomBound { items: [1_u16, 2] };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 2, 0] );
+
+    let deserialized: DerivedStructWithCustomBound< u16 > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(endianness = "big")]
+struct DerivedBigEndianStruct {
+    a: u16,
+    b: u32,
+    c: Vec< u16 >
+}
+
+#[test]
+fn test_derived_struct_with_overridden_endianness() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedBigEndianStruct { a: 1, b: 2, c: vec![ 3 ] };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0, 1, 0, 0, 0, 2, 0, 0, 0, 1, 0, 3] );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &original ), serialized.len() );
+
+    let deserialized = DerivedBigEndianStruct::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+}
+
+#[cfg(feature = "instrumentation")]
+#[test]
+fn test_instrumented_histogram() {
+    use speedy::{Readable, Writable, Endianness, Instrumented, VecWriter, BufferReader};
+
+    let mut writer = Instrumented::new( VecWriter::new( Endianness::LittleEndian ) );
+    DerivedStruct { a: 1, b: 2, c: 3 }.write_to( &mut writer ).unwrap();
+    let (writer, histogram) = writer.into_inner();
+    assert_eq!( histogram[ "DerivedStruct.a" ], 1 );
+    assert_eq!( histogram[ "DerivedStruct.b" ], 2 );
+    assert_eq!( histogram[ "DerivedStruct.c" ], 4 );
+    assert_eq!( histogram.len(), 3 );
+
+    let serialized = writer.into_inner();
+    let mut reader = Instrumented::new( BufferReader::new( Endianness::LittleEndian, &serialized ) );
+    let _ = DerivedStruct::read_from( &mut reader ).unwrap();
+    assert_eq!( reader.histogram(), &histogram );
+
+    let mut writer = Instrumented::new( VecWriter::new( Endianness::LittleEndian ) );
+    DerivedEnum::B( 1, 2, 3 ).write_to( &mut writer ).unwrap();
+    assert_eq!( writer.histogram()[ "DerivedEnum::B.2" ], 4 );
+}
+
+#[test]
+fn test_vec_of_derived_structs() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original: Vec< DerivedStruct > = (0..100_000).map( |index| DerivedStruct { a: index as u8, b: index as u16, c: index } ).collect();
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let serialized = original.write_to_vec( endianness ).unwrap();
+        assert_eq!( serialized.len(), 4 + original.len() * 7 );
+        assert_eq!( Writable::< Endianness >::bytes_needed( &original ), serialized.len() );
+
+        let deserialized: Vec< DerivedStruct > = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+        assert_eq!( deserialized, original );
+    }
+
+    assert_eq!( <DerivedStruct as Readable< 'static, Endianness >>::minimum_bytes_needed(), 7 );
+
+    // A bogus length is rejected before anything is allocated for it.
+    let error = Vec::< DerivedStruct >::read_from_buffer( Endianness::LittleEndian, &[0xff, 0xff, 0xff, 0xff, 1, 2, 0, 3, 0, 0, 0] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedEvent {
+    at: std::time::SystemTime,
+    dur: std::time::Duration,
+    tags: Vec< String >
+}
+
+#[test]
+fn test_derived_struct_with_time_fields() {
+    use std::time::{Duration, UNIX_EPOCH};
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedEvent {
+        at: UNIX_EPOCH + Duration::new( 1_500_000_000, 123_456_789 ),
+        dur: Duration::from_millis( 2500 ),
+        tags: vec![ "a".to_owned(), "bc".to_owned() ]
+    };
+
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let serialized = original.write_to_vec( endianness ).unwrap();
+        assert_eq!( serialized.len(), 12 + 12 + 4 + 5 + 6 );
+
+        let deserialized = DerivedEvent::read_from_buffer( endianness, &serialized ).unwrap();
+        assert_eq!( deserialized, original );
+    }
+}
+
+#[test]
+fn test_derived_minimum_bytes_needed() {
+    use speedy::{Readable, Endianness};
+
+    assert_eq!( <DerivedStruct as Readable< Endianness >>::minimum_bytes_needed(), 7 );
+    assert_eq!( <DerivedTupleStruct as Readable< Endianness >>::minimum_bytes_needed(), 7 );
+    assert_eq!( <DerivedUnitStruct as Readable< Endianness >>::minimum_bytes_needed(), 0 );
+    assert_eq!( <DerivedEnum as Readable< Endianness >>::minimum_bytes_needed(), 4 );
+    assert_eq!( <DerivedEnumWithU8Tag as Readable< Endianness >>::minimum_bytes_needed(), 1 );
+    assert_eq!( <DerivedEnumWithVarIntTag as Readable< Endianness >>::minimum_bytes_needed(), 1 );
+    assert_eq!( <DerivedEnumWithTrailingTag as Readable< Endianness >>::minimum_bytes_needed(), 5 );
+    assert_eq!( <DerivedEnumWithAutoTag as Readable< Endianness >>::minimum_bytes_needed(), 1 );
+    assert_eq!( <DerivedEnumWithAutoTagAndBigDiscriminant as Readable< Endianness >>::minimum_bytes_needed(), 2 );
+    assert_eq!( <DerivedEmptyEnum as Readable< Endianness >>::minimum_bytes_needed(), 0 );
+    assert_eq!( <DerivedEvent as Readable< Endianness >>::minimum_bytes_needed(), 12 + 12 + 4 );
+}
+
+#[test]
+fn test_derived_max_bytes_len() {
+    use speedy::{Writable, Endianness};
+
+    assert_eq!( <DerivedStruct as Writable< Endianness >>::max_bytes_len(), Some( 7 ) );
+    assert_eq!( <DerivedUnitStruct as Writable< Endianness >>::max_bytes_len(), Some( 0 ) );
+    assert_eq!( <DerivedHashedStruct as Writable< Endianness >>::max_bytes_len(), Some( 8 + 7 ) );
+    assert_eq!( <DerivedStructWithFixedString as Writable< Endianness >>::max_bytes_len(), Some( 1 + 16 ) );
+    assert_eq!( <DerivedStructWithFixedCount as Writable< Endianness >>::max_bytes_len(), Some( 32 + 2 * 2 ) );
+    assert_eq!( <DerivedStructWithString as Writable< Endianness >>::max_bytes_len(), None );
+    assert_eq!( <DerivedStructWithOptionalFields as Writable< Endianness >>::max_bytes_len(), None );
+    assert_eq!( <DerivedEnum as Writable< Endianness >>::max_bytes_len(), Some( 4 + 7 ) );
+    assert_eq!( <DerivedEnumWithU8Tag as Writable< Endianness >>::max_bytes_len(), Some( 1 ) );
+    assert_eq!( <DerivedEnumWithVarIntTag as Writable< Endianness >>::max_bytes_len(), Some( 10 ) );
+    assert_eq!( <DerivedEnumWithTrailingTag as Writable< Endianness >>::max_bytes_len(), Some( 4 + 1 ) );
+    assert_eq!( <DerivedEmptyEnum as Writable< Endianness >>::max_bytes_len(), Some( 0 ) );
+    assert_eq!( <DerivedEventV2 as Writable< Endianness >>::max_bytes_len(), Some( 1 + 4 + 5 ) );
+}
+
+#[test]
+fn test_derived_struct_schema() {
+    use speedy::{Schema, SchemaNode, SchemaField};
+
+    let field = |name, ty| SchemaField { name, ty, is_skipped_if_default: false, is_packed: false, since: None, until: None, schema: SchemaNode::Primitive( ty ) };
+    assert_eq!( DerivedStruct::schema(), SchemaNode::Struct {
+        name: "DerivedStruct",
+        type_hash: None,
+        version: None,
+        fields: vec![ field( "a", "u8" ), field( "b", "u16" ), field( "c", "u32" ) ],
+        end_marker: None
+    });
+
+    assert_eq!(
+        DerivedStruct::schema().to_json(),
+        concat!(
+            r#"{"kind":"struct","name":"DerivedStruct","fields":["#,
+            r#"{"name":"a","type":"u8","schema":{"kind":"primitive","type":"u8"}},"#,
+            r#"{"name":"b","type":"u16","schema":{"kind":"primitive","type":"u16"}},"#,
+            r#"{"name":"c","type":"u32","schema":{"kind":"primitive","type":"u32"}}"#,
+            r#"]}"#
+        )
+    );
+}
+
+#[test]
+fn test_derived_enum_schema() {
+    use speedy::{Schema, SchemaNode};
+
+    match DerivedEnumWithTrailingTag::schema() {
+        SchemaNode::Enum { name, tag_type, is_tag_trailing, are_variants_length_prefixed, variants } => {
+            assert_eq!( name, "DerivedEnumWithTrailingTag" );
+            assert_eq!( tag_type, "u8" );
+            assert!( is_tag_trailing );
+            assert!( !are_variants_length_prefixed );
+            assert_eq!( variants.iter().map( |variant| (variant.name, variant.tag) ).collect::< Vec< _ > >(), vec![ ("Point", 0), ("Size", 1) ] );
+            assert_eq!( variants[ 0 ].fields.iter().map( |field| field.name ).collect::< Vec< _ > >(), vec![ "x", "y" ] );
+            assert_eq!( variants[ 1 ].fields[ 0 ].name, "0" );
+            assert_eq!( variants[ 1 ].fields[ 0 ].schema, SchemaNode::Primitive( "u32" ) );
+        },
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn test_read_from_mmap() {
+    use std::fs;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithLifetime { bytes: Cow::Borrowed( &[2, 4, 8] ) };
+    let path = std::env::temp_dir().join( format!( "speedy-mmap-test-{}", std::process::id() ) );
+    fs::write( &path, original.write_to_vec( Endianness::LittleEndian ).unwrap() ).unwrap();
+
+    let mmap = unsafe { speedy::map_file( &path ) }.unwrap();
+    let deserialized = DerivedStructWithLifetime::read_from_mmap( Endianness::LittleEndian, &mmap ).unwrap();
+    assert_eq!( deserialized, original );
+    match deserialized.bytes {
+        Cow::Borrowed( bytes ) => assert_eq!( bytes.as_ptr(), mmap[ 4.. ].as_ptr() ),
+        Cow::Owned( _ ) => panic!( "expected a borrowed slice" )
+    }
+
+    drop( mmap );
+    fs::remove_file( &path ).unwrap();
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+enum DerivedEnumWithSkippedCache {
+    V {
+        id: u32,
+        #[speedy(skip)]
+        cache: String
+    },
+    W( u32, #[speedy(skip)] String )
+}
+
+#[test]
+fn test_derived_enum_with_skipped_cache() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedEnumWithSkippedCache::V { id: 5, cache: "cached".to_owned() };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0, 0, 0, 0, 5, 0, 0, 0] );
+
+    let deserialized = DerivedEnumWithSkippedCache::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, DerivedEnumWithSkippedCache::V { id: 5, cache: String::new() } );
+
+    let original = DerivedEnumWithSkippedCache::W( 6, "cached".to_owned() );
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 0, 0, 6, 0, 0, 0] );
+
+    let deserialized = DerivedEnumWithSkippedCache::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, DerivedEnumWithSkippedCache::W( 6, String::new() ) );
+}
+
+#[test]
+fn test_peek_derived_enum_tag() {
+    use speedy::{Writable, Endianness, BufferReader, Reader};
+
+    let original = DerivedEnum::B( 10, 11, 12 );
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let serialized = original.write_to_vec( endianness ).unwrap();
+        let mut reader = BufferReader::new( endianness, &serialized );
+        assert_eq!( reader.peek_u8().unwrap(), serialized[0] );
+        assert_eq!( reader.peek_u32().unwrap(), 1 );
+
+        let deserialized: DerivedEnum = reader.read_value().unwrap();
+        assert_eq!( deserialized, original );
+    }
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(type_hash)]
+struct DerivedHashedStruct {
+    a: u8,
+    b: u16,
+    c: u32
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(type_hash)]
+struct DerivedHashedStructWithChangedLayout {
+    a: u8,
+    b: u32,
+    c: u32
+}
+
+#[test]
+fn test_derived_struct_with_type_hash() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedHashedStruct { a: 1, b: 2, c: 3 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized.len(), 8 + 7 );
+    assert_eq!( &serialized[ 8.. ], &[1, 2, 0, 3, 0, 0, 0] );
+    assert_eq!( <DerivedHashedStruct as Readable< Endianness >>::minimum_bytes_needed(), 8 + 7 );
+
+    let deserialized = DerivedHashedStruct::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let mut padded = serialized.clone();
+    padded.extend_from_slice( &[0, 0] );
+    let error = DerivedHashedStructWithChangedLayout::read_from_buffer( Endianness::LittleEndian, &padded ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithFrontCodedStrings {
+    #[speedy(front_coded)]
+    words: Vec< String >
+}
+
+#[test]
+fn test_derived_struct_with_front_coded_strings() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let words: Vec< String > = vec![ "apple".to_owned(), "application".to_owned(), "apply".to_owned() ];
+    let original = DerivedStructWithFrontCodedStrings { words: words.clone() };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert!( serialized.len() < words.write_to_vec( Endianness::LittleEndian ).unwrap().len() );
+    assert_eq!( &serialized[ 4..14 ], &[0, 5, 0, 0, 0, b'a', b'p', b'p', b'l', b'e'] );
+    assert_eq!( &serialized[ 14..26 ], &[4, 7, 0, 0, 0, b'i', b'c', b'a', b't', b'i', b'o', b'n'] );
+    assert_eq!( &serialized[ 26.. ], &[4, 1, 0, 0, 0, b'y'] );
+
+    let deserialized = DerivedStructWithFrontCodedStrings::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let error = DerivedStructWithFrontCodedStrings::read_from_buffer( Endianness::LittleEndian, &[1, 0, 0, 0, 1, 0, 0, 0, 0] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithSplitLengthStrings {
+    #[speedy(split_lengths)]
+    words: Vec< String >,
+    count: u8
+}
+
+#[test]
+fn test_derived_struct_with_split_length_strings() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithSplitLengthStrings { words: vec![ "ab".to_owned(), "".to_owned(), "cde".to_owned() ], count: 7 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+
+    // The count, then every length, then all of the bytes.
+    assert_eq!( serialized, [
+        3, 0, 0, 0,
+        2, 0, 0, 0,
+        0, 0, 0, 0,
+        3, 0, 0, 0,
+        b'a', b'b', b'c', b'd', b'e',
+        7
+    ]);
+    assert_eq!( Writable::< Endianness >::bytes_needed( &original ), serialized.len() );
+
+    let deserialized = DerivedStructWithSplitLengthStrings::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let deserialized = DerivedStructWithSplitLengthStrings::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let error = DerivedStructWithSplitLengthStrings::read_from_buffer( Endianness::LittleEndian, &serialized[ ..18 ] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+    let error = DerivedStructWithSplitLengthStrings::read_from_buffer( Endianness::LittleEndian, &[1, 0, 0, 0, 1, 0, 0, 0, 0xff, 7] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    let error = DerivedStructWithSplitLengthStrings::read_from_stream( Endianness::LittleEndian, &[1, 0, 0, 0, 1, 0, 0, 0, 0xff, 7][..] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    // A stream's length table could claim gigabytes which never arrive.
+    let bogus = [2, 0, 0, 0, 0xf0, 0xff, 0xff, 0x7f, 0xf0, 0xff, 0xff, 0x7f, b'a', 7];
+    let error = DerivedStructWithSplitLengthStrings::read_from_stream( Endianness::LittleEndian, &bogus[..] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+}
+
+// Only `Writable` is derived, so the skipped field doesn't need a `Default` impl.
+#[derive(PartialEq, Debug, Writable)]
+struct DerivedWriteOnlyStruct< 'a > {
+    name: &'a str,
+    #[speedy(skip)]
+    cached: Option< String >,
+    count: u32
+}
+
+#[derive(PartialEq, Debug, Readable)]
+struct DerivedReadOnlyStruct {
+    id: u32,
+    #[speedy(default_on_eof, default_value = 7)]
+    version: u8
+}
+
+#[test]
+fn test_derive_only_one_direction() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedWriteOnlyStruct { name: "ab", cached: None, count: 3 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [2, 0, 0, 0, b'a', b'b', 3, 0, 0, 0] );
+
+    let deserialized = DerivedReadOnlyStruct::read_from_buffer( Endianness::LittleEndian, &[1, 0, 0, 0] ).unwrap();
+    assert_eq!( deserialized, DerivedReadOnlyStruct { id: 1, version: 7 } );
+
+    let deserialized = DerivedReadOnlyStruct::read_from_buffer( Endianness::LittleEndian, &[1, 0, 0, 0, 2] ).unwrap();
+    assert_eq!( deserialized, DerivedReadOnlyStruct { id: 1, version: 2 } );
+}
+
+#[test]
+fn test_read_from_owned_buffer() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStruct { a: 1, b: 2, c: 3 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    let array: [u8; 7] = [1, 2, 0, 3, 0, 0, 0];
+
+    assert_eq!( DerivedStruct::read_from_owned_buffer( Endianness::LittleEndian, serialized ).unwrap(), original );
+    assert_eq!( DerivedStruct::read_from_owned_buffer( Endianness::LittleEndian, array ).unwrap(), original );
+    assert_eq!( DerivedStruct::read_from_buffer( Endianness::LittleEndian, &array ).unwrap(), original );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedMessage {
+    id: u32,
+    payload: Vec< u8 >,
+    tags: Vec< String >
+}
+
+#[test]
+fn test_read_into_overwrites_the_previous_value() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let first = DerivedMessage { id: 1, payload: vec![ 1; 64 ], tags: vec![ "first".to_owned(), "second".to_owned() ] };
+    let second = DerivedMessage { id: 2, payload: vec![ 2, 3 ], tags: vec![ "third".to_owned() ] };
+
+    let mut message = DerivedMessage::read_from_buffer( Endianness::LittleEndian, &first.write_to_vec( Endianness::LittleEndian ).unwrap() ).unwrap();
+    assert_eq!( message, first );
+
+    let payload_pointer = message.payload.as_ptr();
+    let tag_pointer = message.tags[ 0 ].as_ptr();
+    message.read_into( Endianness::LittleEndian, &second.write_to_vec( Endianness::LittleEndian ).unwrap() ).unwrap();
+    assert_eq!( message, second );
+
+    // The allocations were big enough to be reused.
+    assert_eq!( message.payload.as_ptr(), payload_pointer );
+    assert_eq!( message.tags[ 0 ].as_ptr(), tag_pointer );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedEnvelope {
+    kind: u8,
+    body: speedy::RawBytes
+}
+
+#[test]
+fn test_embedding_raw_bytes() {
+    use speedy::{Readable, Writable, Endianness, RawBytes};
+
+    let inner = DerivedStruct { a: 1, b: 2, c: 3 };
+    let envelope = DerivedEnvelope { kind: 7, body: RawBytes( inner.write_to_vec( Endianness::LittleEndian ).unwrap() ) };
+
+    let serialized = envelope.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [7, 1, 2, 0, 3, 0, 0, 0] );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &envelope ), serialized.len() );
+
+    let deserialized = DerivedEnvelope::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, envelope );
+    assert_eq!( DerivedStruct::read_from_buffer( Endianness::LittleEndian, &deserialized.body ).unwrap(), inner );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedFramedEnvelope {
+    kind: u8,
+    body: speedy::Framed< DerivedStruct >,
+    trailer: u8
+}
+
+// The same layout as `DerivedFramedEnvelope`, for a reader which doesn't know what's inside of the frame.
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedOpaqueEnvelope {
+    kind: u8,
+    body: Vec< u8 >,
+    trailer: u8
+}
+
+#[test]
+fn test_embedding_framed_value() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness, Framed};
+
+    let envelope = DerivedFramedEnvelope { kind: 7, body: Framed( DerivedStruct { a: 1, b: 2, c: 3 } ), trailer: 9 };
+    let serialized = envelope.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [7, 7, 0, 0, 0, 1, 2, 0, 3, 0, 0, 0, 9] );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &envelope ), serialized.len() );
+    assert_eq!( <DerivedFramedEnvelope as Writable< Endianness >>::max_bytes_len(), Some( serialized.len() ) );
+    assert_eq!( DerivedFramedEnvelope::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), envelope );
+
+    let opaque = DerivedOpaqueEnvelope::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( opaque, DerivedOpaqueEnvelope { kind: 7, body: vec![ 1, 2, 0, 3, 0, 0, 0 ], trailer: 9 } );
+    assert_eq!( opaque.write_to_vec( Endianness::LittleEndian ).unwrap(), serialized );
+
+    // Whatever a newer version of the value appended to it is skipped.
+    let extended = [7, 9, 0, 0, 0, 1, 2, 0, 3, 0, 0, 0, 0xAA, 0xBB, 9];
+    assert_eq!( DerivedFramedEnvelope::read_from_buffer( Endianness::LittleEndian, &extended ).unwrap(), envelope );
+    assert_eq!( DerivedFramedEnvelope::read_from_stream( Endianness::LittleEndian, &extended[..] ).unwrap(), envelope );
+
+    let truncated = [7, 6, 0, 0, 0, 1, 2, 0, 3, 0, 0, 0, 9];
+    let error = DerivedFramedEnvelope::read_from_buffer( Endianness::LittleEndian, &truncated ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+}
+
+#[test]
+fn test_debug_serialization() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedEnum::C { a: 1, b: 2, c: 3 };
+    let serialized = original.write_to_vec_debug( Endianness::LittleEndian ).unwrap();
+
+    let mut expected = vec![ 2, 0, 0, 0 ];
+    for &(label, value) in &[("DerivedEnum::C.a", &[1][..]), ("DerivedEnum::C.b", &[2, 0][..]), ("DerivedEnum::C.c", &[3, 0, 0, 0][..])] {
+        expected.extend_from_slice( &(label.len() as u32).to_le_bytes() );
+        expected.extend_from_slice( label.as_bytes() );
+        expected.extend_from_slice( value );
+    }
+    assert_eq!( serialized, expected );
+
+    let deserialized = DerivedEnum::read_from_buffer_debug( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let serialized = DerivedStruct { a: 1, b: 2, c: 3 }.write_to_vec_debug( Endianness::LittleEndian ).unwrap();
+    let error = DerivedTupleStruct::read_from_buffer_debug( Endianness::LittleEndian, &serialized ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_tests {
+    extern crate bytes;
+
+    use self::bytes::{Bytes, BytesMut};
+    use speedy::{Readable, Writable, Endianness};
+
+    #[derive(PartialEq, Debug, Readable, Writable)]
+    struct DerivedPacket {
+        id: u16,
+        payload: Bytes,
+        scratch: BytesMut
+    }
+
+    #[test]
+    fn test_bytes_field_shares_the_buffer() {
+        let original = DerivedPacket {
+            id: 1,
+            payload: Bytes::from_static( &[10, 20, 30] ),
+            scratch: BytesMut::from( &[40][..] )
+        };
+
+        let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+        assert_eq!( serialized, [1, 0, 3, 0, 0, 0, 10, 20, 30, 1, 0, 0, 0, 40] );
+        assert_eq!( &serialized[ 2..9 ], &vec![ 10_u8, 20, 30 ].write_to_vec( Endianness::LittleEndian ).unwrap()[..] );
+
+        let buffer = Bytes::from( serialized );
+        let deserialized = DerivedPacket::read_from_bytes( Endianness::LittleEndian, &buffer ).unwrap();
+        assert_eq!( deserialized, original );
+        assert_eq!( deserialized.payload.as_ptr(), buffer[ 6.. ].as_ptr() );
+
+        let deserialized = DerivedPacket::read_from_buffer( Endianness::LittleEndian, &buffer ).unwrap();
+        assert_eq!( deserialized, original );
+        assert_ne!( deserialized.payload.as_ptr(), buffer[ 6.. ].as_ptr() );
+
+        let deserialized = DerivedPacket::read_from_stream( Endianness::LittleEndian, &buffer[..] ).unwrap();
+        assert_eq!( deserialized, original );
+    }
+
+    #[test]
+    fn test_bytes_with_a_bogus_length_in_a_stream() {
+        use std::io;
+
+        // Almost 2GB are claimed but never arrive; this must fail instead of allocating them up front.
+        let input = [0xf0, 0xff, 0xff, 0x7f, 1, 2];
+        let error = Bytes::read_from_stream( Endianness::LittleEndian, &input[..] ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+        let error = BytesMut::read_from_stream( Endianness::LittleEndian, &input[..] ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod async_tests {
+    extern crate tokio;
+
+    use std::io::{self, Cursor};
+    use std::pin::Pin;
+    use std::future::Future;
+    use std::task::{Context, Poll};
+    use self::tokio::io::{AsyncRead, ReadBuf};
+    use speedy::{Writable, Endianness, AsyncReadable};
+    use super::DerivedStruct;
+
+    #[derive(PartialEq, Debug, Readable, Writable)]
+    struct DerivedBatch {
+        name: String,
+        items: Vec< DerivedStruct >
+    }
+
+    fn block_on< F: Future >( future: F ) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on( future )
+    }
+
+    #[test]
+    fn test_read_derived_struct_from_async_cursor() {
+        let original = DerivedStruct { a: 1, b: 2, c: 3 };
+        let mut serialized = Vec::new();
+        original.write_framed_to_stream( Endianness::LittleEndian, &mut serialized ).unwrap();
+
+        let mut cursor = Cursor::new( serialized );
+        let deserialized = block_on( DerivedStruct::read_from_async( Endianness::LittleEndian, &mut cursor ) ).unwrap();
+        assert_eq!( deserialized, original );
+        assert_eq!( cursor.position(), 11 );
+
+        let cursor = Cursor::new( &cursor.get_ref()[ ..10 ] );
+        let error = block_on( DerivedStruct::read_from_async( Endianness::LittleEndian, cursor ) ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+        // The frame's length isn't trusted, so this fails without allocating it first.
+        let cursor = Cursor::new( vec![ 0xf0, 0xff, 0xff, 0x7f, 1, 2 ] );
+        let error = block_on( DerivedStruct::read_from_async( Endianness::LittleEndian, cursor ) ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+    }
+
+    // Returns a single byte at a time, and only every other time it's polled.
+    struct Trickle< 'a > {
+        data: &'a [u8],
+        is_ready: bool
+    }
+
+    impl< 'a > AsyncRead for Trickle< 'a > {
+        fn poll_read( mut self: Pin< &mut Self >, cx: &mut Context, output: &mut ReadBuf ) -> Poll< io::Result< () > > {
+            self.is_ready = !self.is_ready;
+            if !self.is_ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            if let Some( (&byte, rest) ) = self.data.split_first() {
+                output.put_slice( &[byte] );
+                self.data = rest;
+            }
+
+            Poll::Ready( Ok(()) )
+        }
+    }
+
+    #[test]
+    fn test_read_derived_struct_from_async_trickle() {
+        let original = DerivedBatch {
+            name: "batch".to_owned(),
+            items: vec![ DerivedStruct { a: 1, b: 2, c: 3 }, DerivedStruct { a: 4, b: 5, c: 6 } ]
+        };
+
+        let mut serialized = Vec::new();
+        original.write_framed_to_stream( Endianness::BigEndian, &mut serialized ).unwrap();
+        serialized.push( 0xff );
+
+        let mut reader = Trickle { data: &serialized, is_ready: false };
+        let deserialized = block_on( DerivedBatch::read_from_async( Endianness::BigEndian, &mut reader ) ).unwrap();
+        assert_eq!( deserialized, original );
+        assert_eq!( reader.data, [0xff] );
+    }
+}
+
+#[cfg(feature = "tokio-util")]
+mod codec_tests {
+    extern crate bytes;
+    extern crate tokio_util;
+
+    use self::bytes::BytesMut;
+    use self::tokio_util::codec::{Decoder, Encoder};
+    use speedy::{Writable, Endianness, SpeedyCodec};
+    use super::DerivedStruct;
+
+    #[test]
+    fn test_codec_with_a_frame_split_across_chunks() {
+        let original = DerivedStruct { a: 1, b: 2, c: 3 };
+        let mut codec: SpeedyCodec< _, DerivedStruct > = SpeedyCodec::new( Endianness::LittleEndian );
+
+        let mut encoded = BytesMut::new();
+        codec.encode( DerivedStruct { a: 1, b: 2, c: 3 }, &mut encoded ).unwrap();
+        assert_eq!( &encoded[..], &[7, 0, 0, 0, 1, 2, 0, 3, 0, 0, 0][..] );
+
+        let mut framed = Vec::new();
+        original.write_framed_to_stream( Endianness::LittleEndian, &mut framed ).unwrap();
+        assert_eq!( &encoded[..], &framed[..] );
+
+        let mut buffer = BytesMut::from( &framed[ ..6 ] );
+        assert!( codec.decode( &mut buffer ).unwrap().is_none() );
+        assert_eq!( buffer.len(), 6 );
+
+        buffer.extend_from_slice( &framed[ 6.. ] );
+        assert_eq!( codec.decode( &mut buffer ).unwrap(), Some( original ) );
+        assert!( buffer.is_empty() );
+
+        let mut buffer = BytesMut::from( &[framed.clone(), framed][..].concat()[..] );
+        assert!( codec.decode( &mut buffer ).unwrap().is_some() );
+        assert!( codec.decode( &mut buffer ).unwrap().is_some() );
+        assert!( codec.decode( &mut buffer ).unwrap().is_none() );
+    }
+
+    #[test]
+    fn test_codec_rejects_frames_over_the_maximum_length() {
+        use std::io;
+
+        let mut codec: SpeedyCodec< _, DerivedStruct > = SpeedyCodec::new( Endianness::LittleEndian );
+        let mut buffer = BytesMut::from( &[0xf0, 0xff, 0xff, 0xff, 1, 2][..] );
+        let error = codec.decode( &mut buffer ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+        assert!( buffer.capacity() < 1024 );
+
+        codec.set_max_frame_length( 6 );
+        assert_eq!( codec.max_frame_length(), 6 );
+        let mut buffer = BytesMut::from( &[7, 0, 0, 0, 1, 2, 0, 3, 0, 0, 0][..] );
+        let error = codec.decode( &mut buffer ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    }
+}
+
+#[cfg(feature = "derive")]
+mod prelude_tests {
+    use speedy::prelude::*;
+
+    #[derive(PartialEq, Debug, Readable, Writable)]
+    struct Point {
+        x: i16,
+        y: i16
+    }
+
+    #[test]
+    fn test_derive_through_the_prelude() {
+        let original = Point { x: 1, y: -1 };
+        let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+        assert_eq!( serialized, [1, 0, 255, 255] );
+        assert_eq!( Point::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), original );
+    }
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedExtra {
+    a: u8,
+    b: u16
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithFlattenedOption {
+    id: u8,
+    #[speedy(flatten)]
+    extra: Option< DerivedExtra >,
+    tail: u8
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithFlattenedNonZero {
+    id: u8,
+    #[speedy(flatten)]
+    extra: Option< std::num::NonZeroU32 >,
+    tail: u8
+}
+
+#[test]
+fn test_derived_struct_with_flattened_option() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let present = DerivedStructWithFlattenedOption { id: 1, extra: Some( DerivedExtra { a: 2, b: 3 } ), tail: 4 };
+    let serialized = present.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 1, 2, 3, 0, 4] );
+    assert_eq!( DerivedStructWithFlattenedOption::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), present );
+
+    let absent = DerivedStructWithFlattenedOption { id: 1, extra: None, tail: 4 };
+    let serialized = absent.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 4] );
+    assert_eq!( DerivedStructWithFlattenedOption::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), absent );
+
+    // A `NonZero*` has no presence byte; zero means `None`.
+    let present = DerivedStructWithFlattenedNonZero { id: 1, extra: std::num::NonZeroU32::new( 2 ), tail: 4 };
+    let serialized = present.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 2, 0, 0, 0, 4] );
+    assert_eq!( DerivedStructWithFlattenedNonZero::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), present );
+
+    let absent = DerivedStructWithFlattenedNonZero { id: 1, extra: None, tail: 4 };
+    let serialized = absent.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 0, 0, 0, 4] );
+    assert_eq!( DerivedStructWithFlattenedNonZero::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), absent );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithFixedCount {
+    #[speedy(fixed_count = 32)]
+    hash: Vec< u8 >,
+    #[speedy(fixed_count = 2)]
+    pair: Vec< u16 >
+}
+
+#[test]
+fn test_derived_struct_with_fixed_count() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithFixedCount { hash: (0..32).collect(), pair: vec![ 1, 2 ] };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized.len(), 32 + 4 );
+    assert_eq!( &serialized[ ..32 ], &original.hash[..] );
+    assert_eq!( &serialized[ 32.. ], &[1, 0, 2, 0] );
+    assert_eq!( <DerivedStructWithFixedCount as Readable< Endianness >>::minimum_bytes_needed(), 32 + 4 );
+
+    let deserialized = DerivedStructWithFixedCount::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let invalid = DerivedStructWithFixedCount { hash: vec![ 0; 31 ], pair: vec![ 1, 2 ] };
+    let error = invalid.write_to_vec( Endianness::LittleEndian ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidInput );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+struct DerivedStructWithRemainingTail {
+    header: u32,
+    #[speedy(remaining)]
+    tail: Vec< u16 >
+}
+
+#[test]
+fn test_derived_struct_with_remaining_tail() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStructWithRemainingTail { header: 7, tail: vec![ 1, 2, 3 ] };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [7, 0, 0, 0, 1, 0, 2, 0, 3, 0] );
+    assert_eq!( <DerivedStructWithRemainingTail as Readable< Endianness >>::minimum_bytes_needed(), 4 );
+
+    let deserialized = DerivedStructWithRemainingTail::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let deserialized = DerivedStructWithRemainingTail::read_from_stream_buffered( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let deserialized = DerivedStructWithRemainingTail::read_from_buffer( Endianness::LittleEndian, &serialized[ ..4 ] ).unwrap();
+    assert_eq!( deserialized, DerivedStructWithRemainingTail { header: 7, tail: Vec::new() } );
+
+    let error = DerivedStructWithRemainingTail::read_from_buffer( Endianness::LittleEndian, &serialized[ ..9 ] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+}
+
+#[test]
+fn test_derived_struct_read_from_split_buffers() {
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedStruct { a: 1, b: 2, c: 3 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    for split in 0..serialized.len() + 1 {
+        let (header, data) = serialized.split_at( split );
+        let deserialized = DerivedStruct::read_from_buffers( Endianness::LittleEndian, &[header, data] ).unwrap();
+        assert_eq!( deserialized, original );
+    }
+
+    let (header, data) = serialized.split_at( 2 );
+    let error = DerivedStruct::read_from_buffers( Endianness::LittleEndian, &[header, &data[ ..data.len() - 1 ]] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::UnexpectedEof );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(tag_type = "u8", length_prefixed_variants)]
+enum DerivedEventV1 {
+    Click { x: u16, y: u16 },
+    Close
+}
+
+// A newer version of `DerivedEventV1` with an extra field.
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(tag_type = "u8", length_prefixed_variants)]
+enum DerivedEventV2 {
+    Click { x: u16, y: u16, button: u8 },
+    Close
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(length_prefixed_variants)]
+enum DerivedOpaqueEvent {
+    Raw( speedy::RawBytes )
+}
+
+#[test]
+fn test_derived_enum_with_length_prefixed_variants() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let events = vec![ DerivedEventV2::Click { x: 1, y: 2, button: 3 }, DerivedEventV2::Close ];
+    let serialized = events.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [
+        2, 0, 0, 0,
+        0, 5, 0, 0, 0, 1, 0, 2, 0, 3,
+        1, 0, 0, 0, 0
+    ]);
+    assert_eq!( Writable::< Endianness >::bytes_needed( &events ), serialized.len() );
+    assert_eq!( <DerivedEventV1 as Readable< Endianness >>::minimum_bytes_needed(), 1 + 4 );
+
+    let deserialized: Vec< DerivedEventV2 > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, events );
+
+    // The extra field is skipped, and the value after it is still read correctly.
+    let expected = vec![ DerivedEventV1::Click { x: 1, y: 2 }, DerivedEventV1::Close ];
+    let deserialized: Vec< DerivedEventV1 > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, expected );
+
+    let deserialized: Vec< DerivedEventV1 > = Readable::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    assert_eq!( deserialized, expected );
+
+    let old = DerivedEventV1::Click { x: 1, y: 2 }.write_to_vec( Endianness::LittleEndian ).unwrap();
+    let error = DerivedEventV2::read_from_buffer( Endianness::LittleEndian, &old ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+    // The rest of a variant's frame is read as it comes in, so a bogus length can't allocate it up front.
+    let original = DerivedOpaqueEvent::Raw( speedy::RawBytes( vec![ 1, 2 ] ) );
+    let serialized = original.write_to_vec( Endianness::BigEndian ).unwrap();
+    assert_eq!( serialized, [0, 0, 0, 0, 0, 0, 0, 2, 1, 2] );
+    assert_eq!( DerivedOpaqueEvent::read_from_stream( Endianness::BigEndian, &serialized[..] ).unwrap(), original );
+
+    let error = DerivedOpaqueEvent::read_from_stream( Endianness::BigEndian, &[0, 0, 0, 0, 0x7f, 0xff, 0xff, 0xf0, 1, 2][..] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(length_prefixed_variants)]
+enum DerivedTupleEventV1 {
+    Key( u32 )
+}
+
+// A newer version of `DerivedTupleEventV1` with an extra field.
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(length_prefixed_variants)]
+enum DerivedTupleEventV2 {
+    Key( u32, String )
+}
+
+#[test]
+fn test_derived_tuple_enum_with_length_prefixed_variants() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let serialized = DerivedTupleEventV2::Key( 7, "a".to_owned() ).write_to_vec( Endianness::BigEndian ).unwrap();
+    assert_eq!( serialized, [0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 7, 0, 0, 0, 1, b'a'] );
+    assert_eq!( DerivedTupleEventV1::read_from_buffer( Endianness::BigEndian, &serialized ).unwrap(), DerivedTupleEventV1::Key( 7 ) );
+    assert_eq!( DerivedTupleEventV1::read_from_stream( Endianness::BigEndian, &serialized[..] ).unwrap(), DerivedTupleEventV1::Key( 7 ) );
+
+    // A frame which is too short for the variant is an error even if there's more input after it.
+    let error = DerivedTupleEventV1::read_from_buffer( Endianness::BigEndian, &[0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 7] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+}
+
+type Pair = (u32, u32);
+
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
+struct DerivedStructWithTupleAlias {
+    p: Pair,
+    rest: Vec< (u8, Pair) >
+}
+
+#[test]
+fn test_derived_struct_with_tuple_alias() {
+    use speedy::{Readable, Writable, Endianness, Schema, SchemaNode};
+
+    let original = DerivedStructWithTupleAlias { p: (1, 2), rest: vec![ (3, (4, 5)) ] };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [
+        1, 0, 0, 0, 2, 0, 0, 0,
+        1, 0, 0, 0,
+        3, 4, 0, 0, 0, 5, 0, 0, 0
+    ]);
+    assert_eq!( <DerivedStructWithTupleAlias as Readable< Endianness >>::minimum_bytes_needed(), 8 + 4 );
+
+    let deserialized = DerivedStructWithTupleAlias::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    match DerivedStructWithTupleAlias::schema() {
+        SchemaNode::Struct { fields, .. } => {
+            assert_eq!( fields[ 0 ].ty, "Pair" );
+            assert_eq!( fields[ 0 ].schema, <(u32, u32)>::schema() );
+        },
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
+
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
+struct DerivedStructWithForeignEndianField {
+    native: u32,
+    foreign: speedy::endian::U32Be,
+    other: speedy::endian::I16Le
+}
+
+#[test]
+fn test_derived_struct_with_foreign_endian_field() {
+    use speedy::{Readable, Writable, Endianness, Schema, SchemaNode};
+    use speedy::endian::{U32Be, I16Le};
+
+    let original = DerivedStructWithForeignEndianField { native: 0x01020304, foreign: U32Be( 0x01020304 ), other: I16Le( -2 ) };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [4, 3, 2, 1, 1, 2, 3, 4, 0xfe, 0xff] );
+    assert_eq!( DerivedStructWithForeignEndianField::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), original );
+
+    let serialized = original.write_to_vec( Endianness::BigEndian ).unwrap();
+    assert_eq!( serialized, [1, 2, 3, 4, 1, 2, 3, 4, 0xfe, 0xff] );
+    assert_eq!( DerivedStructWithForeignEndianField::read_from_buffer( Endianness::BigEndian, &serialized ).unwrap(), original );
+
+    match DerivedStructWithForeignEndianField::schema() {
+        SchemaNode::Struct { fields, .. } => assert_eq!( fields[ 1 ].schema, SchemaNode::Primitive( "u32be" ) ),
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
+
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
+#[speedy(tag_type = "u8", tag_from = "command_from_wire_code", tag_to = "command_to_wire_code")]
+enum DerivedCommand {
+    Reset,
+    Move { x: i16, y: i16 },
+    Stop
+}
+
+// The external codes of the variants in the order in which they're declared.
+const COMMAND_WIRE_CODES: [u8; 3] = [3, 7, 1];
+
+fn command_from_wire_code( code: u8 ) -> Option< u8 > {
+    COMMAND_WIRE_CODES.iter().position( |&wire_code| wire_code == code ).map( |index| index as u8 )
+}
+
+fn command_to_wire_code( kind: u8 ) -> u8 {
+    COMMAND_WIRE_CODES[ kind as usize ]
+}
+
+#[test]
+fn test_derived_enum_with_mapped_tags() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness, Schema, SchemaNode};
+
+    let original = DerivedCommand::Move { x: 1, y: -1 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [7, 1, 0, 0xff, 0xff] );
+    assert_eq!( DerivedCommand::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), original );
+
+    assert_eq!( DerivedCommand::Reset.write_to_vec( Endianness::LittleEndian ).unwrap(), [3] );
+    assert_eq!( DerivedCommand::read_from_buffer( Endianness::LittleEndian, &[1] ).unwrap(), DerivedCommand::Stop );
+
+    let error = DerivedCommand::read_from_buffer( Endianness::LittleEndian, &[0] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    match DerivedCommand::schema() {
+        SchemaNode::Enum { variants, .. } => {
+            assert_eq!( variants.iter().map( |variant| variant.tag ).collect::< Vec< _ > >(), vec![ 3, 7, 1 ] );
+        },
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
+
+// Version 1 had `id` and `flags`, version 2 added `name` and version 3 replaced `flags` with `priority`.
+#[derive(PartialEq, Debug, Default, Readable, Writable, Schema)]
+#[speedy(multi_version)]
+struct DerivedVersionedRecord {
+    id: u32,
+    #[speedy(until = 3)]
+    flags: u8,
+    #[speedy(since = 2)]
+    name: String,
+    #[speedy(since = 3, default_value = 5)]
+    priority: u16
+}
+
+#[test]
+fn test_derived_multi_version_struct() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness, Schema, SchemaNode};
+
+    let v1 = [1, 10, 0, 0, 0, 0xff];
+    let deserialized = DerivedVersionedRecord::read_from_buffer( Endianness::LittleEndian, &v1 ).unwrap();
+    assert_eq!( deserialized, DerivedVersionedRecord { id: 10, flags: 0xff, name: String::new(), priority: 5 } );
+
+    let v3 = [3, 20, 0, 0, 0, 1, 0, 0, 0, b'x', 2, 0];
+    let expected = DerivedVersionedRecord { id: 20, flags: 0, name: "x".to_owned(), priority: 2 };
+    assert_eq!( DerivedVersionedRecord::read_from_buffer( Endianness::LittleEndian, &v3 ).unwrap(), expected );
+
+    let mut in_place = DerivedVersionedRecord { flags: 1, ..DerivedVersionedRecord::default() };
+    in_place.read_into( Endianness::LittleEndian, &v3 ).unwrap();
+    assert_eq!( in_place, expected );
+
+    // The latest version is always written.
+    let serialized = deserialized.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [3, 10, 0, 0, 0, 0, 0, 0, 0, 5, 0] );
+    assert_eq!( <DerivedVersionedRecord as Readable< Endianness >>::minimum_bytes_needed(), 1 + 4 );
+    assert_eq!( <DerivedVersionedRecord as Writable< Endianness >>::max_bytes_len(), None );
+
+    for version in &[0, 4] {
+        let error = DerivedVersionedRecord::read_from_buffer( Endianness::LittleEndian, &[*version, 0, 0, 0, 0] ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    }
+
+    match DerivedVersionedRecord::schema() {
+        SchemaNode::Struct { version, fields, .. } => {
+            assert_eq!( version, Some( 3 ) );
+            assert_eq!( fields.iter().map( |field| (field.since, field.until) ).collect::< Vec< _ > >(), vec![ (None, None), (None, Some( 3 )), (Some( 2 ), None), (Some( 3 ), None) ] );
+        },
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
+
+#[test]
+fn test_derived_enum_with_borrowed_fields() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness};
+
+    let original = DerivedEnumWithBorrowedFields::Text( "hello" );
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0, 0, 0, 0, 5, 0, 0, 0, b'h', b'e', b'l', b'l', b'o'] );
+
+    let deserialized = DerivedEnumWithBorrowedFields::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+    match deserialized {
+        DerivedEnumWithBorrowedFields::Text( text ) => assert_eq!( text.as_ptr(), serialized[ 8.. ].as_ptr() ),
+        _ => unreachable!()
+    }
+
+    let original = DerivedEnumWithBorrowedFields::Blob { bytes: &[1, 2, 3] };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    let deserialized = DerivedEnumWithBorrowedFields::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let error = DerivedEnumWithBorrowedFields::read_from_buffer( Endianness::LittleEndian, &[0, 0, 0, 0, 1, 0, 0, 0, 0xff] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    let error = DerivedEnumWithBorrowedFields::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidInput );
+}
+
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
+#[speedy(end_marker = 0xFF)]
+struct DerivedStructWithEndMarker {
+    id: u16,
+    name: String
+}
+
+#[derive(PartialEq, Debug, Readable, Writable)]
+#[speedy(end_marker = 0xCAFE_u16)]
+struct DerivedTupleStructWithWideEndMarker( u8 );
+
+#[test]
+fn test_derived_struct_with_end_marker() {
+    use std::io;
+    use speedy::{Readable, Writable, Endianness, Schema, SchemaNode};
+
+    let original = DerivedStructWithEndMarker { id: 1, name: "x".to_owned() };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 1, 0, 0, 0, b'x', 0xFF] );
+    assert_eq!( DerivedStructWithEndMarker::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), original );
+    assert_eq!( <DerivedStructWithEndMarker as Readable< Endianness >>::minimum_bytes_needed(), 2 + 4 + 1 );
+
+    let mut in_place = DerivedStructWithEndMarker { id: 0, name: String::new() };
+    in_place.read_into( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( in_place, original );
+
+    let error = DerivedStructWithEndMarker::read_from_buffer( Endianness::LittleEndian, &serialized[ ..serialized.len() - 1 ] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+    let error = DerivedStructWithEndMarker::read_from_buffer( Endianness::LittleEndian, &[1, 0, 1, 0, 0, 0, b'x', 0xFE] ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    let original = DerivedTupleStructWithWideEndMarker( 7 );
+    let serialized = original.write_to_vec( Endianness::BigEndian ).unwrap();
+    assert_eq!( serialized, [7, 0xCA, 0xFE] );
+    assert_eq!( DerivedTupleStructWithWideEndMarker::read_from_buffer( Endianness::BigEndian, &serialized ).unwrap(), original );
+    assert_eq!( <DerivedTupleStructWithWideEndMarker as Writable< Endianness >>::max_bytes_len(), Some( 3 ) );
+
+    match DerivedStructWithEndMarker::schema() {
+        SchemaNode::Struct { end_marker, .. } => assert_eq!( end_marker, Some( ("u8", 0xFF) ) ),
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
+
+#[derive(PartialEq, Debug, Readable, Writable, Schema)]
+struct DerivedStructWithReservedBytes {
+    a: u8,
+    #[speedy(reserved = 3)]
+    b: u32,
+    #[speedy(reserved = 2, skip)]
+    c: u8
+}
+
+#[test]
+fn test_derived_struct_with_reserved_bytes() {
+    use speedy::{Readable, Writable, Endianness, Schema, SchemaNode};
+
+    let original = DerivedStructWithReservedBytes { a: 1, b: 0x0403_0201, c: 0 };
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [1, 0, 0, 0, 1, 2, 3, 4, 0, 0] );
+    assert_eq!( DerivedStructWithReservedBytes::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap(), original );
+    assert_eq!( <DerivedStructWithReservedBytes as Readable< Endianness >>::minimum_bytes_needed(), 10 );
+    assert_eq!( <DerivedStructWithReservedBytes as Writable< Endianness >>::max_bytes_len(), Some( 10 ) );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &original ), 10 );
+
+    // The contents of the reserved bytes are ignored.
+    let deserialized = DerivedStructWithReservedBytes::read_from_buffer( Endianness::LittleEndian, &[1, 0xAA, 0xBB, 0xCC, 1, 2, 3, 4, 0xDD, 0xEE] ).unwrap();
+    assert_eq!( deserialized, original );
+    let deserialized = DerivedStructWithReservedBytes::read_from_stream( Endianness::LittleEndian, &[1, 0xAA, 0xBB, 0xCC, 1, 2, 3, 4, 0xDD, 0xEE][..] ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let mut in_place = DerivedStructWithReservedBytes { a: 0, b: 0, c: 5 };
+    in_place.read_into( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( in_place, original );
+
+    match DerivedStructWithReservedBytes::schema() {
+        SchemaNode::Struct { fields, .. } => {
+            let kinds: Vec< _ > = fields.iter().map( |field| (field.name, field.schema.clone()) ).collect();
+            assert_eq!( kinds, vec![
+                ("a", SchemaNode::Primitive( "u8" )),
+                ("", SchemaNode::Reserved( 3 )),
+                ("b", SchemaNode::Primitive( "u32" )),
+                ("", SchemaNode::Reserved( 2 ))
+            ]);
+        },
+        schema => panic!( "unexpected schema: {:?}", schema )
+    }
+}
@@ -2,7 +2,7 @@ extern crate speedy;
 
 use std::borrow::Cow;
 #[allow(unused_imports)]
-use speedy::{Readable, Writable, Endianness};
+use speedy::{Readable, Writable, Endianness, SerializableInstant, BufferReader, Reader};
 
 macro_rules! symmetric_tests {
     ($(
@@ -62,7 +62,7 @@ symmetric_tests! {
             11
         ]
     }
-    cow_u8 for Cow< 'static, [u8] > {
+    cow_u8 for Cow< [u8] > {
         in = Cow::Owned( vec![ 10, 11 ] ),
         le = [
             2, 0, 0, 0,
@@ -88,7 +88,7 @@ symmetric_tests! {
             0, 11
         ]
     }
-    cow_u16 for Cow< 'static, [u16] > {
+    cow_u16 for Cow< [u16] > {
         in = Cow::Owned( vec![ 10, 11 ] ),
         le = [
             2, 0, 0, 0,
@@ -114,7 +114,7 @@ symmetric_tests! {
             0, 0, 0, 11
         ]
     }
-    cow_u32 for Cow< 'static, [u32] > {
+    cow_u32 for Cow< [u32] > {
         in = Cow::Owned( vec![ 10, 11 ] ),
         le = [
             2, 0, 0, 0,
@@ -140,7 +140,7 @@ symmetric_tests! {
             0, 0, 0, 0, 0, 0, 0, 11
         ]
     }
-    cow_u64 for Cow< 'static, [u64] > {
+    cow_u64 for Cow< [u64] > {
         in = Cow::Owned( vec![ 10, 11 ] ),
         le = [
             2, 0, 0, 0,
@@ -208,4 +208,1127 @@ symmetric_tests! {
         le = [5, 0, 0, 0, 72, 101, 108, 108, 111],
         be = [0, 0, 0, 5, 72, 101, 108, 108, 111]
     }
+    tuple for (u8, u16, String) {
+        in = (1, 2, "x".to_owned()),
+        le = [1, 2, 0, 1, 0, 0, 0, b'x'],
+        be = [1, 0, 2, 0, 0, 0, 1, b'x']
+    }
+    serializable_instant for SerializableInstant {
+        in = SerializableInstant::from_nanos( 0x0102030405060708 ),
+        le = [8, 7, 6, 5, 4, 3, 2, 1],
+        be = [1, 2, 3, 4, 5, 6, 7, 8]
+    }
+    duration for std::time::Duration {
+        in = std::time::Duration::new( 1, 2 ),
+        le = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0],
+        be = [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2]
+    }
+    option_none for Option< u16 > {
+        in = None,
+        le = [0],
+        be = [0]
+    }
+    option_some for Option< u16 > {
+        in = Some( 3 ),
+        le = [1, 3, 0],
+        be = [1, 0, 3]
+    }
+    option_string_none for Option< String > {
+        in = None,
+        le = [0],
+        be = [0]
+    }
+    option_empty_string_some for Option< String > {
+        in = Some( String::new() ),
+        le = [1, 0, 0, 0, 0],
+        be = [1, 0, 0, 0, 0]
+    }
+    option_non_zero_u32_none for Option< std::num::NonZeroU32 > {
+        in = None,
+        le = [0, 0, 0, 0],
+        be = [0, 0, 0, 0]
+    }
+    option_non_zero_u32_some for Option< std::num::NonZeroU32 > {
+        in = std::num::NonZeroU32::new( 0x01020304 ),
+        le = [4, 3, 2, 1],
+        be = [1, 2, 3, 4]
+    }
+    option_non_zero_i8_some for Option< std::num::NonZeroI8 > {
+        in = std::num::NonZeroI8::new( -1 ),
+        le = [255],
+        be = [255]
+    }
+    result_ok for Result< u16, u8 > {
+        in = Ok( 3 ),
+        le = [0, 3, 0],
+        be = [0, 0, 3]
+    }
+    result_err for Result< u16, u8 > {
+        in = Err( 4 ),
+        le = [1, 4],
+        be = [1, 4]
+    }
+}
+
+#[test]
+fn serializable_instant_round_trip_now() {
+    use std::time::{Duration, Instant};
+
+    let instant = Instant::now() + Duration::from_millis( 1500 );
+    let original = SerializableInstant::from_instant( instant );
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    let deserialized: SerializableInstant = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( original, deserialized );
+    assert_eq!( deserialized.to_instant(), instant );
+}
+
+#[test]
+fn binary_heap_round_trip() {
+    use std::collections::BinaryHeap;
+
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let original: BinaryHeap< u32 > = vec![ 5, 1, 8, 3, 8, 2 ].into_iter().collect();
+        let serialized = original.write_to_vec( endianness ).unwrap();
+        assert_eq!( serialized.len(), 4 + 6 * 4 );
+
+        let deserialized: BinaryHeap< u32 > = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+        assert_eq!( deserialized.into_sorted_vec(), vec![ 1, 2, 3, 5, 8, 8 ] );
+    }
+}
+
+#[test]
+fn cow_u8_borrows_from_buffer() {
+    let serialized = vec![ 3, 0, 0, 0, 10, 11, 12 ];
+
+    let deserialized: Cow< [u8] > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    match deserialized {
+        Cow::Borrowed( slice ) => {
+            assert_eq!( slice, &[ 10, 11, 12 ] );
+            assert_eq!( slice.as_ptr(), serialized[ 4.. ].as_ptr() );
+        },
+        Cow::Owned( _ ) => panic!( "expected a borrowed slice" )
+    }
+
+    let deserialized: Cow< [u8] > = Readable::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    match deserialized {
+        Cow::Owned( vec ) => assert_eq!( vec, [ 10, 11, 12 ] ),
+        Cow::Borrowed( _ ) => panic!( "expected an owned vector" )
+    }
+}
+
+#[test]
+fn cow_borrows_only_if_the_reader_can_borrow() {
+    let serialized = vec![ 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0 ];
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &serialized );
+    assert!( reader.can_borrow() );
+    assert!( matches!( reader.read_cow_bytes( 4 ).unwrap(), Cow::Borrowed( _ ) ) );
+
+    for &buffered in &[false, true] {
+        let read = |data: &[u8]| -> Cow< 'static, [u8] > {
+            if buffered {
+                Readable::read_from_stream_buffered( Endianness::LittleEndian, data ).unwrap()
+            } else {
+                Readable::read_from_stream( Endianness::LittleEndian, data ).unwrap()
+            }
+        };
+
+        match read( &serialized ) {
+            Cow::Owned( vec ) => assert_eq!( vec, [ 1, 0 ] ),
+            Cow::Borrowed( _ ) => panic!( "expected an owned vector" )
+        }
+    }
+
+    let deserialized: Cow< [u32] > = Readable::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    match deserialized {
+        Cow::Owned( vec ) => assert_eq!( vec, [ 1, 2 ] ),
+        Cow::Borrowed( _ ) => panic!( "expected an owned vector" )
+    }
+
+    let error = <&[u8]>::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidInput );
+}
+
+#[test]
+fn empty_and_absent_optional_strings_are_distinct() {
+    let original = vec![ None, Some( String::new() ), None, Some( "a".to_owned() ), Some( String::new() ) ];
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [
+        5, 0, 0, 0,
+        0,
+        1, 0, 0, 0, 0,
+        0,
+        1, 1, 0, 0, 0, b'a',
+        1, 0, 0, 0, 0
+    ]);
+    assert_eq!( Writable::< Endianness >::bytes_needed( &original ), serialized.len() );
+
+    let deserialized: Vec< Option< String > > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let deserialized: Vec< Option< String > > = Readable::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    assert_eq!( deserialized, original );
+}
+
+#[test]
+fn borrowed_optional_slice() {
+    let some: Option< &[u8] > = Some( &[ 1, 2, 3 ] );
+    let serialized = some.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [ 1, 3, 0, 0, 0, 1, 2, 3 ] );
+
+    let deserialized: Option< &[u8] > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    let slice = deserialized.unwrap();
+    assert_eq!( slice, &[ 1, 2, 3 ] );
+    assert_eq!( slice.as_ptr(), serialized[ 5.. ].as_ptr() );
+
+    let none: Option< &[u8] > = None;
+    let serialized = none.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [ 0 ] );
+
+    let deserialized: Option< &[u8] > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( deserialized, None );
+
+    let error = <Option< &[u8] >>::read_from_stream( Endianness::LittleEndian, &[ 1, 1, 0, 0, 0, 1 ][..] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidInput );
+
+    let error = <Option< &[u8] >>::read_from_buffer( Endianness::LittleEndian, &[ 2 ] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+}
+
+#[cfg(feature = "smallvec")]
+mod smallvec_tests {
+    extern crate smallvec;
+
+    use self::smallvec::SmallVec;
+    use super::*;
+
+    #[test]
+    fn smallvec_matches_vec_encoding() {
+        for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+            let vec: Vec< u16 > = vec![ 1, 2, 3 ];
+            let small: SmallVec< [u16; 16] > = SmallVec::from_slice( &vec );
+
+            let serialized = small.write_to_vec( endianness ).unwrap();
+            assert_eq!( serialized, vec.write_to_vec( endianness ).unwrap() );
+
+            let deserialized: SmallVec< [u16; 16] > = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+            assert_eq!( deserialized, small );
+            assert!( !deserialized.spilled() );
+        }
+    }
+
+    #[test]
+    fn smallvec_spills_long_data() {
+        let vec: Vec< u8 > = (0..32).collect();
+        let serialized = vec.write_to_vec( Endianness::LittleEndian ).unwrap();
+        let deserialized: SmallVec< [u8; 16] > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+        assert_eq!( &deserialized[..], &vec[..] );
+        assert!( deserialized.spilled() );
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+mod arrayvec_tests {
+    extern crate arrayvec;
+
+    use self::arrayvec::ArrayVec;
+    use super::*;
+
+    #[test]
+    fn arrayvec_matches_vec_encoding() {
+        for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+            let vec: Vec< u8 > = vec![ 1, 2, 3 ];
+            let serialized = vec.write_to_vec( endianness ).unwrap();
+
+            let deserialized: ArrayVec< u8, 4 > = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+            assert_eq!( &deserialized[..], &vec[..] );
+            assert_eq!( deserialized.write_to_vec( endianness ).unwrap(), serialized );
+        }
+    }
+
+    #[test]
+    fn arrayvec_rejects_data_which_does_not_fit() {
+        let vec: Vec< u8 > = vec![ 1, 2, 3, 4, 5 ];
+        let serialized = vec.write_to_vec( Endianness::LittleEndian ).unwrap();
+        let error = <ArrayVec< u8, 4 >>::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+    }
+}
+
+#[cfg(feature = "uuid")]
+mod uuid_tests {
+    extern crate uuid;
+
+    use self::uuid::Uuid;
+    use super::*;
+
+    #[test]
+    fn uuid_is_written_as_its_bytes() {
+        let uuid = Uuid::new_v4();
+        for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+            let serialized = uuid.write_to_vec( endianness ).unwrap();
+            assert_eq!( serialized, uuid.as_bytes() );
+
+            let deserialized: Uuid = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+            assert_eq!( deserialized, uuid );
+        }
+
+        assert_eq!( <Uuid as Readable< Endianness >>::minimum_bytes_needed(), 16 );
+    }
+}
+
+#[cfg(feature = "bincode_compat")]
+mod bincode_compat_tests {
+    use speedy::bincode_compat::read_bincode_from_buffer;
+
+    #[test]
+    fn reading_a_bincode_vec() {
+        // `bincode::serialize( &vec![ 1_u32, 2, 0xdeadbeef ] )`
+        let data = [
+            3, 0, 0, 0, 0, 0, 0, 0,
+            1, 0, 0, 0,
+            2, 0, 0, 0,
+            0xef, 0xbe, 0xad, 0xde
+        ];
+
+        let values: Vec< u32 > = read_bincode_from_buffer( &data ).unwrap();
+        assert_eq!( values, [1, 2, 0xdeadbeef] );
+
+        let error = read_bincode_from_buffer::< Vec< u32 > >( &data[ ..data.len() - 1 ] ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::UnexpectedEof );
+    }
+
+    #[test]
+    fn reading_bincode_strings_and_options() {
+        // `bincode::serialize( &vec![ Some( "ab".to_owned() ), None ] )`
+        let data = [
+            2, 0, 0, 0, 0, 0, 0, 0,
+            1, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b',
+            0
+        ];
+
+        let values: Vec< Option< String > > = read_bincode_from_buffer( &data ).unwrap();
+        assert_eq!( values, [Some( "ab".to_owned() ), None] );
+
+        let error = read_bincode_from_buffer::< Option< u8 > >( &[2, 0] ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+
+        let error = read_bincode_from_buffer::< String >( &[0, 0, 0, 0, 0, 0, 0, 0x7f, b'a'] ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::UnexpectedEof );
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_tests {
+    extern crate chrono;
+
+    use self::chrono::{DateTime, Utc, NaiveDate, NaiveDateTime, NaiveTime};
+    use super::*;
+
+    #[test]
+    fn date_time_round_trip() {
+        for &(secs, nanos) in &[(1_600_000_000, 123_456_789), (-1_000_000_000, 1), (0, 0)] {
+            let original = DateTime::from_timestamp( secs, nanos ).unwrap();
+            let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+            assert_eq!( &serialized[ ..8 ], &secs.to_le_bytes() );
+            assert_eq!( &serialized[ 8.. ], &nanos.to_le_bytes() );
+
+            let deserialized: DateTime< Utc > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+            assert_eq!( deserialized, original );
+        }
+    }
+
+    #[test]
+    fn naive_date_time_round_trip() {
+        let before_epoch = NaiveDate::from_ymd_opt( 1955, 11, 5 ).unwrap().and_hms_nano_opt( 6, 15, 0, 500 ).unwrap();
+        for &original in &[before_epoch, NaiveDateTime::default()] {
+            for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+                let serialized = original.write_to_vec( endianness ).unwrap();
+                assert_eq!( serialized, original.and_utc().write_to_vec( endianness ).unwrap() );
+
+                let deserialized: NaiveDateTime = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+                assert_eq!( deserialized, original );
+            }
+        }
+
+        let date = before_epoch.date();
+        let deserialized: NaiveDate = Readable::read_from_buffer( Endianness::LittleEndian, &date.write_to_vec( Endianness::LittleEndian ).unwrap() ).unwrap();
+        assert_eq!( deserialized, date );
+
+        let time = before_epoch.time();
+        let deserialized: NaiveTime = Readable::read_from_buffer( Endianness::LittleEndian, &time.write_to_vec( Endianness::LittleEndian ).unwrap() ).unwrap();
+        assert_eq!( deserialized, time );
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let mut serialized = i64::MAX.write_to_vec( Endianness::LittleEndian ).unwrap();
+        serialized.extend_from_slice( &[0; 4] );
+        let error = <DateTime< Utc >>::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+
+        let serialized = [0x80, 0x51, 0x01, 0x00, 0, 0, 0, 0];
+        let error = NaiveTime::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+
+        let error = NaiveDate::read_from_buffer( Endianness::LittleEndian, &i32::MAX.to_le_bytes() ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_tests {
+    extern crate rust_decimal;
+
+    use self::rust_decimal::Decimal;
+    use super::*;
+
+    #[test]
+    fn decimal_round_trip() {
+        let original = Decimal::new( 1234567, 4 );
+        assert_eq!( original.to_string(), "123.4567" );
+
+        let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+        assert_eq!( serialized, original.serialize() );
+
+        for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+            for &value in &[original, -original, Decimal::ZERO, Decimal::MAX, Decimal::MIN] {
+                let serialized = value.write_to_vec( endianness ).unwrap();
+                let deserialized: Decimal = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+                assert_eq!( deserialized, value );
+                assert_eq!( deserialized.scale(), value.scale() );
+            }
+        }
+    }
+
+    #[test]
+    fn decimal_with_maximum_scale_round_trip() {
+        let original = Decimal::from_i128_with_scale( -79_228_162_514_264_337_593_543_950_335, Decimal::MAX_SCALE );
+        let serialized = original.write_to_vec( Endianness::BigEndian ).unwrap();
+        let deserialized: Decimal = Readable::read_from_buffer( Endianness::BigEndian, &serialized ).unwrap();
+        assert_eq!( deserialized, original );
+        assert_eq!( deserialized.scale(), 28 );
+    }
+
+    #[test]
+    fn out_of_range_scale_is_rejected() {
+        let mut serialized = Decimal::ONE.serialize();
+        serialized[ 2 ] = 29;
+        let error = Decimal::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+
+        let mut serialized = Decimal::ONE.serialize();
+        serialized[ 0 ] = 1;
+        let error = Decimal::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap_err();
+        assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+    }
+}
+
+#[cfg(feature = "indexmap")]
+mod indexmap_tests {
+    extern crate indexmap;
+
+    use self::indexmap::{IndexMap, IndexSet};
+    use super::*;
+
+    #[test]
+    fn indexmap_preserves_insertion_order() {
+        let mut map: IndexMap< String, u16 > = IndexMap::new();
+        map.insert( "zebra".to_owned(), 1 );
+        map.insert( "apple".to_owned(), 2 );
+        map.insert( "mango".to_owned(), 3 );
+
+        let serialized = map.write_to_vec( Endianness::LittleEndian ).unwrap();
+        assert_eq!( serialized.len(), Writable::< Endianness >::bytes_needed( &map ) );
+        assert_eq!( &serialized[ ..4 ], &[3, 0, 0, 0] );
+        assert_eq!( &serialized[ 4..15 ], &[5, 0, 0, 0, b'z', b'e', b'b', b'r', b'a', 1, 0] );
+
+        let deserialized: IndexMap< String, u16 > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+        assert_eq!( deserialized.keys().collect::< Vec< _ > >(), vec![ "zebra", "apple", "mango" ] );
+        assert_eq!( deserialized, map );
+    }
+
+    #[test]
+    fn indexset_preserves_insertion_order() {
+        let set: IndexSet< u32 > = vec![ 30, 10, 20 ].into_iter().collect();
+        let serialized = set.write_to_vec( Endianness::LittleEndian ).unwrap();
+        assert_eq!( serialized, vec![ 30_u32, 10, 20 ].write_to_vec( Endianness::LittleEndian ).unwrap() );
+
+        let deserialized: IndexSet< u32 > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+        assert_eq!( deserialized.iter().cloned().collect::< Vec< _ > >(), vec![ 30, 10, 20 ] );
+    }
+}
+
+#[test]
+fn cow_str_is_written_like_a_string() {
+    let string = "Zażółć".to_owned();
+    let expected = string.write_to_vec( Endianness::LittleEndian ).unwrap();
+
+    let borrowed: Cow< str > = Cow::Borrowed( &string );
+    assert_eq!( borrowed.write_to_vec( Endianness::LittleEndian ).unwrap(), expected );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &borrowed ), expected.len() );
+
+    let owned: Cow< str > = Cow::Owned( string.clone() );
+    assert_eq!( owned.write_to_vec( Endianness::LittleEndian ).unwrap(), expected );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &owned ), expected.len() );
+
+    let deserialized: Cow< str > = Readable::read_from_buffer( Endianness::LittleEndian, &expected ).unwrap();
+    match deserialized {
+        Cow::Borrowed( value ) => assert_eq!( value, string ),
+        Cow::Owned( _ ) => panic!( "expected a borrowed string" )
+    }
+
+    let deserialized: Cow< str > = Readable::read_from_stream( Endianness::LittleEndian, &expected[..] ).unwrap();
+    assert_eq!( deserialized, string );
+
+    let error = <Cow< str >>::read_from_buffer( Endianness::LittleEndian, &[1, 0, 0, 0, 0xff] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+}
+
+#[test]
+fn cow_i8_borrows_from_buffer() {
+    let serialized = vec![ 2, 0, 0, 0, 0xff, 0x7f ];
+
+    let deserialized: Cow< [i8] > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    match deserialized {
+        Cow::Borrowed( slice ) => {
+            assert_eq!( slice, &[ -1, 127 ] );
+            assert_eq!( slice.as_ptr() as *const u8, serialized[ 4.. ].as_ptr() );
+        },
+        Cow::Owned( _ ) => panic!( "expected a borrowed slice" )
+    }
+
+    let deserialized: Cow< [i8] > = Readable::read_from_stream( Endianness::LittleEndian, &serialized[..] ).unwrap();
+    match deserialized {
+        Cow::Owned( vec ) => assert_eq!( vec, [ -1, 127 ] ),
+        Cow::Borrowed( _ ) => panic!( "expected an owned vector" )
+    }
+}
+
+#[test]
+fn cow_u32_borrows_under_native_endianness() {
+    let native = Endianness::default();
+    let foreign = if native == Endianness::LittleEndian { Endianness::BigEndian } else { Endianness::LittleEndian };
+
+    // Backed by an `u32` vector so that the data after the length prefix is properly aligned.
+    let storage: Vec< u32 > = vec![ 2, 10, 11 ];
+    let serialized = unsafe { std::slice::from_raw_parts( storage.as_ptr() as *const u8, storage.len() * 4 ) };
+
+    let deserialized: Cow< [u32] > = Readable::read_from_buffer( native, serialized ).unwrap();
+    match deserialized {
+        Cow::Borrowed( slice ) => {
+            assert_eq!( slice, &[ 10, 11 ] );
+            assert_eq!( slice.as_ptr(), storage[ 1.. ].as_ptr() );
+        },
+        Cow::Owned( _ ) => panic!( "expected a borrowed slice" )
+    }
+
+    let serialized = Cow::Borrowed( &[ 10_u32, 11 ][..] ).write_to_vec( foreign ).unwrap();
+    let deserialized: Cow< [u32] > = Readable::read_from_buffer( foreign, &serialized ).unwrap();
+    match deserialized {
+        Cow::Owned( vec ) => assert_eq!( vec, [ 10, 11 ] ),
+        Cow::Borrowed( _ ) => panic!( "expected an owned vector" )
+    }
+}
+
+#[test]
+fn fixed_size_arrays_round_trip_without_length_prefix() {
+    let mut original = [0_u8; 100];
+    for (index, byte) in original.iter_mut().enumerate() {
+        *byte = index as u8;
+    }
+
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( &serialized[..], &original[..] );
+    let deserialized: [u8; 100] = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    assert_eq!( &deserialized[..], &original[..] );
+
+    let original: [f64; 3] = [ 1.0, -2.5, 1024.125 ];
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let serialized = original.write_to_vec( endianness ).unwrap();
+        assert_eq!( serialized.len(), 3 * 8 );
+        let deserialized: [f64; 3] = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+        assert_eq!( deserialized, original );
+    }
+
+    assert_eq!( <[u8; 1024] as Readable< 'static, Endianness >>::minimum_bytes_needed(), 1024 );
+}
+
+#[test]
+fn max_bytes_len() {
+    use std::num::NonZeroU32;
+
+    assert_eq!( <u64 as Writable< Endianness >>::max_bytes_len(), Some( 8 ) );
+    assert_eq!( <Option< u32 > as Writable< Endianness >>::max_bytes_len(), Some( 5 ) );
+    assert_eq!( <Option< NonZeroU32 > as Writable< Endianness >>::max_bytes_len(), Some( 4 ) );
+    assert_eq!( <Result< u8, u32 > as Writable< Endianness >>::max_bytes_len(), Some( 5 ) );
+    assert_eq!( <[u16; 3] as Writable< Endianness >>::max_bytes_len(), Some( 6 ) );
+    assert_eq!( <(u8, bool, f64) as Writable< Endianness >>::max_bytes_len(), Some( 10 ) );
+    assert_eq!( <&SerializableInstant as Writable< Endianness >>::max_bytes_len(), Some( 8 ) );
+    assert_eq!( <Vec< u8 > as Writable< Endianness >>::max_bytes_len(), None );
+    assert_eq!( <String as Writable< Endianness >>::max_bytes_len(), None );
+    assert_eq!( <(u8, String) as Writable< Endianness >>::max_bytes_len(), None );
+    assert_eq!( <[u64; usize::MAX / 4] as Writable< Endianness >>::max_bytes_len(), None );
+}
+
+#[test]
+fn write_to_vec_with_capacity() {
+    let original = "Hello".to_owned();
+    let serialized = original.write_to_vec_with_capacity( Endianness::LittleEndian, 64 ).unwrap();
+    assert_eq!( serialized, original.write_to_vec( Endianness::LittleEndian ).unwrap() );
+    assert!( serialized.capacity() >= 64 );
+}
+
+#[cfg(target_endian = "little")]
+#[test]
+fn cow_u32_is_swapped_under_big_endian() {
+    // Properly aligned, so the only reason not to borrow is the endianness conversion.
+    let storage: Vec< u32 > = vec![ 2_u32.to_be(), 10_u32.to_be(), 256_u32.to_be() ];
+    let serialized = unsafe { std::slice::from_raw_parts( storage.as_ptr() as *const u8, storage.len() * 4 ) };
+    assert_eq!( &serialized[ 4.. ], &[ 0, 0, 0, 10, 0, 0, 1, 0 ] );
+
+    let deserialized: Cow< [u32] > = Readable::read_from_buffer( Endianness::BigEndian, serialized ).unwrap();
+    match deserialized {
+        Cow::Owned( vec ) => assert_eq!( vec, [ 10, 256 ] ),
+        Cow::Borrowed( _ ) => panic!( "expected an owned vector" )
+    }
+}
+
+#[test]
+fn read_bytes_into_slice() {
+    use std::io;
+
+    let serialized = vec![ 1_u8, 2, 3 ].write_to_vec( Endianness::LittleEndian ).unwrap();
+
+    let mut output = [0_u8; 8];
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &serialized );
+    let length = reader.read_bytes_into_slice( &mut output ).unwrap();
+    assert_eq!( &output[ ..length ], &[ 1, 2, 3 ] );
+    assert!( reader.remaining().is_empty() );
+
+    let mut output = [0_u8; 2];
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &serialized );
+    let error = reader.read_bytes_into_slice( &mut output ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+
+    let mut output = [0_u8; 8];
+    let length = speedy::read_bytes_from_buffer_into( Endianness::LittleEndian, &serialized, &mut output ).unwrap();
+    assert_eq!( &output[ ..length ], &[ 1, 2, 3 ] );
+
+    let mut output = [0_u8; 2];
+    let error = speedy::read_bytes_from_buffer_into( Endianness::LittleEndian, &serialized, &mut output ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+}
+
+#[test]
+fn non_zero_niche() {
+    use std::num::NonZeroU32;
+
+    let value = NonZeroU32::new( 7 );
+    assert_eq!( Writable::< Endianness >::bytes_needed( &value ), 4 );
+    assert_eq!( <Option< NonZeroU32 > as Readable< Endianness >>::minimum_bytes_needed(), 4 );
+
+    let deserialized: Option< NonZeroU32 > = Readable::read_from_buffer( Endianness::LittleEndian, &[ 0, 0, 0, 0 ] ).unwrap();
+    assert_eq!( deserialized, None );
+
+    let error = NonZeroU32::read_from_buffer( Endianness::LittleEndian, &[ 0, 0, 0, 0 ] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+}
+
+#[test]
+fn bit_reader() {
+    use speedy::BitReader;
+
+    let data = [ 0b101_00011, 0b1_0000000, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0xaa ];
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+    {
+        let mut bits = BitReader::new( &mut reader );
+        assert_eq!( bits.read_bits( 3 ).unwrap(), 5 );
+        assert_eq!( bits.read_bits( 5 ).unwrap(), 3 );
+        assert_eq!( bits.read_bits( 1 ).unwrap(), 1 );
+        bits.align();
+        assert_eq!( bits.read_bits( 64 ).unwrap(), 0x0102030405060708 );
+        assert_eq!( bits.read_bits( 65 ).unwrap_err().kind(), std::io::ErrorKind::InvalidInput );
+        assert_eq!( bits.read_bits( 4 ).unwrap(), 0xa );
+    }
+
+    assert!( reader.remaining().is_empty() );
+    assert_eq!( reader.read_u8().unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof );
+}
+
+#[test]
+fn hashing_reader() {
+    use speedy::HashingReader;
+
+    let mut data = vec![ "foo".to_owned(), "bar".to_owned() ].write_to_vec( Endianness::LittleEndian ).unwrap();
+    data.extend_from_slice( &0xc976020766fdd450_u64.to_le_bytes() );
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+    let digest = {
+        let mut hashing_reader = HashingReader::new( &mut reader );
+        assert_eq!( hashing_reader.digest(), 0xcbf29ce484222325 );
+        assert_eq!( hashing_reader.peek_u32().unwrap(), 2 );
+        assert_eq!( hashing_reader.digest(), 0xcbf29ce484222325 );
+
+        let strings: Vec< Cow< str > > = hashing_reader.read_value().unwrap();
+        assert_eq!( strings, [ "foo", "bar" ] );
+        hashing_reader.digest()
+    };
+
+    assert_eq!( reader.read_u64().unwrap(), digest );
+    assert!( reader.remaining().is_empty() );
+}
+
+#[test]
+fn reading_past_the_end_of_a_buffer() {
+    use std::io::ErrorKind;
+
+    let data = [ 1, 2 ];
+    let error = u32::read_from_buffer( Endianness::LittleEndian, &data ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+    assert_eq!( reader.read_u32().unwrap_err().kind(), ErrorKind::UnexpectedEof );
+    assert_eq!( reader.read_cow_bytes( 3 ).unwrap_err().kind(), ErrorKind::UnexpectedEof );
+    assert_eq!( reader.remaining(), &data );
+    assert_eq!( reader.read_u16().unwrap(), 0x0201 );
+
+    let error = u32::read_from_stream_buffered( Endianness::LittleEndian, &data[..] ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+}
+
+#[test]
+fn compile_time_endianness_contexts() {
+    use speedy::{LittleEndianCtx, BigEndianCtx};
+
+    let original: Vec< u32 > = vec![ 1, 0x01020304 ];
+    assert_eq!( original.write_to_vec( LittleEndianCtx ).unwrap(), original.write_to_vec( Endianness::LittleEndian ).unwrap() );
+    assert_eq!( original.write_to_vec( BigEndianCtx ).unwrap(), original.write_to_vec( Endianness::BigEndian ).unwrap() );
+
+    let serialized = original.write_to_vec( BigEndianCtx ).unwrap();
+    let deserialized: Vec< u32 > = Readable::read_from_buffer( BigEndianCtx, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+}
+
+struct HeaderAndTail {
+    header: u16,
+    tail: Vec< u8 >
+}
+
+impl< 'a, C: speedy::Context > Readable< 'a, C > for HeaderAndTail {
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> std::io::Result< Self > {
+        let header = reader.read_u16()?;
+        let tail = reader.read_remaining()?;
+        Ok( HeaderAndTail { header, tail } )
+    }
+}
+
+#[test]
+fn read_remaining() {
+    let data = [ 1, 0, 10, 11, 12 ];
+
+    let value = HeaderAndTail::read_from_buffer( Endianness::LittleEndian, &data ).unwrap();
+    assert_eq!( (value.header, value.tail), (1, vec![ 10, 11, 12 ]) );
+
+    let value = HeaderAndTail::read_from_stream( Endianness::LittleEndian, &data[..] ).unwrap();
+    assert_eq!( (value.header, value.tail), (1, vec![ 10, 11, 12 ]) );
+
+    let value = HeaderAndTail::read_from_stream_buffered( Endianness::LittleEndian, &data[..] ).unwrap();
+    assert_eq!( (value.header, value.tail), (1, vec![ 10, 11, 12 ]) );
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &data[ ..2 ] );
+    reader.read_u16().unwrap();
+    assert_eq!( reader.read_remaining().unwrap(), Vec::< u8 >::new() );
+
+    // The frame claims almost 2GB, which must not be allocated before it arrives.
+    let mut messages = HeaderAndTail::stream_from_reader( Endianness::LittleEndian, &[ 0xf0, 0xff, 0xff, 0x7f, 1, 0, 2 ][..] );
+    let error = messages.next().unwrap().err().unwrap();
+    assert_eq!( error.kind(), std::io::ErrorKind::UnexpectedEof );
+}
+
+struct TaggedByPeeking( u32, Vec< u8 > );
+
+impl< 'a, C: speedy::Context > Readable< 'a, C > for TaggedByPeeking {
+    fn read_from< R: Reader< 'a, C > >( reader: &mut R ) -> std::io::Result< Self > {
+        let tag = reader.peek_u32()?;
+        let body = reader.read_remaining()?;
+        Ok( TaggedByPeeking( tag, body ) )
+    }
+}
+
+#[test]
+fn peek() {
+    let data = [ 0, 0, 1, 2, 3 ];
+
+    let mut reader = BufferReader::new( Endianness::BigEndian, &data );
+    assert_eq!( reader.peek_u8().unwrap(), 0 );
+    assert_eq!( reader.peek_u32().unwrap(), 0x00000102 );
+    assert_eq!( reader.read_u16().unwrap(), 0 );
+    assert_eq!( reader.peek_u32().unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof );
+    assert_eq!( reader.read_remaining().unwrap(), [ 1, 2, 3 ] );
+
+    let value = TaggedByPeeking::read_from_buffer( Endianness::LittleEndian, &data ).unwrap();
+    assert_eq!( (value.0, value.1), (0x02010000, data.to_vec()) );
+
+    let value = TaggedByPeeking::read_from_stream_buffered( Endianness::LittleEndian, &data[..] ).unwrap();
+    assert_eq!( (value.0, value.1), (0x02010000, data.to_vec()) );
+
+    let value = TaggedByPeeking::read_from_stream_buffered( Endianness::LittleEndian, &data[ ..3 ] );
+    assert_eq!( value.err().unwrap().kind(), std::io::ErrorKind::UnexpectedEof );
+
+    let value = TaggedByPeeking::read_from_stream( Endianness::LittleEndian, &data[..] );
+    assert_eq!( value.err().unwrap().kind(), std::io::ErrorKind::Unsupported );
+}
+
+struct StrictContext;
+
+impl speedy::Context for StrictContext {
+    fn endianness( &self ) -> Endianness {
+        Endianness::LittleEndian
+    }
+
+    fn strict_bool( &self ) -> bool {
+        true
+    }
+}
+
+#[test]
+fn strict_bool() {
+    assert!( bool::read_from_buffer( Endianness::LittleEndian, &[0x02] ).unwrap() );
+    assert!( bool::read_from_buffer( StrictContext, &[0x01] ).unwrap() );
+    assert!( !bool::read_from_buffer( StrictContext, &[0x00] ).unwrap() );
+
+    let error = bool::read_from_buffer( StrictContext, &[0x02] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+}
+
+struct ElementByElement< 'a >( &'a [u32] );
+
+impl< 'r, C: speedy::Context > Writable< C > for ElementByElement< 'r > {
+    fn write_to< 'a, W: ?Sized + speedy::Writer< 'a, C > >( &'a self, writer: &mut W ) -> std::io::Result< () > {
+        writer.write_u32( self.0.len() as u32 )?;
+        for &value in self.0 {
+            writer.write_u32( value )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn bulk_write_matches_element_by_element() {
+    let original: Vec< u32 > = (0..1000).map( |index: u32| index.wrapping_mul( 0x01010101 ) ).collect();
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let expected = ElementByElement( &original ).write_to_vec( endianness ).unwrap();
+        assert_eq!( original.write_to_vec( endianness ).unwrap(), expected );
+
+        let mut stream = Vec::new();
+        original.write_to_stream( endianness, &mut stream ).unwrap();
+        assert_eq!( stream, expected );
+    }
+}
+
+#[test]
+fn result_with_infallible_error() {
+    use std::convert::Infallible;
+
+    let original: Result< u32, Infallible > = Ok( 0x01020304 );
+    let serialized = original.write_to_vec( Endianness::LittleEndian ).unwrap();
+    assert_eq!( serialized, [0, 4, 3, 2, 1] );
+
+    let deserialized: Result< u32, Infallible > = Readable::read_from_buffer( Endianness::LittleEndian, &serialized ).unwrap();
+    match deserialized {
+        Ok( value ) => assert_eq!( value, 0x01020304 ),
+        Err( never ) => match never {}
+    }
+
+    let error = Result::< u32, Infallible >::read_from_buffer( Endianness::LittleEndian, &[1] ).unwrap_err();
+    assert_eq!( error.kind(), std::io::ErrorKind::InvalidData );
+    assert_eq!( <Result< u32, Infallible > as Readable< Endianness >>::minimum_bytes_needed(), 1 );
+}
+
+struct VersionedContext {
+    version: u32
+}
+
+impl speedy::Context for VersionedContext {
+    fn endianness( &self ) -> Endianness {
+        Endianness::BigEndian
+    }
+}
+
+// The payload's width depends on the version of the format.
+#[derive(PartialEq, Debug)]
+struct VersionedPayload( u32 );
+
+impl< 'a > Readable< 'a, VersionedContext > for VersionedPayload {
+    fn read_from< R: Reader< 'a, VersionedContext > >( reader: &mut R ) -> std::io::Result< Self > {
+        match reader.context().version {
+            1 => Ok( VersionedPayload( reader.read_u8()? as u32 ) ),
+            _ => Ok( VersionedPayload( reader.read_u16()? as u32 ) )
+        }
+    }
+}
+
+#[test]
+fn read_value_with_a_different_context() {
+    let data = [ 0x12, 0x34, 0x56 ];
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &data );
+    let first: VersionedPayload = reader.read_value_with( VersionedContext { version: 1 } ).unwrap();
+    let second: VersionedPayload = reader.read_value_with( VersionedContext { version: 2 } ).unwrap();
+    assert_eq!( first, VersionedPayload( 0x12 ) );
+    assert_eq!( second, VersionedPayload( 0x3456 ) );
+    assert!( reader.remaining().is_empty() );
+}
+
+#[test]
+fn reading_with_a_custom_context() {
+    let data = [ 0x12, 0x34 ];
+    let v1 = || VersionedContext { version: 1 };
+    let v2 = || VersionedContext { version: 2 };
+
+    assert_eq!( VersionedPayload::read_from_buffer( v1(), &data ).unwrap(), VersionedPayload( 0x12 ) );
+    assert_eq!( VersionedPayload::read_from_buffer( v2(), &data ).unwrap(), VersionedPayload( 0x1234 ) );
+    assert_eq!( VersionedPayload::read_from_owned_buffer( v2(), data ).unwrap(), VersionedPayload( 0x1234 ) );
+    assert_eq!( VersionedPayload::read_from_stream( v1(), &data[..] ).unwrap(), VersionedPayload( 0x12 ) );
+    assert_eq!( VersionedPayload::read_from_stream_buffered( v2(), &data[..] ).unwrap(), VersionedPayload( 0x1234 ) );
+
+    let path = std::env::temp_dir().join( format!( "speedy-custom-context-test-{}", std::process::id() ) );
+    std::fs::write( &path, data ).unwrap();
+    let first = VersionedPayload::read_from_file( v1(), &path );
+    let second = VersionedPayload::read_from_file( v2(), &path );
+    std::fs::remove_file( &path ).unwrap();
+    assert_eq!( first.unwrap(), VersionedPayload( 0x12 ) );
+    assert_eq!( second.unwrap(), VersionedPayload( 0x1234 ) );
+}
+
+#[test]
+fn file_round_trip() {
+    let original: Vec< u16 > = vec![ 1, 2, 3 ];
+    let path = std::env::temp_dir().join( format!( "speedy-file-test-{}", std::process::id() ) );
+    original.write_to_file( Endianness::BigEndian, &path ).unwrap();
+    let serialized = std::fs::read( &path ).unwrap();
+    let deserialized: std::io::Result< Vec< u16 > > = Readable::read_from_file( Endianness::BigEndian, &path );
+    std::fs::remove_file( &path ).unwrap();
+
+    assert_eq!( serialized, original.write_to_vec( Endianness::BigEndian ).unwrap() );
+    assert_eq!( deserialized.unwrap(), original );
+}
+
+#[test]
+fn varint() {
+    use speedy::{VecWriter, Writer};
+
+    for &value in &[0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+        let mut writer = VecWriter::new( Endianness::BigEndian );
+        writer.write_varint_u64( value ).unwrap();
+        let serialized = writer.into_inner();
+
+        let mut reader = BufferReader::new( Endianness::BigEndian, &serialized );
+        assert_eq!( reader.read_varint_u64().unwrap(), value );
+        assert!( reader.remaining().is_empty() );
+    }
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &[0xac, 0x02] );
+    assert_eq!( reader.read_varint_u64().unwrap(), 300 );
+
+    let too_big = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &too_big );
+    assert_eq!( reader.read_varint_u64().unwrap_err().kind(), std::io::ErrorKind::InvalidData );
+
+    let mut reader = BufferReader::new( Endianness::LittleEndian, &[0x80] );
+    assert_eq!( reader.read_varint_u64().unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof );
+}
+
+// Little endian values with big endian length prefixes.
+struct MixedEndianContext;
+
+impl speedy::Context for MixedEndianContext {
+    fn endianness( &self ) -> Endianness {
+        Endianness::LittleEndian
+    }
+
+    fn length_endianness( &self ) -> Option< Endianness > {
+        Some( Endianness::BigEndian )
+    }
+}
+
+#[test]
+fn length_prefix_with_a_different_endianness() {
+    let original: Vec< u16 > = vec![ 1, 2 ];
+    let serialized = original.write_to_vec( MixedEndianContext ).unwrap();
+    assert_eq!( serialized, [0, 0, 0, 2, 1, 0, 2, 0] );
+    assert_eq!( Writable::< MixedEndianContext >::bytes_needed( &original ), serialized.len() );
+
+    let deserialized: Vec< u16 > = Readable::read_from_buffer( MixedEndianContext, &serialized ).unwrap();
+    assert_eq!( deserialized, original );
+
+    let mut framed = Vec::new();
+    original.write_framed_to_stream( MixedEndianContext, &mut framed ).unwrap();
+    assert_eq!( &framed[ ..4 ], &[0, 0, 0, 8] );
+
+    let messages: Vec< Vec< u16 > > = Readable::stream_from_reader( MixedEndianContext, &framed[..] ).collect::< Result< _, _ > >().unwrap();
+    assert_eq!( messages, [original] );
+}
+
+#[cfg(target_has_atomic = "64")]
+#[test]
+fn atomic_round_trip() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let original = AtomicU64::new( 0x0102030405060708 );
+        let serialized = original.write_to_vec( endianness ).unwrap();
+        assert_eq!( serialized, 0x0102030405060708_u64.write_to_vec( endianness ).unwrap() );
+
+        let deserialized: AtomicU64 = Readable::read_from_buffer( endianness, &serialized ).unwrap();
+        original.store( 0, Ordering::SeqCst );
+        assert_eq!( deserialized.load( Ordering::SeqCst ), 0x0102030405060708 );
+    }
+}
+
+#[test]
+fn reading_into_a_generic_collection() {
+    use std::collections::BTreeSet;
+    use std::io::ErrorKind;
+    use std::iter::FromIterator;
+
+    fn read_collection< U: FromIterator< u32 > >( serialized: &[u8] ) -> std::io::Result< U > {
+        BufferReader::new( Endianness::LittleEndian, serialized ).read_into_collection()
+    }
+
+    let serialized = vec![ 5_u32, 1, 5, 3 ].write_to_vec( Endianness::LittleEndian ).unwrap();
+    let vec: Vec< u32 > = read_collection( &serialized ).unwrap();
+    assert_eq!( vec, [5, 1, 5, 3] );
+
+    let set: BTreeSet< u32 > = read_collection( &serialized ).unwrap();
+    assert_eq!( set.into_iter().collect::< Vec< _ > >(), [1, 3, 5] );
+
+    let error = read_collection::< Vec< u32 > >( &serialized[ ..serialized.len() - 1 ] ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+}
+
+#[test]
+fn reading_a_collection_with_a_callback() {
+    use std::io::ErrorKind;
+
+    let values: Vec< u32 > = (1..=100).collect();
+    let serialized = values.write_to_vec( Endianness::BigEndian ).unwrap();
+
+    let mut reader = BufferReader::new( Endianness::BigEndian, &serialized );
+    let mut sum = 0_u64;
+    let count = reader.read_collection_with( |value: u32| sum += value as u64 ).unwrap();
+    assert_eq!( count, 100 );
+    assert_eq!( sum, 5050 );
+    assert!( reader.remaining().is_empty() );
+
+    let mut reader = BufferReader::new( Endianness::BigEndian, &serialized[ ..serialized.len() - 1 ] );
+    let error = reader.read_collection_with( |_: u32| {} ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+}
+
+#[test]
+fn reading_a_truncated_vec() {
+    use std::io::ErrorKind;
+
+    let mut data = 1000_u32.write_to_vec( Endianness::LittleEndian ).unwrap();
+    data.resize( 4 + 500, 0xaa );
+
+    let error = <Vec< u8 >>::read_from_buffer( Endianness::LittleEndian, &data ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+
+    let error = <Vec< u8 >>::read_from_stream( Endianness::LittleEndian, &data[..] ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+
+    let error = <Vec< u8 >>::read_from_stream_buffered( Endianness::LittleEndian, &data[..] ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+
+    // Bigger than what's allocated at once when the size of the input is unknown.
+    let mut data = 1_000_000_u32.write_to_vec( Endianness::LittleEndian ).unwrap();
+    data.resize( 4 + 3_000_000, 0xaa );
+
+    let error = <Vec< u32 >>::read_from_stream( Endianness::LittleEndian, &data[..] ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+
+    let mut vec = vec![ 1_u32, 2, 3 ];
+    let error = vec.read_into( Endianness::LittleEndian, &data ).unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+    assert!( vec.len() <= 3 );
+}
+
+// A stream which returns at most a few kilobytes per `read`, like a socket would.
+struct ChunkedStream< 'a > {
+    data: &'a [u8]
+}
+
+impl< 'a > std::io::Read for ChunkedStream< 'a > {
+    fn read( &mut self, output: &mut [u8] ) -> std::io::Result< usize > {
+        let count = std::cmp::min( std::cmp::min( output.len(), self.data.len() ), 3000 );
+        output[ ..count ].copy_from_slice( &self.data[ ..count ] );
+        self.data = &self.data[ count.. ];
+        Ok( count )
+    }
+}
+
+#[test]
+fn reading_a_big_vec_from_a_chunked_stream() {
+    let blob: Vec< u8 > = (0..10 * 1024 * 1024_u32).map( |index| (index % 251) as u8 ).collect();
+    let serialized = blob.write_to_vec( Endianness::LittleEndian ).unwrap();
+
+    let deserialized: Vec< u8 > = Readable::read_from_stream( Endianness::LittleEndian, ChunkedStream { data: &serialized } ).unwrap();
+    assert!( deserialized == blob );
+
+    let deserialized: Vec< u8 > = Readable::read_from_stream_buffered( Endianness::LittleEndian, ChunkedStream { data: &serialized } ).unwrap();
+    assert!( deserialized == blob );
+
+    let mut framed = Vec::new();
+    blob.write_framed_to_stream( Endianness::LittleEndian, &mut framed ).unwrap();
+    let mut messages = < Vec< u8 > >::stream_from_reader( Endianness::LittleEndian, ChunkedStream { data: &framed } );
+    assert!( messages.next().unwrap().unwrap() == blob );
+    assert!( messages.next().is_none() );
+}
+
+#[test]
+fn reading_a_frame_with_a_bogus_length() {
+    use std::io::ErrorKind;
+
+    // The frame and the `Vec` claim to be almost 4GB long even though the stream is tiny;
+    // this must fail once the stream ends instead of allocating everything up front.
+    let mut framed = 0xffff_fff0_u32.write_to_vec( Endianness::LittleEndian ).unwrap();
+    framed.extend( 0xffff_ff00_u32.write_to_vec( Endianness::LittleEndian ).unwrap() );
+    framed.resize( framed.len() + 1000, 0xaa );
+
+    let mut messages = < Vec< u8 > >::stream_from_reader( Endianness::LittleEndian, ChunkedStream { data: &framed } );
+    let error = messages.next().unwrap().unwrap_err();
+    assert_eq!( error.kind(), ErrorKind::UnexpectedEof );
+}
+
+#[test]
+fn borrowed_primitive_slices_are_written_like_vecs() {
+    let values: Vec< u32 > = (0..10_000).map( |index: u32| index.wrapping_mul( 0x01020304 ) ).collect();
+    let halves: Vec< i16 > = (0..10_000).map( |index: i32| (index * 7 - 30_000) as i16 ).collect();
+    let floats: Vec< f64 > = (0..10_000).map( |index| index as f64 * 1.5 ).collect();
+
+    for &endianness in &[Endianness::LittleEndian, Endianness::BigEndian] {
+        let slice: &[u32] = &values;
+        assert_eq!( slice.write_to_vec( endianness ).unwrap(), values.write_to_vec( endianness ).unwrap() );
+        assert_eq!( Writable::< Endianness >::bytes_needed( &slice ), 4 + values.len() * 4 );
+
+        let to_bytes = |value: u32| match endianness {
+            Endianness::LittleEndian => value.to_le_bytes(),
+            Endianness::BigEndian => value.to_be_bytes()
+        };
+        let expected: Vec< u8 > = std::iter::once( values.len() as u32 ).chain( values.iter().cloned() ).flat_map( to_bytes ).collect();
+        assert_eq!( slice.write_to_vec( endianness ).unwrap(), expected );
+
+        let slice: &[i16] = &halves;
+        assert_eq!( slice.write_to_vec( endianness ).unwrap(), halves.write_to_vec( endianness ).unwrap() );
+        let deserialized: Vec< i16 > = Readable::read_from_buffer( endianness, &slice.write_to_vec( endianness ).unwrap() ).unwrap();
+        assert_eq!( deserialized, halves );
+
+        let slice: &[f64] = &floats;
+        let deserialized: Vec< f64 > = Readable::read_from_buffer( endianness, &slice.write_to_vec( endianness ).unwrap() ).unwrap();
+        assert_eq!( deserialized, floats );
+    }
 }
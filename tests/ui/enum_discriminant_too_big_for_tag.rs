@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+#[speedy(tag_type = "u8")]
+enum Enum {
+    A = 1,
+    B = 300
+}
+
+fn main() {}
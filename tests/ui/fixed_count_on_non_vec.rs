@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+struct Hash {
+    #[speedy(fixed_count = 32)]
+    bytes: [u8; 32]
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+struct Inner {
+    a: u8
+}
+
+#[derive(Readable, Writable)]
+struct Struct {
+    #[speedy(flatten)]
+    inner: Inner
+}
+
+fn main() {}
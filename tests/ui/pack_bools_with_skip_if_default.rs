@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+#[speedy(pack_bools)]
+struct Flags {
+    is_visible: bool,
+    #[speedy(skip_if_default)]
+    is_hidden: bool
+}
+
+fn main() {}
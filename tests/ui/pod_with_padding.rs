@@ -0,0 +1,21 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Clone, Copy, Readable, Writable)]
+#[repr(C)]
+#[speedy(pod)]
+struct Padded {
+    a: u8,
+    b: u32
+}
+
+#[derive(Clone, Copy, Readable, Writable)]
+#[repr(C, packed(2))]
+#[speedy(pod)]
+struct PackedToTwo {
+    a: u8,
+    b: u32
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+use speedy::RawBytes;
+
+#[derive(Readable, Writable)]
+struct Envelope {
+    body: RawBytes,
+    checksum: u32
+}
+
+fn main() {}
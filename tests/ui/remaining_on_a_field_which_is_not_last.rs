@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+struct Packet {
+    #[speedy(remaining)]
+    payload: Vec< u8 >,
+    checksum: u32
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+struct Record {
+    id: u32,
+    #[speedy(since = 2)]
+    name: String
+}
+
+fn main() {}
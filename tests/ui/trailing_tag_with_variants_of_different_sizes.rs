@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+#[speedy(tag_position = "trailing")]
+enum Record {
+    Short( u16 ),
+    Long( u32 )
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+#[speedy(transparent)]
+struct Meters( f64, f64 );
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate speedy_derive;
+extern crate speedy;
+
+#[derive(Readable, Writable)]
+union Bits {
+    int: u32,
+    float: f32
+}
+
+fn main() {}